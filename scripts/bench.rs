@@ -0,0 +1,98 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Instant;
+
+use rusty_boy::emulator::Emulator;
+use rusty_boy::limiter::FRAME_RATE;
+use rusty_boy::log;
+use rusty_boy::{info, warn};
+
+/// Frames run when `--frames` is not given: one minute of emulated time.
+const DEFAULT_FRAMES: u32 = 3600;
+
+fn main() {
+    let mut args: Vec<String> = env::args().collect();
+    log::init_from_args(&mut args);
+    let mut rom = None;
+    let mut frames = DEFAULT_FRAMES;
+
+    let mut args_iter = args[1..].iter();
+    while let Some(arg) = args_iter.next() {
+        match arg.as_str() {
+            "--frames" => match args_iter.next().map(|value| value.parse()) {
+                Some(Ok(value)) if value > 0 => frames = value,
+                _ => {
+                    eprintln!("❌ --frames expects a positive number of frames");
+                    std::process::exit(1);
+                }
+            },
+            "help" | "--help" | "-h" => {
+                show_help();
+                return;
+            }
+            path if rom.is_none() && !path.starts_with("--") => rom = Some(PathBuf::from(path)),
+            _ => {
+                eprintln!("❌ Unknown argument: {}", arg);
+                show_help();
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let Some(rom) = rom else {
+        eprintln!("❌ Usage: cargo run --release --bin bench -- <ROM> [--frames <N>]");
+        std::process::exit(1);
+    };
+
+    let data = fs::read(&rom).unwrap_or_else(|e| {
+        eprintln!("❌ Failed to read {}: {}", rom.display(), e);
+        std::process::exit(1);
+    });
+    let mut emulator = Emulator::new(data).unwrap_or_else(|e| {
+        eprintln!("❌ Failed to load {}: {}", rom.display(), e);
+        std::process::exit(1);
+    });
+
+    if cfg!(debug_assertions) {
+        warn!("⚠️  Warning: Running a debug build; pass --release for meaningful numbers");
+    }
+    info!("⏱️  Running {} for {} frame(s)...", rom.display(), frames);
+
+    // No frame limiter, so frames run as fast as the host allows
+    let start = Instant::now();
+    emulator.run_frames(frames);
+    let elapsed = start.elapsed();
+
+    let ns_per_frame = elapsed.as_nanos() / frames as u128;
+    let fps = frames as f64 / elapsed.as_secs_f64();
+    info!("✅ {:.1} frames/s ({:.2}× real time), {} ns/frame", fps, fps / FRAME_RATE, ns_per_frame);
+
+    // The summary is printed even with --quiet so scripts can parse it
+    let name = rom.file_name().unwrap_or(rom.as_os_str()).to_string_lossy();
+    println!(
+        "bench rom={} frames={} elapsed_ns={} ns_per_frame={} fps={:.1}",
+        name,
+        frames,
+        elapsed.as_nanos(),
+        ns_per_frame,
+        fps
+    );
+}
+
+fn show_help() {
+    println!("⏱️  Emulation Benchmark");
+    println!();
+    println!("USAGE:");
+    println!("  cargo run --release --bin bench -- <ROM> [--frames <N>]");
+    println!();
+    println!("Runs a ROM headless and uncapped, with the PPU rendering every frame but no");
+    println!("window or audio output, and reports the emulated frames per second. The last");
+    println!("line is a machine-parseable summary:");
+    println!();
+    println!("  bench rom=<NAME> frames=<N> elapsed_ns=<NS> ns_per_frame=<NS> fps=<FPS>");
+    println!();
+    println!("OPTIONS:");
+    println!("  --frames <N>  Frames to run (default: {})", DEFAULT_FRAMES);
+    println!("  --quiet       Only print errors and the summary line");
+}
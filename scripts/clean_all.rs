@@ -0,0 +1,127 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use rusty_boy::config::Config;
+use rusty_boy::launcher::confirm;
+use rusty_boy::log;
+use rusty_boy::info;
+
+/// Bookmark and state files written to the project root by the other scripts.
+const ROOT_FILES: [&str; 3] = [".gb_ctr_bookmark", ".rust_docs_bookmark", ".rusty_boy_servers.pid"];
+
+fn main() {
+    let mut args: Vec<String> = env::args().collect();
+    log::init_from_args(&mut args);
+    let mut dry_run = false;
+    let mut force = false;
+    
+    for arg in &args[1..] {
+        match arg.as_str() {
+            "--dry-run" => dry_run = true,
+            "--force" => force = true,
+            "help" | "--help" | "-h" => {
+                show_help();
+                return;
+            }
+            _ => {
+                eprintln!("❌ Unknown argument: {}", arg);
+                show_help();
+                std::process::exit(1);
+            }
+        }
+    }
+    
+    let config = Config::load_or_exit();
+    let resources_dir = &config.resources_dir;
+    
+    if dry_run {
+        info!("🔍 Dry run, nothing will be deleted");
+    }
+    info!("🧹 Removing build artifacts...");
+    
+    let mut artifacts: Vec<PathBuf> = vec![
+        config.resource_path("gb-ctr").join("gbctr.pdf"),
+        config.resource_path("gb-ctr").join("config.json"),
+        config.resource_path("pandocs").join("book"),
+        config.resource_path("DMG-01").join("book").join("book"),
+        config.resource_path("pandocs").join("env").join(".requirements_installed"),
+        config.resource_path("pandocs").join("env"),
+    ];
+    artifacts.extend(ROOT_FILES.iter().map(PathBuf::from));
+    
+    let mut failures = 0;
+    for path in artifacts.iter().filter(|path| path.exists()) {
+        if !remove(path, dry_run) {
+            failures += 1;
+        }
+    }
+    
+    if resources_dir.exists() {
+        info!();
+        if dry_run {
+            info!("📦 Would remove the cloned resources (asks first unless --force is given):");
+            remove(resources_dir, true);
+        } else if force || confirm(&format!("📦 Also delete every clone in {}?", resources_dir.display())) {
+            if !remove(resources_dir, false) {
+                failures += 1;
+            }
+        } else {
+            info!("⏭️  Kept {} (pass --force to delete it without asking)", resources_dir.display());
+        }
+    }
+    
+    info!();
+    if failures > 0 {
+        eprintln!("❌ {} path(s) could not be removed", failures);
+        std::process::exit(1);
+    }
+    info!("✅ Clean completed!");
+}
+
+/// Deletes a file or directory tree, or only reports it during a dry run.
+/// Returns `false` if the deletion failed.
+fn remove(path: &Path, dry_run: bool) -> bool {
+    if dry_run {
+        info!("  🗑️  Would remove: {}", path.display());
+        return true;
+    }
+    
+    let result = if path.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    };
+    
+    match result {
+        Ok(()) => {
+            info!("  🗑️  Removed: {}", path.display());
+            true
+        }
+        Err(e) => {
+            eprintln!("  ⚠️  Failed to remove {}: {}", path.display(), e);
+            false
+        }
+    }
+}
+
+fn show_help() {
+    println!("🧹 Rusty Boy Cleanup");
+    println!();
+    println!("USAGE:");
+    println!("  cargo run --bin clean-all -- [OPTIONS]");
+    println!();
+    println!("Removes everything the scripts generate:");
+    println!("  • the GB-CTR PDF and its config.json");
+    println!("  • the mdbook book/ output of Pan Docs and DMG-01");
+    println!("  • the Pan Docs Python virtualenv and its install marker");
+    println!("  • the bookmark and server PID files in the project root");
+    println!("  • the cloned resources themselves (after confirmation)");
+    println!();
+    println!("OPTIONS:");
+    println!("  --dry-run           List what would be deleted without deleting anything");
+    println!("  --force             Delete the cloned resources without asking");
+    println!("  --quiet             Only print errors");
+    println!("  --verbose           Also print every external command before running it");
+    println!("  help                Show this help message");
+}
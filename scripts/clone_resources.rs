@@ -1,43 +1,452 @@
+use std::env;
 use std::process::Command;
 use std::fs;
+use std::io;
 use std::path::Path;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use rusty_boy::config::Config;
+use rusty_boy::log::{self, LogCommand};
+use rusty_boy::{info, warn};
+
+const MANIFEST_FILE: &str = "resources.toml";
+
+/// Repositories cloned when no manifest file is present.
+const DEFAULT_REPOSITORIES: [(&str, &str); 5] = [
+    ("DMG-01", "https://github.com/rylev/DMG-01.git"),
+    ("mooneye-gb", "https://github.com/Gekkio/mooneye-gb.git"),
+    ("gb-ctr", "https://github.com/Gekkio/gb-ctr.git"),
+    ("mooneye-test-suite", "https://github.com/Gekkio/mooneye-test-suite.git"),
+    ("pandocs", "https://github.com/gbdev/pandocs.git"),
+];
+
+#[derive(Deserialize)]
+struct Manifest {
+    #[serde(rename = "resource", default)]
+    resources: Vec<Resource>,
+}
+
+#[derive(Deserialize)]
+struct Resource {
+    name: String,
+    url: String,
+    #[serde(rename = "ref")]
+    git_ref: Option<String>,
+}
+
+/// Upper bound on the number of `git clone` processes running at once.
+const MAX_CONCURRENT_CLONES: usize = 4;
+
+/// Number of times a failed clone is retried before giving up.
+const CLONE_RETRIES: u32 = 3;
+/// Delay before the first retry; doubled after every further failure.
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+
+#[derive(Clone, Copy, PartialEq)]
+enum Mode {
+    /// Clone missing repositories and leave existing ones alone.
+    Clone,
+    /// Clone missing repositories and pull existing ones.
+    Update,
+}
+
+#[derive(Clone, Copy)]
+struct Options {
+    mode: Mode,
+    /// History depth passed to `git clone --depth`; `None` clones everything.
+    depth: Option<u32>,
+}
+
+enum CloneOutcome {
+    Cloned,
+    Skipped,
+    UpToDate,
+    Updated,
+    Failed(String),
+}
 
 fn main() {
-    let repositories = [
-        ("https://github.com/rylev/DMG-01.git", "DMG-01"),
-        ("https://github.com/Gekkio/mooneye-gb.git", "mooneye-gb"),
-        ("https://github.com/Gekkio/gb-ctr.git", "gb-ctr"),
-        ("https://github.com/Gekkio/mooneye-test-suite.git", "mooneye-test-suite"),
-        ("https://github.com/gbdev/pandocs.git", "pandocs"),
-    ];
-
-    // Create resources directory if it doesn't exist
-    let resources_dir = Path::new("resources");
+    let mut args: Vec<String> = env::args().collect();
+    log::init_from_args(&mut args);
+    let mut options = Options { mode: Mode::Clone, depth: None };
+    let mut selected = Vec::new();
+    
+    let mut args_iter = args[1..].iter();
+    while let Some(arg) = args_iter.next() {
+        match arg.as_str() {
+            "update" => options.mode = Mode::Update,
+            "--shallow" => options.depth = Some(1),
+            "--depth" => {
+                let depth = args_iter.next().and_then(|value| value.parse::<u32>().ok());
+                match depth {
+                    Some(depth) if depth > 0 => options.depth = Some(depth),
+                    _ => {
+                        eprintln!("--depth expects a positive number");
+                        std::process::exit(1);
+                    }
+                }
+            }
+            "help" | "--help" | "-h" => {
+                show_help();
+                return;
+            }
+            other if other.starts_with('-') => {
+                eprintln!("Unknown argument: {}", other);
+                show_help();
+                std::process::exit(1);
+            }
+            name => selected.push(name.to_string()),
+        }
+    }
+    
+    let repositories = select_resources(load_manifest(), &selected);
+    
+    // Create resources directory once, before any worker needs it
+    let config = Config::load_or_exit();
+    let resources_dir = config.resources_dir.as_path();
     if !resources_dir.exists() {
         fs::create_dir_all(resources_dir).expect("Failed to create resources directory");
     }
 
-    for (repo_url, folder_name) in repositories.iter() {
-        let target_path = resources_dir.join(folder_name);
-        
-        if target_path.exists() {
-            println!("Directory {} already exists, skipping clone", folder_name);
-            continue;
+    let outcomes = clone_all(resources_dir, &repositories, options);
+    print_summary(&repositories, &outcomes);
+
+    info!("Resource cloning complete!");
+}
+
+/// Clones every resource using a small pool of worker threads. Each worker
+/// pulls the next pending resource, so one failed clone never stops the rest.
+fn clone_all(resources_dir: &Path, repositories: &[Resource], options: Options) -> Vec<CloneOutcome> {
+    let next = AtomicUsize::new(0);
+    let outcomes = Mutex::new(Vec::with_capacity(repositories.len()));
+    let workers = MAX_CONCURRENT_CLONES.min(repositories.len());
+
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| {
+                loop {
+                    let index = next.fetch_add(1, Ordering::SeqCst);
+                    let Some(resource) = repositories.get(index) else {
+                        break;
+                    };
+                    let outcome = clone_resource(resources_dir, resource, options);
+                    outcomes.lock().unwrap().push((index, outcome));
+                }
+            });
         }
+    });
 
-        println!("Cloning {} into resources/{}", repo_url, folder_name);
-        
-        let output = Command::new("git")
-            .args(&["clone", repo_url, &target_path.to_string_lossy()])
-            .output()
-            .expect("Failed to execute git clone command");
+    let mut outcomes = outcomes.into_inner().unwrap();
+    outcomes.sort_by_key(|(index, _)| *index);
+    outcomes.into_iter().map(|(_, outcome)| outcome).collect()
+}
 
-        if output.status.success() {
-            println!("Successfully cloned {}", folder_name);
-        } else {
-            eprintln!("Failed to clone {}: {}", folder_name, String::from_utf8_lossy(&output.stderr));
+fn clone_resource(resources_dir: &Path, resource: &Resource, options: Options) -> CloneOutcome {
+    let target_path = resources_dir.join(&resource.name);
+    
+    if target_path.exists() && options.mode == Mode::Update {
+        return update_resource(&target_path, resource);
+    }
+    
+    if target_path.exists() {
+        info!("Directory {} already exists, skipping clone", resource.name);
+        if let Some(git_ref) = &resource.git_ref {
+            verify_checkout(&target_path, &resource.name, git_ref);
         }
+        return CloneOutcome::Skipped;
+    }
+
+    info!("Cloning {} into {}", resource.url, target_path.display());
+    
+    if let Err(e) = clone_with_retry(&target_path, resource, options.depth) {
+        return CloneOutcome::Failed(e);
+    }
+
+    info!("Successfully cloned {}", resource.name);
+    if let Some(git_ref) = &resource.git_ref
+        && let Err(e) = checkout_ref(&target_path, &resource.name, git_ref)
+    {
+        return CloneOutcome::Failed(e);
+    }
+
+    CloneOutcome::Cloned
+}
+
+/// Runs `git clone`, retrying with exponential backoff so a flaky network
+/// does not leave `resources/` incomplete. Whatever a failed attempt left
+/// behind is removed first, as git refuses to clone into a non-empty directory.
+fn clone_with_retry(target_path: &Path, resource: &Resource, depth: Option<u32>) -> Result<(), String> {
+    let mut backoff = INITIAL_BACKOFF;
+    let mut attempt = 1;
+
+    loop {
+        match clone_once(target_path, resource, depth) {
+            Ok(()) => return Ok(()),
+            Err(e) if attempt > CLONE_RETRIES => return Err(e),
+            Err(e) => {
+                warn!(
+                    "Clone of {} failed (attempt {}/{}), retrying in {}s: {}",
+                    resource.name,
+                    attempt,
+                    CLONE_RETRIES + 1,
+                    backoff.as_secs(),
+                    e.lines().last().unwrap_or_default()
+                );
+                if target_path.exists() {
+                    let _ = fs::remove_dir_all(target_path);
+                }
+                thread::sleep(backoff);
+                backoff *= 2;
+                attempt += 1;
+            }
+        }
+    }
+}
+
+fn clone_once(target_path: &Path, resource: &Resource, depth: Option<u32>) -> Result<(), String> {
+    let mut command = Command::new("git");
+    command.arg("clone");
+    if let Some(depth) = depth {
+        command.args(["--depth", &depth.to_string()]);
+    }
+    let output = command
+        .args([&resource.url, &*target_path.to_string_lossy()])
+        .logged()
+        .output()
+        .map_err(|e| format!("Failed to execute git clone command: {}", e))?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+fn print_summary(repositories: &[Resource], outcomes: &[CloneOutcome]) {
+    info!();
+    info!("Summary:");
+    for (resource, outcome) in repositories.iter().zip(outcomes) {
+        match outcome {
+            CloneOutcome::Cloned => info!("  ✅ {} cloned", resource.name),
+            CloneOutcome::Skipped => info!("  ⏭️  {} already present", resource.name),
+            CloneOutcome::UpToDate => info!("  ✅ {} already up to date", resource.name),
+            CloneOutcome::Updated => info!("  ⬆️  {} fast-forwarded", resource.name),
+            CloneOutcome::Failed(stderr) => eprintln!("  ❌ {} failed: {}", resource.name, stderr),
+        }
+    }
+    info!();
+}
+
+/// Fetches and fast-forwards an existing checkout. Pinned resources are moved
+/// to their requested ref instead, since a detached HEAD cannot be pulled.
+///
+/// Shallow clones (made with `--depth`/`--shallow`) are detected from git's own
+/// metadata and refreshed with a depth-1 fetch, because `git pull` cannot prove
+/// a fast-forward without the history it never downloaded.
+fn update_resource(repo_path: &Path, resource: &Resource) -> CloneOutcome {
+    info!("Updating {}", repo_path.display());
+    let before = rev_parse(repo_path, "HEAD");
+    let shallow = is_shallow(repo_path);
+
+    let fetch = if shallow {
+        run_git(repo_path, &["fetch", "--quiet", "--depth", "1"])
+    } else {
+        run_git(repo_path, &["fetch", "--quiet"])
+    };
+    if let Err(e) = fetch {
+        return CloneOutcome::Failed(format!("git fetch failed: {}", e));
     }
 
-    println!("Resource cloning complete!");
+    let result = match &resource.git_ref {
+        Some(git_ref) => checkout_ref(repo_path, &resource.name, git_ref)
+            .and_then(|()| fast_forward_branch(repo_path, git_ref)),
+        // `--keep` refuses to run if it would clobber local modifications
+        None if shallow => run_git(repo_path, &["reset", "--quiet", "--keep", "FETCH_HEAD"])
+            .map_err(|e| format!("cannot move shallow clone to the fetched commit: {}", e)),
+        None => run_git(repo_path, &["pull", "--ff-only", "--quiet"])
+            .map_err(|e| format!("cannot fast-forward (local changes or diverged history?): {}", e)),
+    };
+    if let Err(e) = result {
+        return CloneOutcome::Failed(e);
+    }
+
+    if rev_parse(repo_path, "HEAD") == before {
+        CloneOutcome::UpToDate
+    } else {
+        CloneOutcome::Updated
+    }
+}
+
+fn is_shallow(repo_path: &Path) -> bool {
+    Command::new("git")
+        .args(["rev-parse", "--is-shallow-repository"])
+        .current_dir(repo_path)
+        .logged()
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim() == "true")
+        .unwrap_or(false)
+}
+
+fn run_git(repo_path: &Path, args: &[&str]) -> Result<(), String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(repo_path)
+        .logged()
+        .output()
+        .map_err(|e| format!("Failed to execute git: {}", e))?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+fn checkout_ref(repo_path: &Path, name: &str, git_ref: &str) -> Result<(), String> {
+    let mut result = run_git(repo_path, &["checkout", "--quiet", git_ref]);
+
+    // A shallow clone usually lacks the pinned commit, so fetch just that ref
+    if result.is_err() && is_shallow(repo_path) {
+        result = run_git(repo_path, &["fetch", "--quiet", "--depth", "1", "origin", git_ref])
+            .and_then(|()| run_git(repo_path, &["checkout", "--quiet", "FETCH_HEAD"]));
+    }
+
+    match result {
+        Ok(()) => {
+            info!("Checked out {} at {}", name, git_ref);
+            Ok(())
+        }
+        Err(e) => Err(format!("Failed to check out {}: {}", git_ref, e)),
+    }
+}
+
+/// Fast-forwards the checkout to the fetched tip when `git_ref` names a
+/// remote branch. Tags and commit SHAs are left where they were checked out.
+fn fast_forward_branch(repo_path: &Path, git_ref: &str) -> Result<(), String> {
+    let remote_branch = format!("origin/{}", git_ref);
+    if rev_parse(repo_path, &format!("refs/remotes/{}", remote_branch)).is_none() {
+        return Ok(());
+    }
+    run_git(repo_path, &["merge", "--ff-only", "--quiet", &remote_branch])
+        .map_err(|e| format!("cannot fast-forward {} (local changes or diverged history?): {}", git_ref, e))
+}
+
+/// Warns when an existing checkout is not on the commit the manifest asks for.
+fn verify_checkout(repo_path: &Path, name: &str, git_ref: &str) {
+    let head = rev_parse(repo_path, "HEAD");
+    let expected = rev_parse(repo_path, &format!("{}^{{commit}}", git_ref));
+
+    match (head, expected) {
+        (Some(head), Some(expected)) if head == expected => {}
+        (Some(head), Some(_)) => {
+            warn!("Warning: {} is at {} but {} requests {}", name, &head[..head.len().min(12)], MANIFEST_FILE, git_ref);
+        }
+        (_, None) => {
+            warn!("Warning: {} does not know ref {} (try fetching)", name, git_ref);
+        }
+        (None, _) => {
+            warn!("Warning: could not determine the checked-out commit of {}", name);
+        }
+    }
+}
+
+fn rev_parse(repo_path: &Path, rev: &str) -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--verify", "--quiet", rev])
+        .current_dir(repo_path)
+        .logged()
+        .output()
+        .ok()?;
+
+    if output.status.success() {
+        Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        None
+    }
+}
+
+/// Narrows the manifest down to the resources named on the command line,
+/// keeping all of them when no names were given.
+fn select_resources(repositories: Vec<Resource>, names: &[String]) -> Vec<Resource> {
+    if names.is_empty() {
+        return repositories;
+    }
+
+    let unknown: Vec<&String> = names
+        .iter()
+        .filter(|name| !repositories.iter().any(|resource| &resource.name == *name))
+        .collect();
+    if !unknown.is_empty() {
+        for name in unknown {
+            eprintln!("Unknown resource: {}", name);
+        }
+        eprintln!("Available resources:");
+        for resource in &repositories {
+            eprintln!("  {}", resource.name);
+        }
+        std::process::exit(1);
+    }
+
+    repositories
+        .into_iter()
+        .filter(|resource| names.contains(&resource.name))
+        .collect()
+}
+
+/// Reads the repository list from the manifest, falling back to the built-in
+/// list when the file does not exist.
+/// Any other read error is reported rather than ignored.
+fn load_manifest() -> Vec<Resource> {
+    let content = match fs::read_to_string(MANIFEST_FILE) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            info!("No {} found, using the built-in repository list", MANIFEST_FILE);
+            return DEFAULT_REPOSITORIES
+                .iter()
+                .map(|(name, url)| Resource {
+                    name: name.to_string(),
+                    url: url.to_string(),
+                    git_ref: None,
+                })
+                .collect();
+        }
+        Err(e) => {
+            eprintln!("Failed to read {}: {}", MANIFEST_FILE, e);
+            std::process::exit(1);
+        }
+    };
+    
+    match toml::from_str::<Manifest>(&content) {
+        Ok(manifest) => manifest.resources,
+        Err(e) => {
+            eprintln!("Failed to parse {}: {}", MANIFEST_FILE, e);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn show_help() {
+    println!("Clone the external resources listed in {}", MANIFEST_FILE);
+    println!();
+    println!("USAGE:");
+    println!("  cargo run --bin clone-resources -- [COMMAND] [OPTIONS] [NAME...]");
+    println!();
+    println!("COMMANDS:");
+    println!("  (no args)           Clone missing resources, leave existing ones untouched");
+    println!("  update              Clone missing resources and fast-forward existing ones");
+    println!("  help                Show this help message");
+    println!();
+    println!("OPTIONS:");
+    println!("  --depth <N>         Clone only the last N commits of each repository");
+    println!("  --shallow           Same as --depth 1");
+    println!("  --quiet             Only print errors");
+    println!("  --verbose           Also print every external command before running it");
+    println!();
+    println!("Pass one or more resource names to process only those, e.g.");
+    println!("  cargo run --bin clone-resources -- update mooneye-test-suite pandocs");
 }
@@ -0,0 +1,232 @@
+use std::env;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+
+use rusty_boy::bus::Region;
+use rusty_boy::cartridge;
+use rusty_boy::cpu::{FLAG_C, FLAG_H, FLAG_N, FLAG_Z};
+use rusty_boy::debugger::{Debugger, Stop, Watch};
+use rusty_boy::disasm;
+use rusty_boy::emulator::Emulator;
+use rusty_boy::log;
+use rusty_boy::{info, warn};
+
+/// Bytes shown by `x` when no length is given.
+const DEFAULT_DUMP_LENGTH: usize = 0x40;
+
+fn main() {
+    let mut args: Vec<String> = env::args().collect();
+    log::init_from_args(&mut args);
+
+    let rom_path = match args.get(1).map(String::as_str) {
+        Some("help" | "--help" | "-h") => {
+            show_help();
+            return;
+        }
+        Some(path) if args.len() == 2 => path.to_string(),
+        _ => {
+            eprintln!("❌ Usage: cargo run --bin debugger -- <ROM>");
+            std::process::exit(1);
+        }
+    };
+
+    let data = fs::read(&rom_path).unwrap_or_else(|e| {
+        eprintln!("❌ Failed to read {}: {}", rom_path, e);
+        std::process::exit(1);
+    });
+    let mut emulator = Emulator::new(data).unwrap_or_else(|e| {
+        eprintln!("❌ Failed to load {}: {}", rom_path, e);
+        std::process::exit(1);
+    });
+    let save = cartridge::save_path(Path::new(&rom_path));
+    if let Err(e) = emulator.attach_save_file(&save) {
+        eprintln!("❌ Failed to load {}: {}", save.display(), e);
+        std::process::exit(1);
+    }
+    let mut debugger = Debugger::new(emulator);
+
+    // Ctrl+C stops a running `c` instead of quitting
+    let interrupted = debugger.interrupt_flag();
+    if let Err(e) = ctrlc::set_handler(move || interrupted.store(true, std::sync::atomic::Ordering::Relaxed)) {
+        warn!("⚠️  Warning: Failed to install Ctrl+C handler: {}", e);
+    }
+
+    info!("🐞 Debugging {} (type \"help\" for commands)", rom_path);
+    print_current(&debugger);
+
+    let stdin = io::stdin();
+    loop {
+        print!("> ");
+        let _ = io::stdout().flush();
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line).unwrap_or(0) == 0 {
+            break;
+        }
+
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            [] => {}
+            ["b" | "break", addr] => match parse_addr(addr) {
+                Some(addr) if debugger.add_breakpoint(addr) => println!("Breakpoint at {:04X}", addr),
+                Some(addr) => println!("Breakpoint at {:04X} already set", addr),
+                None => eprintln!("❌ Invalid address: {}", addr),
+            },
+            ["w" | "watch", addr, rest @ ..] => {
+                let watch = match rest {
+                    [] | ["rw"] => Some(Watch::ReadWrite),
+                    ["r"] => Some(Watch::Read),
+                    ["w"] => Some(Watch::Write),
+                    _ => None,
+                };
+                match (parse_addr(addr), watch) {
+                    (Some(addr), Some(watch)) => {
+                        debugger.add_watchpoint(addr, watch);
+                        println!("Watchpoint ({:?}) at {:04X}", watch, addr);
+                    }
+                    _ => eprintln!("❌ Usage: w <ADDR> [r|w|rw]"),
+                }
+            }
+            ["d" | "delete", addr] => match parse_addr(addr) {
+                Some(addr) => {
+                    let removed = debugger.remove_breakpoint(addr) | debugger.remove_watchpoint(addr);
+                    if !removed {
+                        println!("Nothing set at {:04X}", addr);
+                    }
+                }
+                None => eprintln!("❌ Invalid address: {}", addr),
+            },
+            ["l" | "list"] => {
+                for addr in debugger.breakpoints() {
+                    println!("Breakpoint  {:04X}", addr);
+                }
+                for (addr, watch) in debugger.watchpoints() {
+                    println!("Watchpoint  {:04X} ({:?})", addr, watch);
+                }
+            }
+            ["s" | "step", count @ ..] => {
+                let count = match count {
+                    [] => Some(1),
+                    [count] => count.parse().ok(),
+                    _ => None,
+                };
+                let Some(count) = count else {
+                    eprintln!("❌ Usage: s [COUNT]");
+                    continue;
+                };
+                for _ in 0..count {
+                    if let Some(stop) = debugger.step() {
+                        print_stop(stop);
+                        break;
+                    }
+                }
+                print_current(&debugger);
+            }
+            ["c" | "continue"] => {
+                print_stop(debugger.continue_until_break());
+                print_current(&debugger);
+            }
+            ["r" | "regs"] => print_registers(&debugger),
+            ["x" | "examine", addr, len @ ..] => {
+                let len = match len {
+                    [] => Some(DEFAULT_DUMP_LENGTH),
+                    [len] => len.parse().ok(),
+                    _ => None,
+                };
+                match (parse_addr(addr), len) {
+                    (Some(addr), Some(len)) => print_dump(addr, &debugger.emulator().read_range(addr, len)),
+                    _ => eprintln!("❌ Usage: x <ADDR> [LENGTH]"),
+                }
+            }
+            ["dump", name] => match Region::ALL.into_iter().find(|region| format!("{:?}", region).eq_ignore_ascii_case(name)) {
+                Some(region) => print_dump(*region.range().start(), &debugger.emulator().dump_region(region)),
+                None => eprintln!("❌ Unknown region: {} (vram, oam, wram, hram or io)", name),
+            },
+            ["q" | "quit"] => break,
+            ["h" | "help"] => show_commands(),
+            _ => eprintln!("❌ Unknown command: {} (type \"help\" for commands)", line.trim()),
+        }
+    }
+}
+
+/// Parses an address in hexadecimal, with or without a `0x` or `$` prefix.
+fn parse_addr(value: &str) -> Option<u16> {
+    let hex = value.strip_prefix("0x").or_else(|| value.strip_prefix('$')).unwrap_or(value);
+    u16::from_str_radix(hex, 16).ok()
+}
+
+/// Prints `bytes`, read from `start` on, 16 per line.
+fn print_dump(start: u16, bytes: &[u8]) {
+    for (line, chunk) in bytes.chunks(16).enumerate() {
+        let addr = start.wrapping_add(line as u16 * 16);
+        let hex: Vec<String> = chunk.iter().map(|byte| format!("{:02X}", byte)).collect();
+        let ascii: String = chunk
+            .iter()
+            .map(|&byte| if byte.is_ascii_graphic() || byte == b' ' { byte as char } else { '.' })
+            .collect();
+        println!("{:04X}  {:<47}  {}", addr, hex.join(" "), ascii);
+    }
+}
+
+fn print_stop(stop: Stop) {
+    match stop {
+        Stop::Breakpoint(addr) => println!("🛑 Breakpoint at {:04X}", addr),
+        Stop::Watchpoint(hit) => {
+            let access = if hit.write { "Write" } else { "Read" };
+            println!("👀 {} of {:02X} at {:04X}", access, hit.value, hit.addr);
+        }
+        Stop::Interrupted => println!("⏸️  Interrupted"),
+    }
+}
+
+/// Prints the instruction at PC.
+fn print_current(debugger: &Debugger) {
+    let emulator = debugger.emulator();
+    let pc = emulator.cpu().pc;
+    let (text, _) = disasm::disassemble(&emulator.read_range(pc, 3), pc);
+    let halted = if emulator.cpu().halted { "  (halted)" } else { "" };
+    println!("{:04X}  {}{}", pc, text, halted);
+}
+
+fn print_registers(debugger: &Debugger) {
+    let cpu = debugger.emulator().cpu();
+    let flags: String = [(FLAG_Z, 'Z'), (FLAG_N, 'N'), (FLAG_H, 'H'), (FLAG_C, 'C')]
+        .iter()
+        .map(|&(mask, name)| if cpu.flag(mask) { name } else { '-' })
+        .collect();
+    println!("AF={:04X} BC={:04X} DE={:04X} HL={:04X}", cpu.af(), cpu.bc(), cpu.de(), cpu.hl());
+    println!("SP={:04X} PC={:04X} flags={} IME={}", cpu.sp, cpu.pc, flags, cpu.ime as u8);
+    println!("cycles={}", debugger.emulator().bus().cycles());
+}
+
+fn show_commands() {
+    println!("COMMANDS:");
+    println!("  b <ADDR>           Set a breakpoint");
+    println!("  w <ADDR> [r|w|rw]  Watch reads and/or writes of an address (default: rw)");
+    println!("  d <ADDR>           Delete the breakpoint or watchpoint at an address");
+    println!("  l                  List breakpoints and watchpoints");
+    println!("  s [COUNT]          Step one or COUNT instructions");
+    println!("  c                  Continue until a breakpoint or watchpoint (Ctrl+C stops)");
+    println!("  regs               Show the CPU registers");
+    println!("  x <ADDR> [LENGTH]  Dump memory (default: {} bytes)", DEFAULT_DUMP_LENGTH);
+    println!("  dump <REGION>      Dump vram, oam, wram, hram or io");
+    println!("  q                  Quit");
+    println!();
+    println!("Addresses are hexadecimal, e.g. 0150, 0x0150 or $0150.");
+}
+
+fn show_help() {
+    println!("🐞 Interactive Debugger");
+    println!();
+    println!("USAGE:");
+    println!("  cargo run --bin debugger -- <ROM>");
+    println!();
+    println!("Loads a ROM paused at the entry point and reads commands from stdin.");
+    println!("Battery-backed RAM is loaded from and saved to the ROM path with a .sav");
+    println!("extension.");
+    println!();
+    println!("OPTIONS:");
+    println!("  --quiet             Only print errors");
+    println!();
+    show_commands();
+}
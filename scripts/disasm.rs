@@ -0,0 +1,94 @@
+use std::env;
+use std::fs;
+
+use rusty_boy::cartridge::mbc::ROM_BANK_SIZE;
+use rusty_boy::disasm;
+use rusty_boy::log;
+
+/// Bytes disassembled when `--length` is not given.
+const DEFAULT_LENGTH: usize = 0x40;
+
+fn main() {
+    let mut args: Vec<String> = env::args().collect();
+    log::init_from_args(&mut args);
+    let mut rom_path = None;
+    let mut start = 0x0100;
+    let mut length = DEFAULT_LENGTH;
+
+    let mut args_iter = args[1..].iter();
+    while let Some(arg) = args_iter.next() {
+        match arg.as_str() {
+            "--start" | "--length" => {
+                let Some(value) = args_iter.next().and_then(|value| parse_number(value)) else {
+                    eprintln!("❌ {} expects a number, e.g. 0x150", arg);
+                    std::process::exit(1);
+                };
+                if arg == "--start" {
+                    start = value;
+                } else {
+                    length = value;
+                }
+            }
+            "help" | "--help" | "-h" => {
+                show_help();
+                return;
+            }
+            path if rom_path.is_none() && !path.starts_with("--") => rom_path = Some(path.to_string()),
+            _ => {
+                eprintln!("❌ Unknown argument: {}", arg);
+                show_help();
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let Some(rom_path) = rom_path else {
+        eprintln!("❌ Usage: cargo run --bin disasm -- <ROM> [--start <OFFSET>] [--length <BYTES>]");
+        std::process::exit(1);
+    };
+    let rom = fs::read(&rom_path).unwrap_or_else(|e| {
+        eprintln!("❌ Failed to read {}: {}", rom_path, e);
+        std::process::exit(1);
+    });
+    if start >= rom.len() {
+        eprintln!("❌ Offset {:#X} is past the end of the {} byte ROM", start, rom.len());
+        std::process::exit(1);
+    }
+
+    let end = rom.len().min(start + length);
+    let mut offset = start;
+    while offset < end {
+        // Banks past the first are mapped at 0x4000..=0x7FFF
+        let bank = offset / ROM_BANK_SIZE;
+        let addr = if bank == 0 { offset } else { ROM_BANK_SIZE + offset % ROM_BANK_SIZE } as u16;
+        let (text, size) = disasm::disassemble(&rom[offset..end], addr);
+
+        let bytes: Vec<String> = rom[offset..offset + size].iter().map(|byte| format!("{:02X}", byte)).collect();
+        println!("{:02X}:{:04X}  {:<9} {}", bank, addr, bytes.join(" "), text);
+        offset += size;
+    }
+}
+
+/// Parses a decimal number or a hexadecimal one prefixed with `0x` or `$`.
+fn parse_number(value: &str) -> Option<usize> {
+    match value.strip_prefix("0x").or_else(|| value.strip_prefix('$')) {
+        Some(hex) => usize::from_str_radix(hex, 16).ok(),
+        None => value.parse().ok(),
+    }
+}
+
+fn show_help() {
+    println!("🔍 ROM Disassembler");
+    println!();
+    println!("USAGE:");
+    println!("  cargo run --bin disasm -- <ROM> [--start <OFFSET>] [--length <BYTES>]");
+    println!();
+    println!("Disassembles a region of a ROM file. Each line shows the bank and address");
+    println!("the instruction is mapped at, its bytes and its mnemonic.");
+    println!();
+    println!("OPTIONS:");
+    println!("  --start <OFFSET>  File offset to start at (default: 0x100, the entry point)");
+    println!("  --length <BYTES>  Number of bytes to disassemble (default: {:#X})", DEFAULT_LENGTH);
+    println!();
+    println!("Numbers are decimal, or hexadecimal with a 0x or $ prefix.");
+}
@@ -0,0 +1,130 @@
+use std::env;
+
+use rusty_boy::launcher::command_exists;
+use rusty_boy::log;
+use rusty_boy::info;
+
+struct Tool {
+    name: &'static str,
+    used_by: &'static str,
+    install: &'static str,
+    required: bool,
+}
+
+const TOOLS: &[Tool] = &[
+    Tool {
+        name: "git",
+        used_by: "clone-resources",
+        install: "https://git-scm.com/downloads",
+        required: true,
+    },
+    Tool {
+        name: "cargo",
+        used_by: "all binaries, pandocs preprocessors",
+        install: "https://rustup.rs",
+        required: true,
+    },
+    Tool {
+        name: "rustup",
+        used_by: "rust-docs",
+        install: "https://rustup.rs",
+        required: true,
+    },
+    Tool {
+        name: "mdbook",
+        used_by: "launch-pandocs, launch-dmg01",
+        install: "cargo install mdbook",
+        required: true,
+    },
+    Tool {
+        name: "python3",
+        used_by: "launch-pandocs",
+        install: "https://www.python.org/downloads",
+        required: true,
+    },
+    Tool {
+        name: "just",
+        used_by: "gb-ctr-book build",
+        install: "brew install just",
+        required: false,
+    },
+    Tool {
+        name: "typst",
+        used_by: "gb-ctr-book build",
+        install: "brew install typst",
+        required: false,
+    },
+];
+
+fn main() {
+    let mut args: Vec<String> = env::args().collect();
+    log::init_from_args(&mut args);
+    
+    if args.len() > 1 {
+        match args[1].as_str() {
+            "help" | "--help" | "-h" => {
+                show_help();
+                return;
+            }
+            _ => {
+                eprintln!("❌ Unknown argument: {}", args[1]);
+                show_help();
+                std::process::exit(1);
+            }
+        }
+    }
+    
+    info!("🩺 Checking Rusty Boy prerequisites...");
+    info!();
+    
+    let mut missing_required = Vec::new();
+    let mut missing = Vec::new();
+    
+    for tool in TOOLS {
+        let found = command_exists(tool.name);
+        let marker = match (found, tool.required) {
+            (true, _) => "✅",
+            (false, true) => "❌",
+            (false, false) => "⚠️ ",
+        };
+        info!("  {} {:<10} {}", marker, tool.name, tool.used_by);
+        
+        if !found {
+            if tool.required {
+                missing_required.push(tool.name);
+            }
+            missing.push(tool);
+        }
+    }
+    
+    info!();
+    if missing.is_empty() {
+        info!("✅ All prerequisites are installed!");
+        return;
+    }
+    
+    info!("📦 Install the missing tools with:");
+    for tool in &missing {
+        let note = if tool.required { "" } else { " (optional)" };
+        info!("  • {}{}: {}", tool.name, note, tool.install);
+    }
+    
+    if !missing_required.is_empty() {
+        info!();
+        eprintln!("❌ Missing required tool(s): {}", missing_required.join(", "));
+        std::process::exit(1);
+    }
+}
+
+fn show_help() {
+    println!("🩺 Rusty Boy Prerequisite Checker");
+    println!();
+    println!("USAGE:");
+    println!("  cargo run --bin doctor -- [--quiet|--verbose]");
+    println!();
+    println!("Checks that every external tool used by the project scripts is on the PATH");
+    println!("and prints the install command for each missing one.");
+    println!();
+    println!("Exits with a non-zero status if any required tool is missing, so it can");
+    println!("gate CI. Tools marked optional are only needed to build the GB-CTR PDF.");
+}
@@ -1,13 +1,26 @@
+use std::collections::BTreeMap;
 use std::env;
 use std::fs;
 use std::path::Path;
-use std::process::Command;
+use std::process::{Command, Stdio};
+use std::time::SystemTime;
+
+use rusty_boy::config::Config;
+use rusty_boy::launcher::{
+    BROWSER_ENV, NO_OPEN_FLAG, confirm, open_in_browser, open_with, preferred_browser, take_flag,
+};
+use rusty_boy::log::{self, LogCommand};
+use rusty_boy::{info, warn};
 
 const BOOKMARK_FILE: &str = ".gb_ctr_bookmark";
-const GB_CTR_DIR: &str = "resources/gb-ctr";
+const DEFAULT_BOOKMARK: &str = "default";
+const GB_CTR_RESOURCE: &str = "gb-ctr";
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
+    let mut args: Vec<String> = env::args().collect();
+    log::init_from_args(&mut args);
+    let rebuild = take_flag(&mut args, "--rebuild");
+    let no_open = take_flag(&mut args, NO_OPEN_FLAG);
     
     if args.len() > 1 {
         match args[1].as_str() {
@@ -15,21 +28,29 @@ fn main() {
                 build_book();
             }
             "open" => {
-                open_book();
+                let name = args.get(2).map_or(DEFAULT_BOOKMARK, String::as_str);
+                open_book(name, rebuild, no_open);
             }
             "save" => {
-                if args.len() < 3 {
-                    eprintln!("❌ Usage: cargo run --bin gb-ctr-book save <page_number>");
-                    eprintln!("Example: cargo run --bin gb-ctr-book save 25");
-                    std::process::exit(1);
-                }
-                if let Ok(page) = args[2].parse::<u32>() {
-                    save_bookmark(page);
+                let (name, page) = match args.len() {
+                    3 => (DEFAULT_BOOKMARK, &args[2]),
+                    4 => (args[2].as_str(), &args[3]),
+                    _ => {
+                        eprintln!("❌ Usage: cargo run --bin gb-ctr-book save [name] <page_number>");
+                        eprintln!("Example: cargo run --bin gb-ctr-book save ppu 25");
+                        std::process::exit(1);
+                    }
+                };
+                if let Ok(page) = page.parse::<u32>() {
+                    save_bookmark(name, page);
                 } else {
-                    eprintln!("❌ Invalid page number: {}", args[2]);
+                    eprintln!("❌ Invalid page number: {}", page);
                     std::process::exit(1);
                 }
             }
+            "bookmarks" => {
+                list_bookmarks();
+            }
             "clean" => {
                 clean_build();
             }
@@ -44,17 +65,13 @@ fn main() {
         }
     } else {
         // No arguments - open existing book, resuming from bookmark if available
-        let bookmark = load_bookmark();
-        if let Some(page) = bookmark {
-            println!("📖 Last bookmarked page: {}", page);
-        }
-        open_book();
+        open_book(DEFAULT_BOOKMARK, rebuild, no_open);
     }
 }
 
 fn get_gb_ctr_path() -> String {
     let current_dir = env::current_dir().expect("Failed to get current directory");
-    let gb_ctr_path = current_dir.join(GB_CTR_DIR);
+    let gb_ctr_path = current_dir.join(Config::load_or_exit().resource_path(GB_CTR_RESOURCE));
     
     if !gb_ctr_path.exists() {
         eprintln!("❌ GB-CTR directory not found at: {}", gb_ctr_path.display());
@@ -66,17 +83,18 @@ fn get_gb_ctr_path() -> String {
 }
 
 fn build_book() {
-    println!("🔨 Building Game Boy Complete Technical Reference...");
+    info!("🔨 Building Game Boy Complete Technical Reference...");
     let gb_ctr_path = get_gb_ctr_path();
     
     let result = Command::new("just")
         .arg("build")
         .current_dir(&gb_ctr_path)
+        .logged()
         .status();
     
     match result {
         Ok(status) if status.success() => {
-            println!("✅ Book built successfully!");
+            info!("✅ Book built successfully!");
         }
         Ok(_) => {
             eprintln!("❌ Failed to build book");
@@ -91,96 +109,201 @@ fn build_book() {
     }
 }
 
-fn open_book() {
+fn open_book(bookmark: &str, rebuild: bool, no_open: bool) {
     let gb_ctr_path = get_gb_ctr_path();
     let pdf_path = Path::new(&gb_ctr_path).join("gbctr.pdf");
     
+    if !pdf_path.exists() && rebuild {
+        build_book();
+    } else if pdf_is_stale(Path::new(&gb_ctr_path), &pdf_path) {
+        warn!("⚠️  The Typst sources are newer than gbctr.pdf");
+        if rebuild || confirm("🔨 Rebuild the book before opening?") {
+            build_book();
+        } else {
+            info!("📖 Opening the existing (possibly outdated) PDF");
+        }
+    }
+    
     if !pdf_path.exists() {
         eprintln!("❌ PDF not found at: {}", pdf_path.display());
         eprintln!("Run 'cargo run --bin gb-ctr-book build' first to build the book.");
         std::process::exit(1);
     }
     
-    open_pdf(&pdf_path.to_string_lossy());
+    if no_open {
+        info!("✅ PDF ready at: {}", pdf_path.display());
+        return;
+    }
+    
+    // Resume from the bookmarked page, or start at the beginning
+    let page = load_bookmark(bookmark).unwrap_or(1);
+    open_pdf(&pdf_path.to_string_lossy(), page);
+}
+
+/// Returns `true` if any `.typ` source under `gb_ctr_path` was modified after
+/// the PDF was built.
+fn pdf_is_stale(gb_ctr_path: &Path, pdf_path: &Path) -> bool {
+    let Ok(pdf_modified) = pdf_path.metadata().and_then(|metadata| metadata.modified()) else {
+        return false;
+    };
+    
+    newest_typst_source(gb_ctr_path).is_some_and(|source_modified| source_modified > pdf_modified)
+}
+
+fn newest_typst_source(dir: &Path) -> Option<SystemTime> {
+    let mut newest = None;
+    
+    for entry in fs::read_dir(dir).ok()?.flatten() {
+        let path = entry.path();
+        let is_hidden = entry.file_name().to_string_lossy().starts_with('.');
+        
+        let modified = if path.is_dir() && !is_hidden {
+            newest_typst_source(&path)
+        } else if path.extension().is_some_and(|ext| ext == "typ") {
+            entry.metadata().and_then(|metadata| metadata.modified()).ok()
+        } else {
+            None
+        };
+        
+        newest = newest.max(modified);
+    }
+    
+    newest
 }
 
-fn open_pdf(pdf_path: &str) {
-    println!("📚 Opening Game Boy Complete Technical Reference in browser...");
-    
-    let file_url = format!("file://{}", pdf_path);
-    
-    let result = if cfg!(target_os = "macos") {
-        // On macOS, use Google Chrome to open the PDF
-        Command::new("open")
-            .arg("-a")
-            .arg("Google Chrome")
-            .arg(&file_url)
-            .status()
-    } else if cfg!(target_os = "linux") {
-        Command::new("xdg-open").arg(&file_url).status()
-    } else if cfg!(target_os = "windows") {
-        Command::new("cmd").args(&["/C", "start", &file_url]).status()
+fn open_pdf(pdf_path: &str, page: u32) {
+    info!("📚 Opening Game Boy Complete Technical Reference in browser at page {}...", page);
+    
+    // Browser PDF viewers jump to the page given in the `#page=N` fragment
+    let file_url = format!("file://{}#page={}", pdf_path, page);
+    
+    let result = if cfg!(target_os = "macos") && preferred_browser().is_none() {
+        // On macOS, use Google Chrome to open the PDF unless a browser is configured
+        if chrome_installed() {
+            info!("🌐 Using Google Chrome");
+            open_with("Google Chrome", &file_url)
+        } else {
+            // The default PDF handler (usually Preview) ignores `#page=N`
+            info!("🌐 Google Chrome not found, using the system default PDF viewer");
+            info!("📖 Jump to page {} manually", page);
+            open_in_browser(pdf_path)
+        }
     } else {
-        eprintln!("❌ Unsupported operating system");
-        std::process::exit(1);
+        open_in_browser(&file_url)
     };
     
     match result {
-        Ok(status) if status.success() => {
-            println!("✅ Book opened successfully in browser!");
+        Ok(()) => {
+            info!("✅ Book opened successfully in browser!");
             show_usage_tips();
         }
-        _ => {
-            eprintln!("❌ Failed to open PDF in browser");
+        Err(e) => {
+            eprintln!("❌ Failed to open PDF in browser: {}", e);
             eprintln!("You can manually open: {}", file_url);
         }
     }
 }
 
-fn save_bookmark(page: u32) {
+/// Asks Launch Services whether Google Chrome is installed (macOS only).
+fn chrome_installed() -> bool {
+    Command::new("open")
+        .args(["-Ra", "Google Chrome"])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .logged()
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+fn save_bookmark(name: &str, page: u32) {
+    if name.is_empty() || name.contains(['=', '\n']) {
+        eprintln!("❌ Invalid bookmark name: {:?}", name);
+        std::process::exit(1);
+    }
+    
+    let mut bookmarks = load_bookmarks();
+    bookmarks.insert(name.to_string(), page);
+    
+    let content: String = bookmarks
+        .iter()
+        .map(|(name, page)| format!("{}={}\n", name, page))
+        .collect();
+    
     let current_dir = env::current_dir().expect("Failed to get current directory");
     let bookmark_path = current_dir.join(BOOKMARK_FILE);
     
-    if let Err(e) = fs::write(&bookmark_path, page.to_string()) {
-        eprintln!("⚠️  Warning: Failed to save bookmark: {}", e);
+    if let Err(e) = fs::write(&bookmark_path, content) {
+        warn!("⚠️  Warning: Failed to save bookmark: {}", e);
     } else {
-        println!("📖 Bookmarked page: {}", page);
+        info!("📖 Bookmarked page {} as '{}'", page, name);
+    }
+}
+
+fn load_bookmark(name: &str) -> Option<u32> {
+    let page = load_bookmarks().get(name).copied();
+    
+    match page {
+        Some(page) => info!("📚 Resuming from bookmark '{}': page {}", name, page),
+        None if name != DEFAULT_BOOKMARK => warn!("⚠️  No bookmark named '{}', starting at page 1", name),
+        None => {}
     }
+    
+    page
 }
 
-fn load_bookmark() -> Option<u32> {
+/// Reads every `name=page` line of the bookmark file. A bare page number (the
+/// old single-bookmark format) is read as the `default` bookmark.
+fn load_bookmarks() -> BTreeMap<String, u32> {
     let current_dir = env::current_dir().expect("Failed to get current directory");
     let bookmark_path = current_dir.join(BOOKMARK_FILE);
+    let mut bookmarks = BTreeMap::new();
     
     if bookmark_path.exists() {
         match fs::read_to_string(&bookmark_path) {
             Ok(content) => {
-                let content = content.trim();
-                if let Ok(page) = content.parse::<u32>() {
-                    println!("📚 Resuming from bookmarked page: {}", page);
-                    return Some(page);
+                for line in content.lines().map(str::trim).filter(|line| !line.is_empty()) {
+                    let (name, page) = line.split_once('=').unwrap_or((DEFAULT_BOOKMARK, line));
+                    if let Ok(page) = page.trim().parse::<u32>() {
+                        bookmarks.insert(name.trim().to_string(), page);
+                    }
                 }
             }
             Err(e) => {
-                eprintln!("⚠️  Warning: Failed to read bookmark: {}", e);
+                warn!("⚠️  Warning: Failed to read bookmark: {}", e);
             }
         }
     }
     
-    None
+    bookmarks
+}
+
+fn list_bookmarks() {
+    let bookmarks = load_bookmarks();
+    
+    if bookmarks.is_empty() {
+        println!("📭 No bookmarks saved yet");
+        println!("Save one with: cargo run --bin gb-ctr-book save <name> <page>");
+        return;
+    }
+    
+    println!("📚 Saved bookmarks:");
+    for (name, page) in &bookmarks {
+        println!("  {:<16} page {}", name, page);
+    }
 }
 
 fn clean_build() {
-    println!("🧹 Cleaning build artifacts...");
+    info!("🧹 Cleaning build artifacts...");
     let gb_ctr_path = get_gb_ctr_path();
     
     // Remove the PDF file
     let pdf_path = Path::new(&gb_ctr_path).join("gbctr.pdf");
     if pdf_path.exists() {
         if let Err(e) = fs::remove_file(&pdf_path) {
-            eprintln!("⚠️  Warning: Failed to remove PDF: {}", e);
+            warn!("⚠️  Warning: Failed to remove PDF: {}", e);
         } else {
-            println!("🗑️  Removed: gbctr.pdf");
+            info!("🗑️  Removed: gbctr.pdf");
         }
     }
     
@@ -188,13 +311,13 @@ fn clean_build() {
     let config_path = Path::new(&gb_ctr_path).join("config.json");
     if config_path.exists() {
         if let Err(e) = fs::remove_file(&config_path) {
-            eprintln!("⚠️  Warning: Failed to remove config.json: {}", e);
+            warn!("⚠️  Warning: Failed to remove config.json: {}", e);
         } else {
-            println!("🗑️  Removed: config.json");
+            info!("🗑️  Removed: config.json");
         }
     }
     
-    println!("✅ Clean completed!");
+    info!("✅ Clean completed!");
 }
 
 fn show_help() {
@@ -204,32 +327,45 @@ fn show_help() {
     println!("  cargo run --bin gb-ctr-book [COMMAND] [ARGS]");
     println!();
     println!("COMMANDS:");
-    println!("  (no args)           Open existing book from last bookmark");
+    println!("  (no args)           Open existing book from the default bookmark");
     println!("  build               Build the book (PDF)");
-    println!("  open                Open the existing PDF in Google Chrome");
-    println!("  save <number>       Save a page bookmark without opening");
+    println!("  open [name]         Open the existing PDF at a bookmarked page (Google Chrome");
+    println!("                      on macOS when installed)");
+    println!("  save [name] <page>  Save a page bookmark without opening");
+    println!("  bookmarks           List all saved bookmarks");
     println!("  clean               Remove build artifacts");
     println!("  help                Show this help message");
     println!();
+    println!("OPTIONS:");
+    println!("  --no-open           Build/check the PDF without opening it (for CI)");
+    println!("  --rebuild           Rebuild without asking when the Typst sources are newer");
+    println!("                      than the PDF (or the PDF is missing)");
+    println!("  --quiet             Only print errors");
+    println!("  --verbose           Also print every external command before running it");
+    println!();
     println!("EXAMPLES:");
     println!("  cargo run --bin gb-ctr-book");
     println!("  cargo run --bin gb-ctr-book build");
     println!("  cargo run --bin gb-ctr-book open");
     println!("  cargo run --bin gb-ctr-book save 42");
+    println!("  cargo run --bin gb-ctr-book save ppu 120");
+    println!("  cargo run --bin gb-ctr-book open ppu");
     println!();
     println!("REQUIREMENTS:");
     println!("  • just (install with: brew install just)");
     println!("  • typst (install with: brew install typst)");
     println!();
     println!("📁 Bookmark file: {}", BOOKMARK_FILE);
-    println!("📂 Book directory: {}", GB_CTR_DIR);
+    println!("📂 Book directory: {}", Config::load_or_exit().resource_path(GB_CTR_RESOURCE).display());
+    println!("🌐 Set {} to open the PDF with another browser", BROWSER_ENV);
 }
 
 fn show_usage_tips() {
-    println!();
-    println!("💡 Usage tips:");
-    println!("  • Use 'cargo run --bin gb-ctr-book save <N>' to bookmark page N");
-    println!("  • In Chrome PDF viewer, press Ctrl+G to 'Go to page' for quick navigation");
-    println!("  • The bookmark file ({}) can be committed to git", BOOKMARK_FILE);
-    println!("  • Run 'cargo run --bin gb-ctr-book clean' to remove build files");
+    info!();
+    info!("💡 Usage tips:");
+    info!("  • Use 'cargo run --bin gb-ctr-book save <N>' to bookmark page N");
+    info!("  • Keep several places with named bookmarks: 'save timers 80', 'open timers'");
+    info!("  • The book opens directly at the bookmarked page");
+    info!("  • The bookmark file ({}) can be committed to git", BOOKMARK_FILE);
+    info!("  • Run 'cargo run --bin gb-ctr-book clean' to remove build files");
 }
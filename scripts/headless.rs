@@ -0,0 +1,156 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use rusty_boy::cartridge;
+use rusty_boy::emulator::Emulator;
+use rusty_boy::log;
+use rusty_boy::screenshot;
+use rusty_boy::{info, warn};
+
+/// Frames run when `--frames` is not given: one second of emulated time.
+const DEFAULT_FRAMES: u32 = 60;
+
+fn main() {
+    let mut args: Vec<String> = env::args().collect();
+    log::init_from_args(&mut args);
+    let mut rom = None;
+    let mut frames = DEFAULT_FRAMES;
+    let mut out = None;
+    let mut expect = None;
+    let mut tolerance = 0;
+    let mut play = None;
+
+    let mut args_iter = args[1..].iter();
+    while let Some(arg) = args_iter.next() {
+        match arg.as_str() {
+            "--frames" => match args_iter.next().map(|value| value.parse()) {
+                Some(Ok(value)) => frames = value,
+                _ => {
+                    eprintln!("❌ --frames expects a number of frames");
+                    std::process::exit(1);
+                }
+            },
+            "--out" => match args_iter.next() {
+                Some(value) => out = Some(PathBuf::from(value)),
+                None => {
+                    eprintln!("❌ Missing value for --out");
+                    std::process::exit(1);
+                }
+            },
+            "--expect" => match args_iter.next() {
+                Some(value) => expect = Some(PathBuf::from(value)),
+                None => {
+                    eprintln!("❌ Missing value for --expect");
+                    std::process::exit(1);
+                }
+            },
+            "--play" => match args_iter.next() {
+                Some(value) => play = Some(PathBuf::from(value)),
+                None => {
+                    eprintln!("❌ Missing value for --play");
+                    std::process::exit(1);
+                }
+            },
+            "--tolerance" => match args_iter.next().map(|value| value.parse()) {
+                Some(Ok(value)) => tolerance = value,
+                _ => {
+                    eprintln!("❌ --tolerance expects a number of pixels");
+                    std::process::exit(1);
+                }
+            },
+            "help" | "--help" | "-h" => {
+                show_help();
+                return;
+            }
+            path if rom.is_none() && !path.starts_with("--") => rom = Some(PathBuf::from(path)),
+            _ => {
+                eprintln!("❌ Unknown argument: {}", arg);
+                show_help();
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let Some(rom) = rom else {
+        eprintln!("❌ Usage: cargo run --bin headless -- <ROM> [--frames <N>] [--out <PNG>] [--expect <PNG>]");
+        std::process::exit(1);
+    };
+    let out = out.unwrap_or_else(|| rom.with_extension("png"));
+
+    let data = fs::read(&rom).unwrap_or_else(|e| {
+        eprintln!("❌ Failed to read {}: {}", rom.display(), e);
+        std::process::exit(1);
+    });
+    let mut emulator = Emulator::new(data).unwrap_or_else(|e| {
+        eprintln!("❌ Failed to load {}: {}", rom.display(), e);
+        std::process::exit(1);
+    });
+    let save = cartridge::save_path(&rom);
+    if let Err(e) = emulator.attach_save_file(&save) {
+        eprintln!("❌ Failed to load {}: {}", save.display(), e);
+        std::process::exit(1);
+    }
+
+    if let Some(play) = &play
+        && let Err(e) = emulator.play_recording(play)
+    {
+        eprintln!("❌ Failed to load {}: {}", play.display(), e);
+        std::process::exit(1);
+    }
+
+    info!("🎮 Running {} for {} frame(s)...", rom.display(), frames);
+    emulator.run_frames(frames);
+    if let Err(e) = emulator.flush_save_file() {
+        warn!("⚠️  Warning: Failed to write {}: {}", save.display(), e);
+    }
+    let framebuffer = emulator.framebuffer();
+    if let Err(e) = screenshot::write_png(&out, framebuffer) {
+        eprintln!("❌ Failed to write {}: {}", out.display(), e);
+        std::process::exit(1);
+    }
+    info!("✅ Saved the last frame to {}", out.display());
+
+    if let Some(expect) = expect {
+        match screenshot::compare_framebuffer(emulator.framebuffer(), &expect) {
+            Ok(diff) if diff.within(tolerance) => info!("✅ Matches {}", expect.display()),
+            Ok(diff) => {
+                eprintln!("❌ {} pixel(s) differ from {}", diff.differing_pixels, expect.display());
+                if let Some(bounds) = diff.bounds {
+                    eprintln!(
+                        "   in the {}×{} area at ({}, {})",
+                        bounds.width, bounds.height, bounds.x, bounds.y
+                    );
+                }
+                std::process::exit(1);
+            }
+            Err(e) => {
+                eprintln!("❌ Failed to compare with {}: {}", expect.display(), e);
+                std::process::exit(1);
+            }
+        }
+    }
+}
+
+fn show_help() {
+    println!("🎮 Headless Runner");
+    println!();
+    println!("USAGE:");
+    println!("  cargo run --bin headless -- <ROM> [--frames <N>] [--out <PNG>] [--expect <PNG>]");
+    println!("                                    [--play <GBI>]");
+    println!();
+    println!("Runs a ROM without a window and saves the last rendered frame as a PNG.");
+    println!("With --expect, the frame is also compared with a reference screenshot and");
+    println!("the runner exits with a non-zero status if they differ.");
+    println!();
+    println!("Battery-backed RAM is loaded from and saved to the ROM path with a .sav");
+    println!("extension.");
+    println!();
+    println!("OPTIONS:");
+    println!("  --frames <N>          Frames to run (default: {})", DEFAULT_FRAMES);
+    println!("  --out <PNG>           Image to write (default: the ROM path with a .png extension)");
+    println!("  --expect <PNG>        Reference screenshot to compare the frame with");
+    println!("  --tolerance <PIXELS>  Differing pixels allowed by --expect (default: 0)");
+    println!("  --play <GBI>          Input recording to play back from the first frame");
+    println!("  --quiet               Only print errors");
+}
@@ -1,114 +1,626 @@
+use std::collections::VecDeque;
 use std::env;
-use std::process::Command;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+
+use rusty_boy::config::Config;
+use rusty_boy::launcher::{NO_OPEN_FLAG, command_matches, command_name, http_responds};
+use rusty_boy::log::{self, LogCommand, VERBOSITY_ENV, Verbosity};
+use rusty_boy::{info, warn};
+
+const HEALTH_ATTEMPTS: u32 = 30;
+const HEALTH_INTERVAL: Duration = Duration::from_secs(2);
+/// Background components of the last launch, one `<PID> <COMMAND> <BINARY>`
+/// line each.
+const PID_FILE: &str = ".rusty_boy_servers.pid";
+const SUPERVISE_INTERVAL: Duration = Duration::from_millis(200);
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(3);
+/// How long a freshly spawned server may take to answer. The first pandocs
+/// launch compiles its preprocessors and installs Python packages.
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(300);
+const STARTUP_POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// Number of stderr lines kept to explain a server that failed to start.
+const STDERR_TAIL_LINES: usize = 20;
+
+/// A documentation source that launch-all-docs can start.
+#[derive(Clone, Copy, PartialEq)]
+enum Component {
+    RustDocs,
+    Pandocs,
+    Dmg01,
+    GbCtr,
+}
+
+impl Component {
+    /// Every component, in launch order.
+    const ALL: [Component; 4] = [Component::RustDocs, Component::Pandocs, Component::Dmg01, Component::GbCtr];
+    
+    /// Name used to select the component on the command line.
+    fn name(self) -> &'static str {
+        match self {
+            Component::RustDocs => "rust-docs",
+            Component::Pandocs => "pandocs",
+            Component::Dmg01 => "dmg01",
+            Component::GbCtr => "gb-ctr",
+        }
+    }
+    
+    /// Resource checkout the component needs cloned, if any.
+    fn resource(self) -> Option<&'static str> {
+        match self {
+            Component::RustDocs => None,
+            Component::Pandocs => Some("pandocs"),
+            Component::Dmg01 => Some("DMG-01"),
+            Component::GbCtr => Some("gb-ctr"),
+        }
+    }
+    
+    fn from_name(name: &str) -> Option<Component> {
+        Component::ALL.into_iter().find(|component| component.name() == name)
+    }
+}
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
+    let mut args: Vec<String> = env::args().collect();
+    log::init_from_args(&mut args);
+    let mut health = false;
+    let mut no_open = false;
+    let mut selected = Vec::new();
     
-    if args.len() > 1 {
-        match args[1].as_str() {
-            "help" | "--help" | "-h" => {
-                show_help();
+    for arg in &args[1..] {
+        match arg.as_str() {
+            "--health" => {
+                health = true;
+            }
+            NO_OPEN_FLAG => {
+                no_open = true;
+            }
+            "stop" => {
+                stop_servers();
                 return;
             }
-            _ => {
-                eprintln!("❌ Unknown argument: {}", args[1]);
+            "status" => {
+                show_status();
+                return;
+            }
+            "help" | "--help" | "-h" => {
                 show_help();
-                std::process::exit(1);
+                return;
             }
+            name => match Component::from_name(name) {
+                Some(component) => selected.push(component),
+                None => {
+                    eprintln!("❌ Unknown argument: {}", name);
+                    show_components();
+                    std::process::exit(1);
+                }
+            },
         }
     }
     
-    println!("🚀 Launching Rusty Boy Development Environment");
-    println!("===============================================");
-    println!();
-    
-    // Step 1: Clone resources
-    println!("📥 Step 1: Cloning resources...");
-    run_command("clone-resources", "Cloning external resources");
-    
-    // Brief pause between operations
-    thread::sleep(Duration::from_millis(500));
-    
-    // Step 2: Launch documentation
-    println!();
-    println!("📚 Step 2: Launching documentation...");
+    // Launch everything unless specific components were named
+    let components: Vec<Component> = if selected.is_empty() {
+        Component::ALL.to_vec()
+    } else {
+        Component::ALL.into_iter().filter(|component| selected.contains(component)).collect()
+    };
     
-    // Launch Rust docs
-    println!("  🦀 Opening Rust documentation...");
-    run_command_background("rust-docs", "Rust documentation");
+    let interrupted = Arc::new(AtomicBool::new(false));
+    let handler_flag = Arc::clone(&interrupted);
+    if let Err(e) = ctrlc::set_handler(move || handler_flag.store(true, Ordering::SeqCst)) {
+        warn!("⚠️  Warning: Failed to install Ctrl+C handler: {}", e);
+    }
+    let mut children = Vec::new();
+    let config = Config::load_or_exit();
     
-    // Brief pause
-    thread::sleep(Duration::from_millis(1000));
+    info!("🚀 Launching Rusty Boy Development Environment");
+    info!("===============================================");
+    info!();
     
-    // Launch Pandocs
-    println!("  📖 Opening Pandocs (Game Boy development guide)...");
-    run_command_background("launch-pandocs", "Pandocs");
+    // Step 1: Clone resources, limited to what the named components need
+    let resources: Vec<&str> = selected.iter().filter_map(|component| component.resource()).collect();
+    if selected.is_empty() || !resources.is_empty() {
+        info!("📥 Step 1: Cloning resources...");
+        run_command("clone-resources", &resources, "Cloning external resources");
+        
+        // Brief pause between operations
+        thread::sleep(Duration::from_millis(500));
+    }
     
-    // Brief pause
-    thread::sleep(Duration::from_millis(1000));
+    // Step 2: Launch documentation
+    info!();
+    info!("📚 Step 2: Launching documentation...");
+    reset_pid_file();
     
-    // Launch DMG-01 docs
-    println!("  🎮 Opening DMG-01 documentation...");
-    run_command_background("launch-dmg01", "DMG-01 docs");
+    for (i, component) in components.iter().enumerate() {
+        // Brief pause between launches
+        if i > 0 {
+            thread::sleep(Duration::from_millis(1000));
+        }
+        
+        match component {
+            Component::RustDocs => {
+                info!("  🦀 Opening Rust documentation...");
+                children.extend(run_command_background("rust-docs", "Rust documentation", no_open));
+            }
+            Component::Pandocs => {
+                info!("  📖 Opening Pandocs (Game Boy development guide)...");
+                children.extend(launch_server("launch-pandocs", "Pandocs", config.pandocs_port, no_open));
+            }
+            Component::Dmg01 => {
+                info!("  🎮 Opening DMG-01 documentation...");
+                children.extend(launch_server("launch-dmg01", "DMG-01 docs", config.dmg01_port, no_open));
+            }
+            Component::GbCtr => {
+                info!("  📕 Opening Game Boy Complete Technical Reference...");
+                children.extend(run_command_background("gb-ctr-book", "GB-CTR book", no_open));
+            }
+        }
+    }
     
-    // Brief pause
-    thread::sleep(Duration::from_millis(1000));
+    info!();
+    info!("✅ Development environment launched successfully!");
+    info!();
+    show_summary();
     
-    // Launch GB-CTR book
-    println!("  📕 Opening Game Boy Complete Technical Reference...");
-    run_command_background("gb-ctr-book", "GB-CTR book");
+    if health && !check_health(&components, &config) {
+        std::process::exit(1);
+    }
     
-    println!();
-    println!("✅ Development environment launched successfully!");
-    println!();
-    show_summary();
+    supervise(children, &interrupted);
 }
 
-fn run_command(binary_name: &str, description: &str) {
-    let result = Command::new("cargo")
-        .args(&["run", "--bin", binary_name])
+/// Builds a `cargo run` invocation of one of the project binaries, passing
+/// the current verbosity on so the child prints as much as this process does.
+fn cargo_run(binary_name: &str) -> Command {
+    let mut command = Command::new("cargo");
+    command.arg("run");
+    if log::verbosity() == Verbosity::Quiet {
+        command.arg("--quiet");
+    }
+    command.args(["--bin", binary_name]);
+    command.env(VERBOSITY_ENV, log::verbosity().as_str());
+    command
+}
+
+fn run_command(binary_name: &str, args: &[&str], description: &str) {
+    let result = cargo_run(binary_name)
+        .arg("--")
+        .args(args)
+        .logged()
         .status();
     
     match result {
         Ok(status) if status.success() => {
-            println!("  ✅ {}", description);
+            info!("  ✅ {}", description);
         }
         Ok(_) => {
-            println!("  ⚠️  {} completed with warnings", description);
+            warn!("  ⚠️  {} completed with warnings", description);
         }
         Err(e) => {
-            println!("  ❌ Failed to run {}: {}", description, e);
+            eprintln!("  ❌ Failed to run {}: {}", description, e);
         }
     }
 }
 
-fn run_command_background(binary_name: &str, description: &str) {
-    let result = Command::new("cargo")
-        .args(&["run", "--bin", binary_name])
-        .spawn();
+fn run_command_background<'a>(binary_name: &str, description: &'a str, no_open: bool) -> Option<(&'a str, Child)> {
+    let mut command = cargo_run(binary_name);
+    if no_open {
+        command.args(["--", NO_OPEN_FLAG]);
+    }
+    let result = command.logged().spawn();
     
     match result {
-        Ok(_) => {
-            println!("    ✅ {} launched", description);
+        Ok(child) => {
+            info!("    ✅ {} launched", description);
+            track_pid(child.id(), binary_name);
+            Some((description, child))
         }
         Err(e) => {
-            println!("    ❌ Failed to launch {}: {}", description, e);
+            eprintln!("    ❌ Failed to launch {}: {}", description, e);
+            None
         }
     }
 }
 
+/// Spawns an mdbook launcher on a fixed `port` and only reports success once
+/// the server actually answers HTTP requests. If the server dies first (most
+/// often because the port was taken), the tail of its stderr is shown.
+fn launch_server<'a>(binary_name: &str, description: &'a str, port: u16, no_open: bool) -> Option<(&'a str, Child)> {
+    let mut command = cargo_run(binary_name);
+    command.args(["--", "--port", &port.to_string()]);
+    if no_open {
+        command.arg(NO_OPEN_FLAG);
+    }
+    
+    let mut child = match command.stderr(Stdio::piped()).logged().spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            eprintln!("    ❌ Failed to launch {}: {}", description, e);
+            return None;
+        }
+    };
+    track_pid(child.id(), binary_name);
+    let stderr = StderrTail::capture(&mut child);
+    
+    info!("    ⏳ Waiting for {} on http://localhost:{}...", description, port);
+    let deadline = Instant::now() + STARTUP_TIMEOUT;
+    loop {
+        if http_responds(port) {
+            info!("    ✅ {} is serving on http://localhost:{}", description, port);
+            stderr.forward();
+            return Some((description, child));
+        }
+        
+        if let Ok(Some(status)) = child.try_wait() {
+            eprintln!("    ❌ {} exited before serving ({})", description, status);
+            stderr.print();
+            return None;
+        }
+        
+        if Instant::now() >= deadline {
+            warn!("    ⚠️  {} is still not answering on port {}", description, port);
+            stderr.print();
+            stderr.forward();
+            return Some((description, child));
+        }
+        
+        thread::sleep(STARTUP_POLL_INTERVAL);
+    }
+}
+
+/// Keeps the last lines a background component wrote to stderr while it is
+/// starting up, then passes its output straight through once it is running.
+struct StderrTail {
+    lines: Arc<Mutex<VecDeque<String>>>,
+    forwarding: Arc<AtomicBool>,
+}
+
+impl StderrTail {
+    fn capture(child: &mut Child) -> StderrTail {
+        let tail = StderrTail {
+            lines: Arc::new(Mutex::new(VecDeque::with_capacity(STDERR_TAIL_LINES))),
+            forwarding: Arc::new(AtomicBool::new(false)),
+        };
+        
+        if let Some(stderr) = child.stderr.take() {
+            let lines = Arc::clone(&tail.lines);
+            let forwarding = Arc::clone(&tail.forwarding);
+            thread::spawn(move || {
+                for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                    if forwarding.load(Ordering::SeqCst) {
+                        eprintln!("{}", line);
+                        continue;
+                    }
+                    let mut lines = lines.lock().unwrap();
+                    if lines.len() == STDERR_TAIL_LINES {
+                        lines.pop_front();
+                    }
+                    lines.push_back(line);
+                }
+            });
+        }
+        
+        tail
+    }
+    
+    fn print(&self) {
+        // Give the reader thread a moment to drain what the child wrote last
+        thread::sleep(Duration::from_millis(100));
+        for line in self.lines.lock().unwrap().iter() {
+            eprintln!("      {}", line);
+        }
+    }
+    
+    fn forward(&self) {
+        self.forwarding.store(true, Ordering::SeqCst);
+        self.lines.lock().unwrap().clear();
+    }
+}
+
+/// Keeps the launcher in the foreground while background components are
+/// running, so that Ctrl+C shuts all of them down instead of leaving orphaned
+/// servers behind.
+///
+/// Every tracked component has exited once this returns, so the PID file is
+/// removed whether they were stopped or exited on their own.
+fn supervise(children: Vec<(&str, Child)>, interrupted: &AtomicBool) {
+    wait_for_children(children, interrupted);
+    let _ = fs::remove_file(PID_FILE);
+}
+
+/// Waits until every child has exited, stopping them all on Ctrl+C.
+fn wait_for_children(mut children: Vec<(&str, Child)>, interrupted: &AtomicBool) {
+    children.retain_mut(|(_, child)| matches!(child.try_wait(), Ok(None)));
+    if children.is_empty() {
+        return;
+    }
+    
+    info!();
+    info!("Press Ctrl+C to stop all servers");
+    
+    while !interrupted.load(Ordering::SeqCst) {
+        children.retain_mut(|(_, child)| matches!(child.try_wait(), Ok(None)));
+        if children.is_empty() {
+            return;
+        }
+        thread::sleep(SUPERVISE_INTERVAL);
+    }
+    
+    info!();
+    info!("🛑 Interrupted, stopping {} running component(s)...", children.len());
+    for (_, child) in &children {
+        terminate_process(child.id());
+    }
+    
+    let deadline = Instant::now() + SHUTDOWN_TIMEOUT;
+    for (description, child) in &mut children {
+        while matches!(child.try_wait(), Ok(None)) && Instant::now() < deadline {
+            thread::sleep(SUPERVISE_INTERVAL);
+        }
+        
+        if matches!(child.try_wait(), Ok(Some(_))) {
+            info!("  ✅ {} shut down cleanly", description);
+        } else {
+            let _ = child.kill();
+            let _ = child.wait();
+            warn!("  ⚠️  {} did not exit in time and was killed", description);
+        }
+    }
+}
+
+/// A background component recorded in [`PID_FILE`].
+struct TrackedServer {
+    pid: u32,
+    /// Command name of the spawned binary, checked before signalling the PID
+    /// in case it was reused by an unrelated process.
+    command: String,
+    name: String,
+}
+
+impl TrackedServer {
+    /// Returns `true` if the PID still belongs to the process that was
+    /// spawned, as far as its command name tells.
+    fn is_running(&self) -> bool {
+        process_is_alive(self.pid) && process_command(self.pid).is_some_and(|command| command_matches(&command, &self.command))
+    }
+}
+
+/// Forgets the servers of a previous launch, which have exited or belong to
+/// another session by now.
+fn reset_pid_file() {
+    if let Err(e) = File::create(PID_FILE) {
+        warn!("    ⚠️  Warning: Failed to reset {}: {}", PID_FILE, e);
+    }
+}
+
+fn track_pid(pid: u32, binary_name: &str) {
+    let program = command_name(binary_name);
+    let result = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(PID_FILE)
+        .and_then(|mut file| writeln!(file, "{} {} {}", pid, program, binary_name));
+    
+    if let Err(e) = result {
+        warn!("    ⚠️  Warning: Failed to record PID {} in {}: {}", pid, PID_FILE, e);
+    }
+}
+
+fn load_tracked_pids() -> Vec<TrackedServer> {
+    let Ok(content) = fs::read_to_string(PID_FILE) else {
+        return Vec::new();
+    };
+    
+    content
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split(' ');
+            let pid = fields.next()?.parse().ok()?;
+            let command = fields.next()?.to_string();
+            let name = fields.next()?.to_string();
+            Some(TrackedServer { pid, command, name })
+        })
+        .collect()
+}
+
+fn stop_servers() {
+    let servers = load_tracked_pids();
+    if servers.is_empty() {
+        info!("💤 No tracked servers to stop");
+        return;
+    }
+    
+    info!("🛑 Stopping tracked servers...");
+    for server in &servers {
+        let (pid, name) = (server.pid, &server.name);
+        if !process_is_alive(pid) {
+            info!("  💤 {} (PID {}) was not running", name, pid);
+        } else if !server.is_running() {
+            warn!("  ⚠️  {} (PID {}) exited and its PID was reused, leaving it alone", name, pid);
+        } else if terminate_process(pid) {
+            info!("  ✅ Stopped {} (PID {})", name, pid);
+        } else {
+            eprintln!("  ❌ Failed to stop {} (PID {})", name, pid);
+        }
+    }
+    
+    if let Err(e) = fs::remove_file(PID_FILE) {
+        warn!("⚠️  Warning: Failed to remove {}: {}", PID_FILE, e);
+    }
+}
+
+fn show_status() {
+    let servers = load_tracked_pids();
+    if servers.is_empty() {
+        println!("💤 No tracked servers");
+        return;
+    }
+    
+    println!("📋 Tracked servers:");
+    for server in &servers {
+        if server.is_running() {
+            println!("  🟢 {} (PID {}) is running", server.name, server.pid);
+        } else {
+            println!("  ⚪ {} (PID {}) is not running", server.name, server.pid);
+        }
+    }
+}
+
+/// Returns the executable name of process `pid`, if it exists.
+fn process_command(pid: u32) -> Option<String> {
+    let output = if cfg!(target_os = "windows") {
+        Command::new("tasklist")
+            .args(["/FI", &format!("PID eq {}", pid), "/FO", "CSV", "/NH"])
+            .logged()
+            .output()
+            .ok()?
+    } else {
+        Command::new("ps")
+            .args(["-o", "comm=", "-p", &pid.to_string()])
+            .stderr(Stdio::null())
+            .logged()
+            .output()
+            .ok()?
+    };
+    
+    // tasklist prints `"cargo.exe","1234",...`, ps just the command
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let command = stdout.trim().split(',').next()?.trim_matches('"');
+    (output.status.success() && !command.is_empty()).then(|| command_name(command))
+}
+
+fn process_is_alive(pid: u32) -> bool {
+    if cfg!(target_os = "windows") {
+        Command::new("tasklist")
+            .args(["/FI", &format!("PID eq {}", pid), "/NH"])
+            .logged()
+            .output()
+            .map(|output| String::from_utf8_lossy(&output.stdout).contains(&pid.to_string()))
+            .unwrap_or(false)
+    } else {
+        Command::new("kill")
+            .args(["-0", &pid.to_string()])
+            .stderr(Stdio::null())
+            .logged()
+            .status()
+            .map(|status| status.success())
+            .unwrap_or(false)
+    }
+}
+
+/// Terminates `pid` together with the processes it spawned, since the tracked
+/// launchers run `mdbook serve` as a child of their own.
+fn terminate_process(pid: u32) -> bool {
+    let pid = pid.to_string();
+    
+    if cfg!(target_os = "windows") {
+        Command::new("taskkill")
+            .args(["/PID", &pid, "/T", "/F"])
+            .stdout(Stdio::null())
+            .logged()
+            .status()
+            .map(|status| status.success())
+            .unwrap_or(false)
+    } else {
+        // Children first, so they are not re-parented before we can find them
+        let _ = Command::new("pkill").args(["-TERM", "-P", &pid]).logged().status();
+        Command::new("kill")
+            .args(["-TERM", &pid])
+            .logged()
+            .status()
+            .map(|status| status.success())
+            .unwrap_or(false)
+    }
+}
+
+fn check_health(components: &[Component], config: &Config) -> bool {
+    info!();
+    info!("🩺 Verifying launched components...");
+    
+    let results: Vec<(&str, bool)> = components
+        .iter()
+        .map(|component| match component {
+            Component::RustDocs => ("Rust documentation", rust_docs_available()),
+            Component::Pandocs => ("Pandocs", wait_for_server(config.pandocs_port)),
+            Component::Dmg01 => ("DMG-01 docs", wait_for_server(config.dmg01_port)),
+            Component::GbCtr => ("GB-CTR book", config.resource_path("gb-ctr").join("gbctr.pdf").exists()),
+        })
+        .collect();
+    
+    info!();
+    info!("🩺 Health summary:");
+    for (component, healthy) in &results {
+        if *healthy {
+            info!("  ✅ {}", component);
+        } else {
+            eprintln!("  ❌ {}", component);
+        }
+    }
+    
+    results.iter().all(|(_, healthy)| *healthy)
+}
+
+fn rust_docs_available() -> bool {
+    match Command::new("rustup").args(["doc", "--path"]).logged().output() {
+        Ok(output) if output.status.success() => {
+            let doc_path = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            Path::new(&doc_path).exists()
+        }
+        _ => false,
+    }
+}
+
+/// Polls `http://localhost:<port>` until it answers with an HTTP 200 or the
+/// attempts run out. mdbook servers can take a while to come up (pandocs
+/// builds its preprocessors first), hence the generous retry budget.
+fn wait_for_server(port: u16) -> bool {
+    for attempt in 1..=HEALTH_ATTEMPTS {
+        if http_responds(port) {
+            return true;
+        }
+        if attempt < HEALTH_ATTEMPTS {
+            thread::sleep(HEALTH_INTERVAL);
+        }
+    }
+    
+    false
+}
+
+fn show_components() {
+    eprintln!("Available components:");
+    for component in Component::ALL {
+        eprintln!("  {}", component.name());
+    }
+}
+
 fn show_help() {
     println!("🚀 Rusty Boy Development Environment Launcher");
     println!();
     println!("USAGE:");
-    println!("  cargo run --bin launch-all-docs [COMMAND]");
+    println!("  cargo run --bin launch-all-docs -- [COMMAND] [OPTIONS] [COMPONENT...]");
     println!();
     println!("COMMANDS:");
     println!("  (no args)           Launch the complete development environment");
+    println!("  <component...>      Launch only the named components:");
+    println!("                      rust-docs, pandocs, dmg01, gb-ctr");
+    println!("  stop                Stop the servers started by a previous launch");
+    println!("  status              Show which of the tracked servers are still running");
     println!("  help                Show this help message");
     println!();
+    println!("OPTIONS:");
+    println!("  --no-open           Build and serve everything without opening a browser");
+    println!("  --health            After launching, verify every component is serving");
+    println!("                      and exit non-zero if any of them is not");
+    println!("  --quiet             Only print errors");
+    println!("  --verbose           Also print every external command before running it");
+    println!();
     println!("WHAT IT DOES:");
     println!("  1. Clones external resources (mooneye-gb, pandocs, etc.)");
     println!("  2. Opens Rust documentation in browser");
@@ -117,6 +629,7 @@ fn show_help() {
     println!("  5. Opens Game Boy Complete Technical Reference");
     println!();
     println!("INDIVIDUAL COMMANDS:");
+    println!("  cargo run --bin doctor             # Check external tool prerequisites");
     println!("  cargo run --bin clone-resources    # Clone external resources");
     println!("  cargo run --bin rust-docs          # Open Rust documentation");
     println!("  cargo run --bin launch-pandocs     # Open Pandocs");
@@ -125,15 +638,17 @@ fn show_help() {
 }
 
 fn show_summary() {
-    println!("💡 What's now available:");
-    println!("  • External resources cloned to resources/ folder");
-    println!("  • Rust documentation open in browser");
-    println!("  • Pandocs (Game Boy dev guide) running locally");
-    println!("  • DMG-01 documentation available");
-    println!("  • Game Boy Complete Technical Reference open");
-    println!();
-    println!("🔧 Happy Game Boy development!");
-    println!();
-    println!("💭 Tip: You can run individual components with:");
-    println!("     cargo run --bin <component-name>");
+    info!("💡 What's now available:");
+    info!("  • External resources cloned to resources/ folder");
+    info!("  • Rust documentation open in browser");
+    info!("  • Pandocs (Game Boy dev guide) running locally");
+    info!("  • DMG-01 documentation available");
+    info!("  • Game Boy Complete Technical Reference open");
+    info!();
+    info!("🔧 Happy Game Boy development!");
+    info!();
+    info!("💭 Tip: You can run individual components with:");
+    info!("     cargo run --bin <component-name>");
+    info!("💭 Stop the background servers with:");
+    info!("     cargo run --bin launch-all-docs stop");
 }
@@ -1,15 +1,33 @@
 use std::env;
-use std::process::{Command, Stdio};
+use std::process::Command;
+
+use rusty_boy::config::Config;
+use rusty_boy::launcher::{
+    NO_OPEN_FLAG, command_exists, find_available_port, parse_port_arg, port_is_in_use,
+    preferred_browser, take_flag,
+};
+use rusty_boy::log::{self, LogCommand};
+use rusty_boy::info;
 
 fn main() {
-    println!("🚀 Launching DMG-01 book...");
+    let mut args: Vec<String> = env::args().collect();
+    log::init_from_args(&mut args);
+    let no_open = take_flag(&mut args, NO_OPEN_FLAG);
+    let requested_port = parse_port_arg(&args).unwrap_or_else(|e| {
+        eprintln!("❌ {}", e);
+        eprintln!("Usage: cargo run --bin launch-dmg01 -- [--port <N>] [--no-open] [--quiet|--verbose]");
+        std::process::exit(1);
+    });
+    
+    info!("🚀 Launching DMG-01 book...");
     
     // Get the project root directory
     let current_dir = env::current_dir().expect("Failed to get current directory");
-    let dmg01_dir = current_dir.join("resources").join("DMG-01").join("book");
+    let config = Config::load_or_exit();
+    let dmg01_dir = current_dir.join(config.resource_path("DMG-01")).join("book");
     
-    println!("📁 Project root: {}", current_dir.display());
-    println!("📚 DMG-01 book directory: {}", dmg01_dir.display());
+    info!("📁 Project root: {}", current_dir.display());
+    info!("📚 DMG-01 book directory: {}", dmg01_dir.display());
     
     // Check if DMG-01 book directory exists
     if !dmg01_dir.exists() {
@@ -27,59 +45,46 @@ fn main() {
         std::process::exit(1);
     }
     
-    // Find an available port (starting from 3100 to avoid conflict with Pandocs)
-    let port = find_available_port(3100);
+    let port = match requested_port {
+        // A forced port must be used as-is, never silently replaced
+        Some(port) => {
+            if port_is_in_use(port) {
+                eprintln!("❌ Error: port {} is already in use", port);
+                std::process::exit(1);
+            }
+            port
+        }
+        // Find an available port (by default from 3100 to avoid conflict with Pandocs)
+        None => find_available_port(config.dmg01_port).unwrap_or_else(|e| {
+            eprintln!("❌ {}", e);
+            std::process::exit(1);
+        }),
+    };
     
-    println!("🌐 Starting mdbook server on port {}...", port);
-    println!("📖 The book will be available at: http://localhost:{}", port);
-    println!("📘 Book title: DMG-01: How to Emulate a Game Boy");
-    println!("🔄 The server will watch for file changes and auto-reload");
-    println!();
-    println!("Press Ctrl+C to stop the server");
-    println!();
+    info!("🌐 Starting mdbook server on port {}...", port);
+    info!("📖 The book will be available at: http://localhost:{}", port);
+    info!("📘 Book title: DMG-01: How to Emulate a Game Boy");
+    info!("🔄 The server will watch for file changes and auto-reload");
+    info!();
+    info!("Press Ctrl+C to stop the server");
+    info!();
     
     // Start the mdbook server
-    let status = Command::new("mdbook")
-        .args(&["serve", "--port", &port.to_string(), "--open"])
-        .status()
-        .expect("Failed to execute mdbook serve");
+    let mut cmd = Command::new("mdbook");
+    cmd.args(["serve", "--port", &port.to_string()]);
+    if !no_open {
+        cmd.arg("--open");
+    }
+    
+    // mdbook opens the book through $BROWSER when it is set
+    if let Some(browser) = preferred_browser() {
+        cmd.env("BROWSER", browser);
+    }
+    
+    let status = cmd.logged().status().expect("Failed to execute mdbook serve");
     
     if !status.success() {
         eprintln!("❌ mdbook serve failed");
         std::process::exit(1);
     }
 }
-
-fn command_exists(command: &str) -> bool {
-    Command::new("which")
-        .arg(command)
-        .stdout(Stdio::null())
-        .stderr(Stdio::null())
-        .status()
-        .map(|status| status.success())
-        .unwrap_or(false)
-}
-
-fn find_available_port(start_port: u16) -> u16 {
-    for port in start_port..=65535 {
-        if !port_is_in_use(port) {
-            return port;
-        }
-        if port != start_port {
-            println!("⚠️  Port {} is in use, trying next port...", port - 1);
-        }
-    }
-    
-    eprintln!("❌ No available ports found");
-    std::process::exit(1);
-}
-
-fn port_is_in_use(port: u16) -> bool {
-    Command::new("lsof")
-        .args(&["-i", &format!(":{}", port)])
-        .stdout(Stdio::null())
-        .stderr(Stdio::null())
-        .status()
-        .map(|status| status.success())
-        .unwrap_or(false)
-}
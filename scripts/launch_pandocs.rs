@@ -1,18 +1,38 @@
 use std::env;
-use std::path::Path;
-use std::process::{Command, Stdio};
+use std::path::{Path, PathBuf};
+use std::process::Command;
 use std::fs;
 use std::io;
 
+use sha2::{Digest, Sha256};
+
+use rusty_boy::config::Config;
+use rusty_boy::launcher::{
+    NO_OPEN_FLAG, command_exists, find_available_port, parse_port_arg, port_is_in_use,
+    preferred_browser, take_flag,
+};
+use rusty_boy::log::{self, LogCommand};
+use rusty_boy::info;
+
 fn main() {
-    println!("🚀 Launching Pan Docs book...");
+    let mut args: Vec<String> = env::args().collect();
+    log::init_from_args(&mut args);
+    let no_open = take_flag(&mut args, NO_OPEN_FLAG);
+    let requested_port = parse_port_arg(&args).unwrap_or_else(|e| {
+        eprintln!("❌ {}", e);
+        eprintln!("Usage: cargo run --bin launch-pandocs -- [--port <N>] [--no-open] [--quiet|--verbose]");
+        std::process::exit(1);
+    });
+    
+    info!("🚀 Launching Pan Docs book...");
     
     // Get the project root directory
     let current_dir = env::current_dir().expect("Failed to get current directory");
-    let pandocs_dir = current_dir.join("resources").join("pandocs");
+    let config = Config::load_or_exit();
+    let pandocs_dir = current_dir.join(config.resource_path("pandocs"));
     
-    println!("📁 Project root: {}", current_dir.display());
-    println!("📚 Pandocs directory: {}", pandocs_dir.display());
+    info!("📁 Project root: {}", current_dir.display());
+    info!("📚 Pandocs directory: {}", pandocs_dir.display());
     
     // Check if pandocs directory exists
     if !pandocs_dir.exists() {
@@ -41,9 +61,10 @@ fn main() {
     }
     
     // Build Rust preprocessors
-    println!("🔧 Building Rust preprocessors...");
+    info!("🔧 Building Rust preprocessors...");
     let build_status = Command::new("cargo")
-        .args(&["build", "--release", "--locked"])
+        .args(["build", "--release", "--locked"])
+        .logged()
         .status()
         .expect("Failed to execute cargo build");
     
@@ -52,29 +73,51 @@ fn main() {
         std::process::exit(1);
     }
     
-    // Find an available port (starting from 3000)
-    let port = find_available_port(3000);
+    let port = match requested_port {
+        // A forced port must be used as-is, never silently replaced
+        Some(port) => {
+            if port_is_in_use(port) {
+                eprintln!("❌ Error: port {} is already in use", port);
+                std::process::exit(1);
+            }
+            port
+        }
+        // Find an available port (by default starting from 3000)
+        None => find_available_port(config.pandocs_port).unwrap_or_else(|e| {
+            eprintln!("❌ {}", e);
+            std::process::exit(1);
+        }),
+    };
     
-    println!("🌐 Starting mdbook server on port {}...", port);
-    println!("📖 The book will be available at: http://localhost:{}", port);
-    println!("🔄 The server will watch for file changes and auto-reload");
-    println!();
-    println!("Press Ctrl+C to stop the server");
-    println!();
+    info!("🌐 Starting mdbook server on port {}...", port);
+    info!("📖 The book will be available at: http://localhost:{}", port);
+    info!("🔄 The server will watch for file changes and auto-reload");
+    info!();
+    info!("Press Ctrl+C to stop the server");
+    info!();
     
     // Start the mdbook server
     let mut cmd = Command::new("mdbook");
-    cmd.args(&["serve", "--port", &port.to_string(), "--open"]);
+    cmd.args(["serve", "--port", &port.to_string()]);
+    if !no_open {
+        cmd.arg("--open");
+    }
+    
+    // mdbook opens the book through $BROWSER when it is set
+    if let Some(browser) = preferred_browser() {
+        cmd.env("BROWSER", browser);
+    }
     
     // Set up environment for Python virtual environment
-    let venv_path = pandocs_dir.join("env").join("bin");
+    let venv_path = venv_bin_dir(&pandocs_dir.join("env"));
     if let Ok(current_path) = env::var("PATH") {
-        let new_path = format!("{}:{}", venv_path.display(), current_path);
+        let separator = if cfg!(windows) { ";" } else { ":" };
+        let new_path = format!("{}{}{}", venv_path.display(), separator, current_path);
         cmd.env("PATH", new_path);
     }
     cmd.env("VIRTUAL_ENV", pandocs_dir.join("env"));
     
-    let status = cmd.status().expect("Failed to execute mdbook serve");
+    let status = cmd.logged().status().expect("Failed to execute mdbook serve");
     
     if !status.success() {
         eprintln!("❌ mdbook serve failed");
@@ -87,9 +130,10 @@ fn setup_python_env() -> io::Result<()> {
     
     // Create virtual environment if it doesn't exist
     if !venv_dir.exists() {
-        println!("🐍 Creating Python virtual environment...");
+        info!("🐍 Creating Python virtual environment...");
         let status = Command::new("python3")
-            .args(&["-m", "venv", "env"])
+            .args(["-m", "venv", "env"])
+            .logged()
             .status()?;
         
         if !status.success() {
@@ -102,17 +146,21 @@ fn setup_python_env() -> io::Result<()> {
     let requirements_file = Path::new("requirements.txt");
     let install_marker = venv_dir.join(".requirements_installed");
     
-    let needs_install = !install_marker.exists() || 
-        (requirements_file.exists() && 
-         requirements_file.metadata()?.modified()? > install_marker.metadata()?.modified()?);
+    // The marker stores the hash of the requirements it was installed from, so
+    // only an actual content change triggers a reinstall
+    let requirements_hash = sha256_hex(&fs::read(requirements_file)?);
+    let installed_hash = fs::read_to_string(&install_marker).unwrap_or_default();
+    let needs_install = installed_hash.trim() != requirements_hash;
     
     if needs_install {
-        println!("📦 Installing Python dependencies...");
+        info!("📦 Installing Python dependencies...");
         
         // Activate virtual environment and install requirements
-        let pip_path = venv_dir.join("bin").join("pip");
+        let pip = if cfg!(windows) { "pip.exe" } else { "pip" };
+        let pip_path = venv_bin_dir(venv_dir).join(pip);
         let status = Command::new(pip_path)
-            .args(&["install", "-r", "requirements.txt"])
+            .args(["install", "-r", "requirements.txt"])
+            .logged()
             .status()?;
         
         if !status.success() {
@@ -120,45 +168,28 @@ fn setup_python_env() -> io::Result<()> {
             std::process::exit(1);
         }
         
-        // Create marker file
-        fs::write(install_marker, "")?;
+        // Record what was installed
+        fs::write(install_marker, &requirements_hash)?;
     } else {
-        println!("✅ Python dependencies already up to date");
+        info!("✅ Python dependencies already up to date");
     }
     
     Ok(())
 }
 
-fn command_exists(command: &str) -> bool {
-    Command::new("which")
-        .arg(command)
-        .stdout(Stdio::null())
-        .stderr(Stdio::null())
-        .status()
-        .map(|status| status.success())
-        .unwrap_or(false)
+fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
 }
 
-fn find_available_port(start_port: u16) -> u16 {
-    for port in start_port..=65535 {
-        if !port_is_in_use(port) {
-            return port;
-        }
-        if port != start_port {
-            println!("⚠️  Port {} is in use, trying next port...", port - 1);
-        }
+/// Returns the directory holding a virtualenv's executables, which is
+/// `Scripts` on Windows and `bin` everywhere else.
+fn venv_bin_dir(venv_dir: &Path) -> PathBuf {
+    if cfg!(windows) {
+        venv_dir.join("Scripts")
+    } else {
+        venv_dir.join("bin")
     }
-    
-    eprintln!("❌ No available ports found");
-    std::process::exit(1);
-}
-
-fn port_is_in_use(port: u16) -> bool {
-    Command::new("lsof")
-        .args(&["-i", &format!(":{}", port)])
-        .stdout(Stdio::null())
-        .stderr(Stdio::null())
-        .status()
-        .map(|status| status.success())
-        .unwrap_or(false)
 }
@@ -0,0 +1,168 @@
+use std::env;
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+
+use rusty_boy::emulator::Emulator;
+use rusty_boy::launcher::collect_roms;
+use rusty_boy::log;
+use rusty_boy::info;
+
+/// Frames a ROM may run before it is reported as timed out. The full
+/// `cpu_instrs` ROM needs close to a minute of emulated time.
+const MAX_FRAMES: u32 = 7200;
+
+enum Outcome {
+    Passed,
+    Failed,
+    TimedOut,
+    Error(String),
+}
+
+fn main() {
+    let mut args: Vec<String> = env::args().collect();
+    log::init_from_args(&mut args);
+
+    let mut path = None;
+    let mut trace = None;
+
+    let mut args_iter = args[1..].iter();
+    while let Some(arg) = args_iter.next() {
+        match arg.as_str() {
+            "--trace" => match args_iter.next() {
+                Some(value) => trace = Some(PathBuf::from(value)),
+                None => {
+                    eprintln!("❌ Missing value for --trace");
+                    std::process::exit(1);
+                }
+            },
+            "help" | "--help" | "-h" => {
+                show_help();
+                return;
+            }
+            value if path.is_none() && !value.starts_with("--") => path = Some(PathBuf::from(value)),
+            _ => {
+                eprintln!("❌ Unknown argument: {}", arg);
+                show_help();
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let Some(path) = path else {
+        eprintln!("❌ Usage: cargo run --bin run-blargg -- <ROM_OR_DIRECTORY> [--trace <FILE>]");
+        std::process::exit(1);
+    };
+    if trace.is_some() && path.is_dir() {
+        eprintln!("❌ --trace needs a single ROM, not a directory");
+        std::process::exit(1);
+    }
+
+    let roms = if path.is_dir() {
+        let mut roms = Vec::new();
+        collect_roms(&path, &mut roms);
+        roms.sort();
+        roms
+    } else {
+        vec![path.clone()]
+    };
+
+    if roms.is_empty() {
+        eprintln!("❌ No .gb ROMs found in {}", path.display());
+        std::process::exit(1);
+    }
+
+    let mut failures = 0;
+    for rom in &roms {
+        info!("🧪 {}", rom.display());
+        let outcome = match fs::read(rom) {
+            Ok(data) => run_rom(&data, trace.as_deref()),
+            Err(e) => Outcome::Error(e.to_string()),
+        };
+
+        match &outcome {
+            Outcome::Passed => info!("  ✅ Passed"),
+            Outcome::Failed => eprintln!("  ❌ Failed"),
+            Outcome::TimedOut => eprintln!("  ❌ No result after {} frames", MAX_FRAMES),
+            Outcome::Error(message) => eprintln!("  ❌ {}", message),
+        }
+        if !matches!(outcome, Outcome::Passed) {
+            failures += 1;
+        }
+    }
+
+    if roms.len() > 1 {
+        info!();
+        info!("Passed {}/{} ROM(s)", roms.len() - failures, roms.len());
+    }
+    if failures > 0 {
+        std::process::exit(1);
+    }
+}
+
+/// Runs `rom` and classifies the text it printed over the serial port.
+fn run_rom(rom: &[u8], trace: Option<&Path>) -> Outcome {
+    match emulate(rom, MAX_FRAMES, trace) {
+        Ok(output) => {
+            info!("{}", output.trim_end());
+            classify(&output)
+        }
+        Err(message) => Outcome::Error(message),
+    }
+}
+
+/// Looks for the verdict blargg ROMs print once every sub-test has run.
+fn classify(output: &str) -> Outcome {
+    if output.contains("Failed") {
+        Outcome::Failed
+    } else if output.contains("Passed") {
+        Outcome::Passed
+    } else {
+        Outcome::TimedOut
+    }
+}
+
+/// Runs `rom` for at most `max_frames` frames, collecting every byte shifted
+/// out of the serial data register (SB) and stopping once a verdict appears.
+/// With `trace`, a Gameboy Doctor log is written there.
+fn emulate(rom: &[u8], max_frames: u32, trace: Option<&Path>) -> Result<String, String> {
+    let mut emulator = Emulator::new(rom.to_vec()).map_err(|e| e.to_string())?;
+    if let Some(trace) = trace {
+        let file = File::create(trace).map_err(|e| format!("Failed to create {}: {}", trace.display(), e))?;
+        emulator.enable_trace(Box::new(BufWriter::new(file)));
+        emulator.bus_mut().stub_ly(Some(0x90));
+    }
+    let (sender, receiver) = mpsc::channel();
+    emulator.bus_mut().serial_mut().set_callback(Box::new(move |byte| {
+        let _ = sender.send(byte);
+    }));
+
+    let mut output = String::new();
+    for _ in 0..max_frames {
+        emulator.run_frame();
+        output.extend(receiver.try_iter().map(char::from));
+        if !matches!(classify(&output), Outcome::TimedOut) {
+            break;
+        }
+    }
+    emulator.disable_trace();
+    Ok(output)
+}
+
+fn show_help() {
+    println!("🧪 Blargg Test ROM Runner");
+    println!();
+    println!("USAGE:");
+    println!("  cargo run --bin run-blargg -- <ROM_OR_DIRECTORY> [--trace <FILE>]");
+    println!();
+    println!("Runs a blargg test ROM (e.g. cpu_instrs.gb) headless and prints the text it");
+    println!("writes to the serial port. Given a directory, every .gb ROM inside it is run.");
+    println!();
+    println!("OPTIONS:");
+    println!("  --trace <FILE>      Write a Gameboy Doctor CPU log of a single ROM, with LY");
+    println!("                      stubbed to 0x90 as the reference logs expect");
+    println!("  --quiet             Only print errors");
+    println!();
+    println!("Exits with a non-zero status unless every ROM reports \"Passed\".");
+}
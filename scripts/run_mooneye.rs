@@ -0,0 +1,203 @@
+use std::env;
+use std::fs;
+use std::path::Path;
+
+use rusty_boy::config::Config;
+use rusty_boy::emulator::Emulator;
+use rusty_boy::launcher::collect_roms;
+use rusty_boy::log;
+use rusty_boy::info;
+
+const MOONEYE_RESOURCE: &str = "mooneye-test-suite";
+
+/// Frames a ROM may run before it is reported as timed out (about 10 seconds
+/// of emulated time, far more than any mooneye test needs).
+const MAX_FRAMES: u32 = 600;
+
+/// `LD B,B`, the software breakpoint that ends every mooneye test.
+const BREAKPOINT_OPCODE: u8 = 0x40;
+
+/// Register values a mooneye test leaves behind when it passes.
+const PASS_SIGNATURE: Registers = Registers { b: 3, c: 5, d: 8, e: 13, h: 21, l: 34 };
+
+/// The registers mooneye tests report their result in.
+#[derive(Clone, Copy, PartialEq)]
+struct Registers {
+    b: u8,
+    c: u8,
+    d: u8,
+    e: u8,
+    h: u8,
+    l: u8,
+}
+
+enum Outcome {
+    Passed,
+    Failed(Registers),
+    Error(String),
+}
+
+struct TestResult {
+    name: String,
+    expected_to_pass: bool,
+    outcome: Outcome,
+}
+
+fn main() {
+    let mut args: Vec<String> = env::args().collect();
+    log::init_from_args(&mut args);
+    let mut filter = None;
+
+    let mut args_iter = args[1..].iter();
+    while let Some(arg) = args_iter.next() {
+        match arg.as_str() {
+            "--filter" => match args_iter.next() {
+                Some(value) => filter = Some(value.clone()),
+                None => {
+                    eprintln!("❌ Missing value for --filter");
+                    std::process::exit(1);
+                }
+            },
+            "help" | "--help" | "-h" => {
+                show_help();
+                return;
+            }
+            _ => {
+                eprintln!("❌ Unknown argument: {}", arg);
+                show_help();
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let config = Config::load_or_exit();
+    let suite_dir = config.resource_path(MOONEYE_RESOURCE);
+    if !suite_dir.exists() {
+        eprintln!("❌ {} not found", suite_dir.display());
+        eprintln!("Run 'cargo run --bin clone-resources -- {}' first", MOONEYE_RESOURCE);
+        std::process::exit(1);
+    }
+
+    let mut roms = Vec::new();
+    collect_roms(&suite_dir, &mut roms);
+    roms.sort();
+    if let Some(filter) = &filter {
+        roms.retain(|rom| rom.to_string_lossy().contains(filter.as_str()));
+    }
+
+    if roms.is_empty() {
+        eprintln!("❌ No compiled .gb ROMs found in {}", suite_dir.display());
+        eprintln!("Build the suite first with 'make' (it needs wla-dx) in that directory");
+        std::process::exit(1);
+    }
+
+    info!("🧪 Running {} mooneye test ROM(s)...", roms.len());
+    let results: Vec<TestResult> = roms
+        .iter()
+        .map(|rom| {
+            let name = rom.strip_prefix(&suite_dir).unwrap_or(rom).display().to_string();
+            let outcome = match fs::read(rom) {
+                Ok(data) => run_rom(&data),
+                Err(e) => Outcome::Error(e.to_string()),
+            };
+            TestResult { expected_to_pass: expected_to_pass(rom), name, outcome }
+        })
+        .collect();
+
+    let unexpected_failures = print_summary(&results);
+    if unexpected_failures > 0 {
+        eprintln!("❌ {} expected-to-pass ROM(s) failed", unexpected_failures);
+        std::process::exit(1);
+    }
+    info!("✅ All expected-to-pass ROMs passed!");
+}
+
+/// Returns `true` if `rom` targets the original DMG. Mooneye encodes the
+/// models a test is meant for in a suffix such as `-dmgABC`, `-GS` or `-cgb`;
+/// tests without a suffix run on every model.
+fn expected_to_pass(rom: &Path) -> bool {
+    let stem = rom.file_stem().map(|stem| stem.to_string_lossy()).unwrap_or_default();
+    match stem.rsplit_once('-') {
+        Some((_, models)) => models.contains("dmgABC") || models.contains('G'),
+        None => true,
+    }
+}
+
+/// Runs `rom` and classifies the registers it leaves behind.
+fn run_rom(rom: &[u8]) -> Outcome {
+    match emulate(rom, MAX_FRAMES) {
+        Ok(registers) if registers == PASS_SIGNATURE => Outcome::Passed,
+        Ok(registers) => Outcome::Failed(registers),
+        Err(message) => Outcome::Error(message),
+    }
+}
+
+/// Runs `rom` for at most `max_frames` frames, stopping at the `LD B,B`
+/// breakpoint mooneye tests execute once their result is in the registers.
+fn emulate(rom: &[u8], max_frames: u32) -> Result<Registers, String> {
+    let mut emulator = Emulator::new(rom.to_vec()).map_err(|e| e.to_string())?;
+    let last_frame = emulator.bus().ppu().frame_count() + max_frames as u64;
+
+    while emulator.bus().ppu().frame_count() < last_frame {
+        let cpu = emulator.cpu();
+        if !cpu.halted && emulator.bus().read(cpu.pc) == BREAKPOINT_OPCODE {
+            return Ok(Registers { b: cpu.b, c: cpu.c, d: cpu.d, e: cpu.e, h: cpu.h, l: cpu.l });
+        }
+        emulator.step();
+    }
+    Err(format!("no LD B,B breakpoint after {} frames", max_frames))
+}
+
+/// Prints one line per ROM and returns the number of expected-to-pass ROMs
+/// that did not pass.
+fn print_summary(results: &[TestResult]) -> usize {
+    let width = results.iter().map(|result| result.name.len()).max().unwrap_or(0);
+    let mut passed = 0;
+    let mut unexpected_failures = 0;
+
+    info!();
+    for result in results {
+        let status = match &result.outcome {
+            Outcome::Passed => {
+                passed += 1;
+                "✅ pass".to_string()
+            }
+            Outcome::Failed(r) => format!(
+                "❌ fail  B={} C={} D={} E={} H={} L={}",
+                r.b, r.c, r.d, r.e, r.h, r.l
+            ),
+            Outcome::Error(message) => format!("❌ error  {}", message),
+        };
+        let passed_now = matches!(result.outcome, Outcome::Passed);
+        let note = if result.expected_to_pass { "" } else { "  (not a DMG test)" };
+
+        if result.expected_to_pass && !passed_now {
+            unexpected_failures += 1;
+            eprintln!("  {:<width$}  {}{}", result.name, status, note, width = width);
+        } else {
+            info!("  {:<width$}  {}{}", result.name, status, note, width = width);
+        }
+    }
+    info!();
+    info!("Passed {}/{} ROM(s)", passed, results.len());
+
+    unexpected_failures
+}
+
+fn show_help() {
+    println!("🧪 Mooneye Test Suite Runner");
+    println!();
+    println!("USAGE:");
+    println!("  cargo run --bin run-mooneye -- [--filter <SUBSTRING>]");
+    println!();
+    println!("Runs every compiled .gb ROM under resources/{} and checks", MOONEYE_RESOURCE);
+    println!("the Fibonacci register signature (B=3 C=5 D=8 E=13 H=21 L=34) that");
+    println!("mooneye tests leave behind when they pass.");
+    println!();
+    println!("OPTIONS:");
+    println!("  --filter <SUBSTRING>  Only run ROMs whose path contains SUBSTRING");
+    println!("  --quiet               Only print errors");
+    println!();
+    println!("Exits with a non-zero status if any ROM meant for the DMG fails. ROMs");
+    println!("for other models (e.g. -cgb, -sgb) are run but never fail the suite.");
+}
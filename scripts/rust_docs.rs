@@ -1,11 +1,19 @@
 use std::env;
 use std::fs;
+use std::path::Path;
 use std::process::Command;
 
+use rusty_boy::launcher::{BROWSER_ENV, NO_OPEN_FLAG, open_in_browser, take_flag};
+use rusty_boy::log::{self, LogCommand};
+use rusty_boy::{info, warn};
+
 const BOOKMARK_FILE: &str = ".rust_docs_bookmark";
+const MAX_HISTORY: usize = 50;
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
+    let mut args: Vec<String> = env::args().collect();
+    log::init_from_args(&mut args);
+    let no_open = take_flag(&mut args, NO_OPEN_FLAG);
     
     if args.len() > 1 {
         match args[1].as_str() {
@@ -15,69 +23,138 @@ fn main() {
                     eprintln!("Example: cargo run --bin rust-docs save \"book/ch01-01-installation.html\"");
                     std::process::exit(1);
                 }
+                validate_page(&args[2]);
                 save_bookmark(&args[2]);
             }
             "list" => {
                 list_common_pages();
             }
+            "history" => {
+                show_history();
+            }
+            "search" => {
+                if args.len() < 3 {
+                    eprintln!("❌ Usage: cargo run --bin rust-docs search <term>");
+                    eprintln!("Example: cargo run --bin rust-docs search Vec");
+                    std::process::exit(1);
+                }
+                search_std(&args[2..].join(" "), no_open);
+            }
+            "back" => {
+                let mut history = load_history();
+                if history.len() < 2 {
+                    eprintln!("❌ No previous page in the history");
+                    std::process::exit(1);
+                }
+                // The current page becomes the oldest entry, so going back
+                // repeatedly walks the whole history instead of flipping
+                // between the last two pages
+                history.rotate_right(1);
+                let previous = history[history.len() - 1].clone();
+                info!("⬅️  Going back to: {}", previous);
+                if write_history(&history) {
+                    info!("📖 Bookmarked: {}", previous);
+                }
+                open_rust_docs(Some(&previous), no_open);
+            }
             "help" | "--help" | "-h" => {
                 show_help();
             }
             page => {
                 // Treat as a direct page to open
+                validate_page(page);
                 save_bookmark(page);
-                open_rust_docs(Some(page));
+                open_rust_docs(Some(page), no_open);
             }
         }
     } else {
         // No arguments - open last bookmarked page or start from beginning
         let bookmark = load_bookmark();
-        open_rust_docs(bookmark.as_deref());
+        open_rust_docs(bookmark.as_deref(), no_open);
     }
 }
 
+/// Records `page` as the most recent entry of the bookmark history. A page
+/// that is already in the history is moved to the end instead of duplicated.
 fn save_bookmark(page: &str) {
+    let mut history = load_history();
+    history.retain(|entry| entry != page);
+    history.push(page.to_string());
+    if history.len() > MAX_HISTORY {
+        history.drain(..history.len() - MAX_HISTORY);
+    }
+    
+    if write_history(&history) {
+        info!("📖 Bookmarked: {}", page);
+    }
+}
+
+/// Writes the bookmark history, oldest first. Returns `false` after warning
+/// if the file cannot be written.
+fn write_history(history: &[String]) -> bool {
     let current_dir = env::current_dir().expect("Failed to get current directory");
     let bookmark_path = current_dir.join(BOOKMARK_FILE);
     
-    if let Err(e) = fs::write(&bookmark_path, page) {
-        eprintln!("⚠️  Warning: Failed to save bookmark: {}", e);
-    } else {
-        println!("📖 Bookmarked: {}", page);
+    match fs::write(&bookmark_path, history.join("\n") + "\n") {
+        Ok(()) => true,
+        Err(e) => {
+            warn!("⚠️  Warning: Failed to save bookmark: {}", e);
+            false
+        }
     }
 }
 
 fn load_bookmark() -> Option<String> {
+    let bookmark = load_history().pop()?;
+    info!("📚 Resuming from bookmark: {}", bookmark);
+    Some(bookmark)
+}
+
+/// Reads the visited pages, oldest first.
+fn load_history() -> Vec<String> {
     let current_dir = env::current_dir().expect("Failed to get current directory");
     let bookmark_path = current_dir.join(BOOKMARK_FILE);
     
     if bookmark_path.exists() {
         match fs::read_to_string(&bookmark_path) {
             Ok(content) => {
-                let bookmark = content.trim();
-                if !bookmark.is_empty() {
-                    println!("📚 Resuming from bookmark: {}", bookmark);
-                    return Some(bookmark.to_string());
-                }
+                return content
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty())
+                    .map(str::to_string)
+                    .collect();
             }
             Err(e) => {
-                eprintln!("⚠️  Warning: Failed to read bookmark: {}", e);
+                warn!("⚠️  Warning: Failed to read bookmark: {}", e);
             }
         }
     }
     
-    None
+    Vec::new()
 }
 
-fn open_rust_docs(page: Option<&str>) {
-    println!("🦀 Opening Rust documentation...");
+fn show_history() {
+    let history = load_history();
     
-    // Get the Rust documentation path
+    if history.is_empty() {
+        println!("📭 No pages visited yet");
+        return;
+    }
+    
+    println!("📜 Visited pages (most recent last):");
+    for (i, page) in history.iter().enumerate() {
+        println!("  {:>2}. {}", i + 1, page);
+    }
+}
+
+fn get_doc_path() -> String {
     let doc_output = Command::new("rustup")
-        .args(&["doc", "--path"])
+        .args(["doc", "--path"])
+        .logged()
         .output();
     
-    let doc_path = match doc_output {
+    match doc_output {
         Ok(output) if output.status.success() => {
             String::from_utf8_lossy(&output.stdout).trim().to_string()
         }
@@ -86,49 +163,93 @@ fn open_rust_docs(page: Option<&str>) {
             eprintln!("Make sure Rust is installed and rustup is available");
             std::process::exit(1);
         }
-    };
+    }
+}
+
+/// Returns the documentation root directory, i.e. `doc_path` without its
+/// trailing `index.html`.
+fn doc_root(doc_path: &str) -> &str {
+    doc_path.strip_suffix("index.html").unwrap_or(doc_path)
+}
+
+fn is_full_url(page: &str) -> bool {
+    page.starts_with("http") || page.starts_with("file://")
+}
+
+/// Exits with a hint when a relative `page` does not exist in the local
+/// documentation, so typos never reach the browser or the bookmark file.
+fn validate_page(page: &str) {
+    if is_full_url(page) {
+        return;
+    }
+    
+    let doc_path = get_doc_path();
+    // Anchors and queries are resolved by the browser, not the filesystem
+    let file_part = page.split(['#', '?']).next().unwrap_or(page);
+    let local_path = Path::new(doc_root(&doc_path)).join(file_part.trim_start_matches('/'));
+    
+    if !local_path.exists() {
+        eprintln!("❌ Page not found: {}", local_path.display());
+        eprintln!("Run 'cargo run --bin rust-docs list' to see the available pages");
+        std::process::exit(1);
+    }
+}
+
+fn search_std(query: &str, no_open: bool) {
+    info!("🔍 Searching the standard library for: {}", query);
+    let doc_path = get_doc_path();
+    let url = format!("file://{}std/index.html?search={}", doc_root(&doc_path), url_encode(query));
+    open_rust_docs(Some(&url), no_open);
+}
+
+/// Percent-encodes everything except RFC 3986 unreserved characters.
+fn url_encode(text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.' | b'~') {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+fn open_rust_docs(page: Option<&str>, no_open: bool) {
+    info!("🦀 Opening Rust documentation...");
+    
+    // Get the Rust documentation path
+    let doc_path = get_doc_path();
     
     // Construct the full URL
     let url = if let Some(page) = page {
-        if page.starts_with("http") || page.starts_with("file://") {
+        if is_full_url(page) {
             // Already a full URL, use it directly
             page.to_string()
         } else {
-            // Relative path, construct URL from doc_path
-            // Remove the index.html from doc_path to get the base directory
-            let base_path = if doc_path.ends_with("index.html") {
-                doc_path.trim_end_matches("index.html")
-            } else {
-                &doc_path
-            };
-            format!("file://{}{}", base_path, page.trim_start_matches('/'))
+            // Relative path, construct URL from the documentation root
+            format!("file://{}{}", doc_root(&doc_path), page.trim_start_matches('/'))
         }
     } else {
         format!("file://{}", doc_path)
     };
     
-    println!("🌐 Opening: {}", url);
+    if no_open {
+        info!("🌐 Documentation URL: {}", url);
+        return;
+    }
+    
+    info!("🌐 Opening: {}", url);
     
     // Open in default browser
-    let result = if cfg!(target_os = "macos") {
-        Command::new("open").arg(&url).status()
-    } else if cfg!(target_os = "linux") {
-        Command::new("xdg-open").arg(&url).status()
-    } else if cfg!(target_os = "windows") {
-        Command::new("cmd").args(&["/C", "start", &url]).status()
-    } else {
-        eprintln!("❌ Unsupported operating system");
-        std::process::exit(1);
-    };
-    
-    match result {
-        Ok(status) if status.success() => {
-            println!("✅ Documentation opened successfully!");
-            println!();
+    match open_in_browser(&url) {
+        Ok(()) => {
+            info!("✅ Documentation opened successfully!");
+            info!();
             show_usage_tips();
         }
-        _ => {
-            eprintln!("❌ Failed to open documentation in browser");
+        Err(e) => {
+            eprintln!("❌ Failed to open documentation in browser: {}", e);
             eprintln!("You can manually open: {}", url);
         }
     }
@@ -177,23 +298,35 @@ fn show_help() {
     println!("  <page>              Open specific page and bookmark it");
     println!("  save <page>         Save a bookmark without opening");
     println!("  list                List common documentation pages");
+    println!("  search <term>       Search the standard library documentation");
+    println!("  history             List previously visited pages");
+    println!("  back                Open the page visited before the current one; repeat to go");
+    println!("                      further back");
     println!("  help                Show this help message");
     println!();
+    println!("OPTIONS:");
+    println!("  --no-open           Resolve and print the URL without opening a browser");
+    println!("  --quiet             Only print errors");
+    println!("  --verbose           Also print every external command before running it");
+    println!();
     println!("EXAMPLES:");
     println!("  cargo run --bin rust-docs");
     println!("  cargo run --bin rust-docs book/ch04-00-understanding-ownership.html");
     println!("  cargo run --bin rust-docs save \"std/vec/struct.Vec.html\"");
     println!("  cargo run --bin rust-docs list");
+    println!("  cargo run --bin rust-docs search Vec");
     println!();
     println!("📁 Bookmark file: {}", BOOKMARK_FILE);
-    println!("   This file will be created in your project root and can be committed to git.");
+    println!("   This file keeps the last {} visited pages. It is created in your project", MAX_HISTORY);
+    println!("   root and can be committed to git.");
+    println!("🌐 Set {} to open the documentation with another browser", BROWSER_ENV);
 }
 
 fn show_usage_tips() {
-    println!("💡 Usage tips:");
-    println!("  • When you find an interesting page, copy its path from the URL");
-    println!("  • Save it with: cargo run --bin rust-docs save \"<page-path>\"");
-    println!("  • Next time, just run: cargo run --bin rust-docs");
-    println!("  • The bookmark file ({}) can be committed to git", BOOKMARK_FILE);
-    println!("  • Use 'cargo run --bin rust-docs list' to see common pages");
+    info!("💡 Usage tips:");
+    info!("  • When you find an interesting page, copy its path from the URL");
+    info!("  • Save it with: cargo run --bin rust-docs save \"<page-path>\"");
+    info!("  • Next time, just run: cargo run --bin rust-docs");
+    info!("  • The bookmark file ({}) can be committed to git", BOOKMARK_FILE);
+    info!("  • Use 'cargo run --bin rust-docs list' to see common pages");
 }
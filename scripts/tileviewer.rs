@@ -0,0 +1,89 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use rusty_boy::emulator::Emulator;
+use rusty_boy::log;
+use rusty_boy::screenshot;
+use rusty_boy::info;
+
+/// Frames run when `--frames` is not given: one second of emulated time.
+const DEFAULT_FRAMES: u32 = 60;
+
+fn main() {
+    let mut args: Vec<String> = env::args().collect();
+    log::init_from_args(&mut args);
+    let mut rom = None;
+    let mut frames = DEFAULT_FRAMES;
+    let mut out = None;
+
+    let mut args_iter = args[1..].iter();
+    while let Some(arg) = args_iter.next() {
+        match arg.as_str() {
+            "--frames" => match args_iter.next().map(|value| value.parse()) {
+                Some(Ok(value)) => frames = value,
+                _ => {
+                    eprintln!("❌ --frames expects a number of frames");
+                    std::process::exit(1);
+                }
+            },
+            "--out" => match args_iter.next() {
+                Some(value) => out = Some(PathBuf::from(value)),
+                None => {
+                    eprintln!("❌ Missing value for --out");
+                    std::process::exit(1);
+                }
+            },
+            "help" | "--help" | "-h" => {
+                show_help();
+                return;
+            }
+            path if rom.is_none() && !path.starts_with("--") => rom = Some(PathBuf::from(path)),
+            _ => {
+                eprintln!("❌ Unknown argument: {}", arg);
+                show_help();
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let Some(rom) = rom else {
+        eprintln!("❌ Usage: cargo run --bin tileviewer -- <ROM> [--frames <N>] [--out <PNG>]");
+        std::process::exit(1);
+    };
+    let out = out.unwrap_or_else(|| rom.with_extension("tiles.png"));
+
+    let data = fs::read(&rom).unwrap_or_else(|e| {
+        eprintln!("❌ Failed to read {}: {}", rom.display(), e);
+        std::process::exit(1);
+    });
+    let mut emulator = Emulator::new(data).unwrap_or_else(|e| {
+        eprintln!("❌ Failed to load {}: {}", rom.display(), e);
+        std::process::exit(1);
+    });
+
+    info!("🎮 Running {} for {} frame(s)...", rom.display(), frames);
+    emulator.run_frames(frames);
+    let sheet = emulator.bus().ppu().tile_sheet();
+    if let Err(e) = screenshot::write_rgba_png(&out, sheet.width, sheet.height, &sheet.pixels) {
+        eprintln!("❌ Failed to write {}: {}", out.display(), e);
+        std::process::exit(1);
+    }
+    info!("✅ Saved the {}×{} tile sheet to {}", sheet.width, sheet.height, out.display());
+}
+
+fn show_help() {
+    println!("🧩 VRAM Tile Viewer");
+    println!();
+    println!("USAGE:");
+    println!("  cargo run --bin tileviewer -- <ROM> [--frames <N>] [--out <PNG>]");
+    println!();
+    println!("Runs a ROM without a window, then saves every tile in VRAM as a PNG sheet of");
+    println!("16 tiles per row, in grayscale. For CGB games the tiles of VRAM bank 1 follow");
+    println!("those of bank 0.");
+    println!();
+    println!("OPTIONS:");
+    println!("  --frames <N>  Frames to run before the snapshot (default: {})", DEFAULT_FRAMES);
+    println!("  --out <PNG>   Image to write (default: the ROM path with a .tiles.png extension)");
+    println!("  --quiet       Only print errors");
+}
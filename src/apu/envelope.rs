@@ -0,0 +1,58 @@
+//! The volume envelope of the pulse and noise channels (NRx2).
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub(super) struct Envelope {
+    /// The raw NRx2 value.
+    register: u8,
+    volume: u8,
+    timer: u8,
+}
+
+impl Envelope {
+    pub(super) fn read(&self) -> u8 {
+        self.register
+    }
+
+    pub(super) fn write(&mut self, value: u8) {
+        self.register = value;
+    }
+
+    /// The DAC is powered while any of the upper five bits of NRx2 is set;
+    /// clearing them all also disables the channel.
+    pub(super) fn dac_enabled(&self) -> bool {
+        self.register & 0xF8 != 0
+    }
+
+    pub(super) fn volume(&self) -> u8 {
+        self.volume
+    }
+
+    fn period(&self) -> u8 {
+        self.register & 0x07
+    }
+
+    pub(super) fn trigger(&mut self) {
+        self.volume = self.register >> 4;
+        self.timer = self.period();
+    }
+
+    /// Clocked at 64 Hz by the frame sequencer. A period of 0 stops the
+    /// envelope.
+    pub(super) fn clock(&mut self) {
+        if self.period() == 0 || self.timer == 0 {
+            return;
+        }
+
+        self.timer -= 1;
+        if self.timer == 0 {
+            self.timer = self.period();
+            if self.register & 0x08 != 0 {
+                self.volume = (self.volume + 1).min(15);
+            } else {
+                self.volume = self.volume.saturating_sub(1);
+            }
+        }
+    }
+}
@@ -0,0 +1,40 @@
+//! The length counter shared by every channel (NRx1 and bit 6 of NRx4).
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct LengthCounter {
+    /// 64 for the pulse and noise channels, 256 for the wave channel.
+    max: u16,
+    counter: u16,
+    pub(super) enabled: bool,
+}
+
+impl LengthCounter {
+    pub(super) fn new(max: u16) -> LengthCounter {
+        LengthCounter { max, counter: 0, enabled: false }
+    }
+
+    /// Loads the length field written to NRx1.
+    pub(super) fn load(&mut self, length: u8) {
+        self.counter = self.max - length as u16;
+    }
+
+    /// Triggering a channel whose length has run out restarts it at the
+    /// maximum length.
+    pub(super) fn trigger(&mut self) {
+        if self.counter == 0 {
+            self.counter = self.max;
+        }
+    }
+
+    /// Clocked at 256 Hz by the frame sequencer. Returns `true` when the
+    /// length runs out and the channel must be disabled.
+    pub(super) fn clock(&mut self) -> bool {
+        if !self.enabled || self.counter == 0 {
+            return false;
+        }
+        self.counter -= 1;
+        self.counter == 0
+    }
+}
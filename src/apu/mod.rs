@@ -0,0 +1,323 @@
+//! The audio processing unit: the sound channels, the frame sequencer that
+//! clocks their length counters, envelopes and sweep, and the mixer.
+//!
+//! The APU is ticked once per M-cycle and resamples its output to a host
+//! sample rate, producing interleaved stereo `f32` samples. See the "Audio"
+//! chapters of Pan Docs. With the `audio` feature, [`AudioSink`] plays them
+//! on the host audio device.
+
+mod envelope;
+mod length;
+mod noise;
+mod pulse;
+#[cfg(feature = "audio")]
+mod sink;
+mod wave;
+
+use serde::{Deserialize, Serialize};
+
+use noise::Noise;
+use pulse::Pulse;
+use wave::Wave;
+
+#[cfg(feature = "audio")]
+pub use sink::{AudioError, AudioSink};
+
+/// Sample rate used unless another one is configured.
+pub const DEFAULT_SAMPLE_RATE: u32 = 48_000;
+
+/// M-cycles per second of emulated time.
+const M_CYCLES_PER_SECOND: u32 = 1_048_576;
+/// M-cycles between frame sequencer steps, which run at 512 Hz.
+const FRAME_SEQUENCER_PERIOD: u16 = 2048;
+
+const NR52_POWER: u8 = 0x80;
+
+/// What the mixer needs from a sound channel.
+trait Channel {
+    /// Whether the channel is playing, as reported in NR52.
+    fn enabled(&self) -> bool;
+    fn dac_enabled(&self) -> bool;
+    /// Returns the current digital output, 0–15.
+    fn output(&self) -> u8;
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Apu {
+    powered: bool,
+    channel1: Pulse,
+    channel2: Pulse,
+    channel3: Wave,
+    channel4: Noise,
+    /// Master volume (NR50).
+    nr50: u8,
+    /// Panning (NR51): bits 0–3 send channels 1–4 right, bits 4–7 left.
+    nr51: u8,
+    frame_sequencer_cycles: u16,
+    frame_sequencer_step: u8,
+    /// Host configuration rather than machine state, so it is kept when a
+    /// save state is loaded.
+    #[serde(skip)]
+    sample_rate: u32,
+    /// Emulation speed multiplier, also host configuration.
+    #[serde(skip)]
+    speed: f64,
+    /// Samples taken per second of emulated time: `sample_rate` divided by
+    /// `speed`, so the host still receives `sample_rate` samples per second
+    /// of wall-clock time. 0 mutes the output.
+    #[serde(skip)]
+    output_rate: u32,
+    /// Accumulates `output_rate` every M-cycle; a sample is taken each time
+    /// it reaches `M_CYCLES_PER_SECOND`.
+    sample_clock: u32,
+    /// Charge of the high-pass filter capacitor of each side.
+    capacitors: [f32; 2],
+    #[serde(skip)]
+    samples: Vec<f32>,
+}
+
+impl Default for Apu {
+    fn default() -> Apu {
+        Apu::new(DEFAULT_SAMPLE_RATE)
+    }
+}
+
+impl Apu {
+    pub fn new(sample_rate: u32) -> Apu {
+        Apu {
+            powered: false,
+            channel1: Pulse::with_sweep(),
+            channel2: Pulse::new(),
+            channel3: Wave::new(),
+            channel4: Noise::new(),
+            nr50: 0,
+            nr51: 0,
+            frame_sequencer_cycles: 0,
+            frame_sequencer_step: 0,
+            sample_rate,
+            speed: 1.0,
+            output_rate: sample_rate,
+            sample_clock: 0,
+            capacitors: [0.0; 2],
+            samples: Vec::new(),
+        }
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        self.sample_rate = sample_rate;
+        self.update_output_rate();
+    }
+
+    pub fn speed(&self) -> f64 {
+        self.speed
+    }
+
+    /// Adjusts sample generation for running at `speed` times the real
+    /// hardware, keeping the host's sample rate. Pitch follows the emulation
+    /// speed; an infinite speed mutes the output.
+    pub fn set_speed(&mut self, speed: f64) {
+        self.speed = speed;
+        self.update_output_rate();
+    }
+
+    fn update_output_rate(&mut self) {
+        self.output_rate = if self.speed.is_finite() {
+            (self.sample_rate as f64 / self.speed).round().min(M_CYCLES_PER_SECOND as f64) as u32
+        } else {
+            0
+        };
+    }
+
+    /// Takes the samples produced since the last call, as interleaved
+    /// left/right pairs in `-1.0..=1.0`.
+    pub fn take_samples(&mut self) -> Vec<f32> {
+        std::mem::take(&mut self.samples)
+    }
+
+    /// Advances the APU by one M-cycle.
+    pub fn tick(&mut self) {
+        if self.powered {
+            for _ in 0..4 {
+                self.channel1.tick();
+                self.channel2.tick();
+                self.channel3.tick();
+                self.channel4.tick();
+            }
+
+            self.frame_sequencer_cycles += 1;
+            if self.frame_sequencer_cycles == FRAME_SEQUENCER_PERIOD {
+                self.frame_sequencer_cycles = 0;
+                self.step_frame_sequencer();
+            }
+        }
+
+        self.sample_clock += self.output_rate;
+        if self.sample_clock >= M_CYCLES_PER_SECOND {
+            self.sample_clock -= M_CYCLES_PER_SECOND;
+            self.push_sample();
+        }
+    }
+
+    /// Clocks the length counters on even steps, the sweep on steps 2 and 6
+    /// and the envelopes on step 7.
+    fn step_frame_sequencer(&mut self) {
+        let step = self.frame_sequencer_step;
+        if step.is_multiple_of(2) {
+            self.channel1.clock_length();
+            self.channel2.clock_length();
+            self.channel3.clock_length();
+            self.channel4.clock_length();
+        }
+        if step == 2 || step == 6 {
+            self.channel1.clock_sweep();
+        }
+        if step == 7 {
+            self.channel1.clock_envelope();
+            self.channel2.clock_envelope();
+            self.channel4.clock_envelope();
+        }
+        self.frame_sequencer_step = (step + 1) % 8;
+    }
+
+    /// Mixes the channels into one stereo sample.
+    fn push_sample(&mut self) {
+        // Keep at most a second of audio when nobody drains the buffer
+        if self.samples.len() >= self.sample_rate as usize * 2 {
+            return;
+        }
+
+        let outputs = self.channels().map(dac_output);
+        let volumes = [(self.nr50 >> 4) & 0x07, self.nr50 & 0x07];
+        // Left uses the upper nibble of NR51, right the lower one
+        let pan_shifts = [4, 0];
+        // The charge factor of Pan Docs, for our sample rate
+        let charge = 0.999958f32.powf(4_194_304.0 / self.output_rate as f32);
+
+        for side in 0..2 {
+            let mut mixed = 0.0;
+            for (channel, output) in outputs.iter().enumerate() {
+                if let Some(output) = output
+                    && self.nr51 & (1 << (channel + pan_shifts[side])) != 0
+                {
+                    mixed += output;
+                }
+            }
+            let input = mixed / 4.0 * (volumes[side] + 1) as f32 / 8.0;
+
+            let output = input - self.capacitors[side];
+            self.capacitors[side] = input - output * charge;
+            self.samples.push(output);
+        }
+    }
+
+    /// Returns the channels in NR51/NR52 bit order.
+    fn channels(&self) -> [&dyn Channel; 4] {
+        [&self.channel1, &self.channel2, &self.channel3, &self.channel4]
+    }
+
+    pub fn read(&self, addr: u16) -> u8 {
+        match addr {
+            0xFF10..=0xFF14 => self.channel1.read(addr - 0xFF10),
+            0xFF15..=0xFF19 => self.channel2.read(addr - 0xFF15),
+            0xFF1A..=0xFF1E => self.channel3.read(addr - 0xFF1A),
+            0xFF1F..=0xFF23 => self.channel4.read(addr - 0xFF1F),
+            0xFF24 => self.nr50,
+            0xFF25 => self.nr51,
+            0xFF26 => {
+                let channels = self
+                    .channels()
+                    .iter()
+                    .enumerate()
+                    .fold(0, |bits, (i, channel)| bits | (channel.enabled() as u8) << i);
+                0x70 | (self.powered as u8) << 7 | channels
+            }
+            0xFF30..=0xFF3F => self.channel3.read_wave_ram((addr - 0xFF30) as usize),
+            _ => 0xFF,
+        }
+    }
+
+    pub fn write(&mut self, addr: u16, value: u8) {
+        if let 0xFF30..=0xFF3F = addr {
+            self.channel3.write_wave_ram((addr - 0xFF30) as usize, value);
+            return;
+        }
+        if addr == 0xFF26 {
+            let powered = value & NR52_POWER != 0;
+            if self.powered && !powered {
+                // Powering off clears every register, but not wave RAM
+                *self = Apu {
+                    channel3: self.channel3.reset(),
+                    samples: std::mem::take(&mut self.samples),
+                    speed: self.speed,
+                    output_rate: self.output_rate,
+                    ..Apu::new(self.sample_rate)
+                };
+            } else if !self.powered && powered {
+                self.frame_sequencer_cycles = 0;
+                self.frame_sequencer_step = 0;
+            }
+            self.powered = powered;
+            return;
+        }
+        if !self.powered {
+            return;
+        }
+
+        match addr {
+            0xFF10..=0xFF14 => self.channel1.write(addr - 0xFF10, value),
+            0xFF15..=0xFF19 => self.channel2.write(addr - 0xFF15, value),
+            0xFF1A..=0xFF1E => self.channel3.write(addr - 0xFF1A, value),
+            0xFF1F..=0xFF23 => self.channel4.write(addr - 0xFF1F, value),
+            0xFF24 => self.nr50 = value,
+            0xFF25 => self.nr51 = value,
+            _ => {}
+        }
+    }
+}
+
+/// Converts the digital output of a channel to an analog level, or `None`
+/// while its DAC is off. Digital 0 maps to 1.0 and 15 to -1.0.
+fn dac_output(channel: &dyn Channel) -> Option<f32> {
+    channel.dac_enabled().then(|| 1.0 - channel.output() as f32 / 7.5)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Ticks `apu` for one second of emulated time and returns the number of
+    /// samples it produced per side.
+    fn samples_per_second(apu: &mut Apu) -> usize {
+        for _ in 0..M_CYCLES_PER_SECOND {
+            apu.tick();
+        }
+        apu.take_samples().len() / 2
+    }
+
+    #[test]
+    fn speed_scales_the_output_rate() {
+        let mut apu = Apu::new(DEFAULT_SAMPLE_RATE);
+        assert_eq!(samples_per_second(&mut apu), DEFAULT_SAMPLE_RATE as usize);
+
+        apu.set_speed(2.0);
+        assert_eq!(apu.output_rate, DEFAULT_SAMPLE_RATE / 2);
+        assert_eq!(samples_per_second(&mut apu), DEFAULT_SAMPLE_RATE as usize / 2);
+        apu.set_speed(0.5);
+        assert_eq!(apu.output_rate, DEFAULT_SAMPLE_RATE * 2);
+    }
+
+    #[test]
+    fn uncapped_speed_mutes_the_output() {
+        let mut apu = Apu::new(DEFAULT_SAMPLE_RATE);
+        apu.set_speed(crate::limiter::UNCAPPED);
+
+        assert_eq!(apu.output_rate, 0);
+        assert_eq!(samples_per_second(&mut apu), 0);
+        apu.set_speed(1.0);
+        assert_eq!(apu.output_rate, DEFAULT_SAMPLE_RATE);
+    }
+}
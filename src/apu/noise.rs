@@ -0,0 +1,217 @@
+//! Noise channel 4, driven by a linear-feedback shift register (LFSR).
+
+use serde::{Deserialize, Serialize};
+
+use super::Channel;
+use super::envelope::Envelope;
+use super::length::LengthCounter;
+
+/// Clock ticks per LFSR step for each divisor code of NR43, before the shift
+/// is applied. See the "Sound Channel 4" chapter of Pan Docs.
+const DIVISORS: [u16; 8] = [8, 16, 32, 48, 64, 80, 96, 112];
+
+const NR43_WIDTH_7: u8 = 0x08;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct Noise {
+    enabled: bool,
+    length: LengthCounter,
+    envelope: Envelope,
+    /// The raw NR43 value: clock shift, LFSR width and divisor code.
+    nr43: u8,
+    /// The 15-bit shift register; the channel outputs its inverted bit 0.
+    lfsr: u16,
+    /// Clock ticks until the next LFSR step.
+    timer: u32,
+}
+
+impl Noise {
+    pub(super) fn new() -> Noise {
+        Noise {
+            enabled: false,
+            length: LengthCounter::new(64),
+            envelope: Envelope::default(),
+            nr43: 0,
+            lfsr: 0,
+            timer: 0,
+        }
+    }
+
+    /// Reads register `index` (0 for the unused `0xFF1F`, then NR41 through
+    /// NR44).
+    pub(super) fn read(&self, index: u16) -> u8 {
+        match index {
+            0 | 1 => 0xFF,
+            2 => self.envelope.read(),
+            3 => self.nr43,
+            _ => 0xBF | (self.length.enabled as u8) << 6,
+        }
+    }
+
+    pub(super) fn write(&mut self, index: u16, value: u8) {
+        match index {
+            0 => {}
+            1 => self.length.load(value & 0x3F),
+            2 => {
+                self.envelope.write(value);
+                if !self.envelope.dac_enabled() {
+                    self.enabled = false;
+                }
+            }
+            3 => self.nr43 = value,
+            _ => {
+                self.length.enabled = value & 0x40 != 0;
+                if value & 0x80 != 0 {
+                    self.trigger();
+                }
+            }
+        }
+    }
+
+    fn trigger(&mut self) {
+        self.enabled = self.envelope.dac_enabled();
+        self.length.trigger();
+        self.envelope.trigger();
+        self.lfsr = 0x7FFF;
+        self.timer = self.period();
+    }
+
+    /// Clock ticks per LFSR step: the divisor shifted left by the clock
+    /// shift of NR43.
+    fn period(&self) -> u32 {
+        let divisor = DIVISORS[(self.nr43 & 0x07) as usize] as u32;
+        divisor << (self.nr43 >> 4)
+    }
+
+    /// Shifts the LFSR once. The XOR of its two lowest bits is fed back into
+    /// bit 14 and, in 7-bit mode, into bit 6 as well.
+    fn step_lfsr(&mut self) {
+        let feedback = (self.lfsr ^ (self.lfsr >> 1)) & 0x01;
+        self.lfsr = (self.lfsr >> 1) | feedback << 14;
+        if self.nr43 & NR43_WIDTH_7 != 0 {
+            self.lfsr = (self.lfsr & !(1 << 6)) | feedback << 6;
+        }
+    }
+
+    /// Advances the channel by one clock tick.
+    pub(super) fn tick(&mut self) {
+        if self.timer > 0 {
+            self.timer -= 1;
+        }
+        if self.timer == 0 {
+            self.timer = self.period();
+            // Clock shifts 14 and 15 leave the LFSR unclocked
+            if self.nr43 >> 4 < 14 {
+                self.step_lfsr();
+            }
+        }
+    }
+
+    pub(super) fn clock_length(&mut self) {
+        if self.length.clock() {
+            self.enabled = false;
+        }
+    }
+
+    pub(super) fn clock_envelope(&mut self) {
+        self.envelope.clock();
+    }
+}
+
+impl Channel for Noise {
+    fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn dac_enabled(&self) -> bool {
+        self.envelope.dac_enabled()
+    }
+
+    fn output(&self) -> u8 {
+        if !self.enabled {
+            return 0;
+        }
+        (!self.lfsr & 0x01) as u8 * self.envelope.volume()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns a channel triggered at full volume with NR43 set to `nr43`.
+    fn triggered(nr43: u8) -> Noise {
+        let mut noise = Noise::new();
+        noise.write(2, 0xF0);
+        noise.write(3, nr43);
+        noise.write(4, 0x80);
+        noise
+    }
+
+    /// Steps the LFSR `count` times, returning the output bit after each
+    /// step.
+    fn outputs(noise: &mut Noise, count: usize) -> Vec<u8> {
+        (0..count)
+            .map(|_| {
+                noise.step_lfsr();
+                noise.output() / 15
+            })
+            .collect()
+    }
+
+    fn bits(pattern: &str) -> Vec<u8> {
+        pattern.bytes().map(|bit| bit - b'0').collect()
+    }
+
+    #[test]
+    fn lfsr_sequence_in_15_bit_mode() {
+        let mut noise = triggered(0x00);
+
+        assert_eq!(outputs(&mut noise, 32), bits("00000000000000111111111111110111"));
+    }
+
+    #[test]
+    fn lfsr_sequence_in_7_bit_mode() {
+        let mut noise = triggered(NR43_WIDTH_7);
+
+        assert_eq!(outputs(&mut noise, 32), bits("00000011111101111100111101011100"));
+    }
+
+    #[test]
+    fn lfsr_repeats_after_its_full_period() {
+        for (nr43, period) in [(0x00, 32767), (NR43_WIDTH_7, 127)] {
+            let mut noise = triggered(nr43);
+            outputs(&mut noise, 16);
+            let state = noise.lfsr;
+            let steps = (1..).find(|_| {
+                noise.step_lfsr();
+                noise.lfsr == state
+            });
+            assert_eq!(steps, Some(period), "NR43 {:#04X}", nr43);
+        }
+    }
+
+    #[test]
+    fn lfsr_steps_once_per_period() {
+        // Divisor code 1 (16) shifted left by 2
+        let mut noise = triggered(0x21);
+        assert_eq!(noise.period(), 64);
+
+        for _ in 0..63 {
+            noise.tick();
+        }
+        assert_eq!(noise.lfsr, 0x7FFF);
+        noise.tick();
+        assert_eq!(noise.lfsr, 0x3FFF);
+    }
+
+    #[test]
+    fn clock_shifts_14_and_15_stop_the_lfsr() {
+        let mut noise = triggered(0xE0);
+
+        for _ in 0..noise.period() * 2 {
+            noise.tick();
+        }
+        assert_eq!(noise.lfsr, 0x7FFF);
+    }
+}
@@ -0,0 +1,309 @@
+//! Pulse channels 1 and 2: a square wave with a selectable duty cycle, a
+//! volume envelope and, on channel 1 only, a frequency sweep.
+
+use serde::{Deserialize, Serialize};
+
+use super::Channel;
+use super::envelope::Envelope;
+use super::length::LengthCounter;
+
+/// Waveforms of the four duty cycles (12.5%, 25%, 50% and 75%), as listed in
+/// the "Sound Channel 1" chapter of Pan Docs.
+const DUTY_WAVEFORMS: [[u8; 8]; 4] = [
+    [0, 0, 0, 0, 0, 0, 0, 1],
+    [1, 0, 0, 0, 0, 0, 0, 1],
+    [1, 0, 0, 0, 0, 1, 1, 1],
+    [0, 1, 1, 1, 1, 1, 1, 0],
+];
+
+/// Largest value of the 11-bit frequency; a sweep past it disables the
+/// channel.
+const MAX_FREQUENCY: u16 = 0x7FF;
+
+/// The frequency sweep of channel 1 (NR10).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Sweep {
+    register: u8,
+    enabled: bool,
+    shadow_frequency: u16,
+    timer: u8,
+}
+
+impl Sweep {
+    fn period(&self) -> u8 {
+        (self.register >> 4) & 0x07
+    }
+
+    fn shift(&self) -> u8 {
+        self.register & 0x07
+    }
+
+    /// The sweep timer treats a period of 0 as 8.
+    fn reload_timer(&mut self) {
+        self.timer = if self.period() == 0 { 8 } else { self.period() };
+    }
+
+    /// Returns the next frequency, which may overflow `MAX_FREQUENCY`.
+    fn next_frequency(&self) -> u16 {
+        let delta = self.shadow_frequency >> self.shift();
+        if self.register & 0x08 != 0 {
+            self.shadow_frequency - delta
+        } else {
+            self.shadow_frequency + delta
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct Pulse {
+    /// Only channel 1 has a sweep unit.
+    sweep: Option<Sweep>,
+    enabled: bool,
+    duty: u8,
+    duty_position: u8,
+    length: LengthCounter,
+    envelope: Envelope,
+    frequency: u16,
+    /// Clock ticks until the next duty step.
+    timer: u16,
+}
+
+impl Pulse {
+    /// Returns channel 1.
+    pub(super) fn with_sweep() -> Pulse {
+        Pulse { sweep: Some(Sweep::default()), ..Pulse::new() }
+    }
+
+    /// Returns channel 2.
+    pub(super) fn new() -> Pulse {
+        Pulse {
+            sweep: None,
+            enabled: false,
+            duty: 0,
+            duty_position: 0,
+            length: LengthCounter::new(64),
+            envelope: Envelope::default(),
+            frequency: 0,
+            timer: 0,
+        }
+    }
+
+    /// Reads register `index` (0 for NRx0 through 4 for NRx4). Write-only
+    /// bits read as 1.
+    pub(super) fn read(&self, index: u16) -> u8 {
+        match index {
+            0 => match &self.sweep {
+                Some(sweep) => 0x80 | sweep.register,
+                None => 0xFF,
+            },
+            1 => 0x3F | self.duty << 6,
+            2 => self.envelope.read(),
+            3 => 0xFF,
+            _ => 0xBF | (self.length.enabled as u8) << 6,
+        }
+    }
+
+    pub(super) fn write(&mut self, index: u16, value: u8) {
+        match index {
+            0 => {
+                if let Some(sweep) = &mut self.sweep {
+                    sweep.register = value & 0x7F;
+                }
+            }
+            1 => {
+                self.duty = value >> 6;
+                self.length.load(value & 0x3F);
+            }
+            2 => {
+                self.envelope.write(value);
+                if !self.envelope.dac_enabled() {
+                    self.enabled = false;
+                }
+            }
+            3 => self.frequency = (self.frequency & 0x700) | value as u16,
+            _ => {
+                self.frequency = (self.frequency & 0xFF) | ((value & 0x07) as u16) << 8;
+                self.length.enabled = value & 0x40 != 0;
+                if value & 0x80 != 0 {
+                    self.trigger();
+                }
+            }
+        }
+    }
+
+    fn trigger(&mut self) {
+        self.enabled = self.envelope.dac_enabled();
+        self.length.trigger();
+        self.envelope.trigger();
+        self.timer = self.period();
+
+        if let Some(sweep) = &mut self.sweep {
+            sweep.shadow_frequency = self.frequency;
+            sweep.reload_timer();
+            sweep.enabled = sweep.period() != 0 || sweep.shift() != 0;
+            // With a non-zero shift the overflow check runs immediately
+            if sweep.shift() != 0 && sweep.next_frequency() > MAX_FREQUENCY {
+                self.enabled = false;
+            }
+        }
+    }
+
+    /// Clock ticks per duty step.
+    fn period(&self) -> u16 {
+        (2048 - self.frequency) * 4
+    }
+
+    /// Advances the channel by one clock tick.
+    pub(super) fn tick(&mut self) {
+        if self.timer > 0 {
+            self.timer -= 1;
+        }
+        if self.timer == 0 {
+            self.timer = self.period();
+            self.duty_position = (self.duty_position + 1) % 8;
+        }
+    }
+
+    pub(super) fn clock_length(&mut self) {
+        if self.length.clock() {
+            self.enabled = false;
+        }
+    }
+
+    pub(super) fn clock_envelope(&mut self) {
+        self.envelope.clock();
+    }
+
+    /// Clocked at 128 Hz by the frame sequencer; a no-op on channel 2.
+    pub(super) fn clock_sweep(&mut self) {
+        let Some(sweep) = &mut self.sweep else {
+            return;
+        };
+        if sweep.timer > 0 {
+            sweep.timer -= 1;
+        }
+        if sweep.timer != 0 {
+            return;
+        }
+
+        sweep.reload_timer();
+        if !sweep.enabled || sweep.period() == 0 {
+            return;
+        }
+
+        let frequency = sweep.next_frequency();
+        if frequency > MAX_FREQUENCY {
+            self.enabled = false;
+        } else if sweep.shift() != 0 {
+            sweep.shadow_frequency = frequency;
+            self.frequency = frequency;
+            // The new frequency is checked for overflow once more
+            if sweep.next_frequency() > MAX_FREQUENCY {
+                self.enabled = false;
+            }
+        }
+    }
+}
+
+impl Channel for Pulse {
+    fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn dac_enabled(&self) -> bool {
+        self.envelope.dac_enabled()
+    }
+
+    fn output(&self) -> u8 {
+        if !self.enabled {
+            return 0;
+        }
+        DUTY_WAVEFORMS[self.duty as usize][self.duty_position as usize] * self.envelope.volume()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns channel 2 triggered at full volume with duty `duty` and
+    /// frequency `frequency`.
+    fn triggered(duty: u8, frequency: u16) -> Pulse {
+        let mut pulse = Pulse::new();
+        pulse.write(1, duty << 6);
+        pulse.write(2, 0xF0);
+        pulse.write(3, frequency as u8);
+        pulse.write(4, 0x80 | (frequency >> 8) as u8);
+        pulse
+    }
+
+    /// Returns one duty cycle of output, sampled once per duty step.
+    fn waveform(pulse: &mut Pulse) -> Vec<u8> {
+        (0..8)
+            .map(|_| {
+                let output = pulse.output();
+                for _ in 0..pulse.period() {
+                    pulse.tick();
+                }
+                output
+            })
+            .collect()
+    }
+
+    #[test]
+    fn plays_each_duty_cycle() {
+        for (duty, expected) in DUTY_WAVEFORMS.iter().enumerate() {
+            let mut pulse = triggered(duty as u8, 0x7FF);
+            let expected: Vec<u8> = expected.iter().map(|&bit| bit * 15).collect();
+            assert_eq!(waveform(&mut pulse), expected, "duty {}", duty);
+        }
+    }
+
+    #[test]
+    fn steps_the_duty_once_per_period() {
+        let mut pulse = triggered(2, 0x700);
+        assert_eq!(pulse.period(), 1024);
+
+        for _ in 0..1023 {
+            pulse.tick();
+        }
+        assert_eq!(pulse.duty_position, 0);
+        pulse.tick();
+        assert_eq!(pulse.duty_position, 1);
+        // The new period is picked up at the next duty step
+        pulse.write(3, 0xFF);
+        for _ in 0..1024 {
+            pulse.tick();
+        }
+        assert_eq!(pulse.duty_position, 2);
+        for _ in 0..4 {
+            pulse.tick();
+        }
+        assert_eq!(pulse.duty_position, 3);
+    }
+
+    #[test]
+    fn is_silent_with_the_dac_off() {
+        let mut pulse = triggered(3, 0x7FF);
+        pulse.write(2, 0x00);
+
+        assert!(!pulse.enabled());
+        assert_eq!(waveform(&mut pulse), [0; 8]);
+    }
+
+    #[test]
+    fn sweep_overflow_disables_channel_1() {
+        let mut pulse = Pulse::with_sweep();
+        // Period 1, increasing, shift 1
+        pulse.write(0, 0x11);
+        pulse.write(2, 0xF0);
+        pulse.write(3, 0x00);
+        pulse.write(4, 0x84);
+        assert!(pulse.enabled());
+
+        // 0x400 + 0x200 fits; the follow-up check of 0x600 + 0x300 does not
+        pulse.clock_sweep();
+        assert_eq!(pulse.frequency, 0x600);
+        assert!(!pulse.enabled());
+    }
+}
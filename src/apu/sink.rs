@@ -0,0 +1,91 @@
+//! Plays APU samples on the host audio device through `cpal`.
+//!
+//! The emulation thread pushes samples into a ring buffer that the audio
+//! callback drains, which absorbs the jitter between the two. When the
+//! buffer runs dry the callback outputs silence instead of repeating stale
+//! audio.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+
+/// Seconds of audio the ring buffer can hold before new samples are dropped.
+const BUFFER_SECONDS: f32 = 0.2;
+
+#[derive(Debug)]
+pub enum AudioError {
+    NoDevice,
+    BuildStream(cpal::BuildStreamError),
+    PlayStream(cpal::PlayStreamError),
+}
+
+impl fmt::Display for AudioError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AudioError::NoDevice => write!(f, "no audio output device available"),
+            AudioError::BuildStream(e) => write!(f, "failed to open the audio stream: {}", e),
+            AudioError::PlayStream(e) => write!(f, "failed to start the audio stream: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for AudioError {}
+
+/// An open stereo output stream fed with interleaved `f32` samples, such as
+/// those returned by [`Apu::take_samples`](super::Apu::take_samples).
+pub struct AudioSink {
+    /// Kept alive for as long as the sink plays.
+    _stream: cpal::Stream,
+    buffer: Arc<Mutex<VecDeque<f32>>>,
+    capacity: usize,
+}
+
+impl AudioSink {
+    /// Opens the default output device at `sample_rate`, which should match
+    /// the APU's.
+    pub fn new(sample_rate: u32) -> Result<AudioSink, AudioError> {
+        let device = cpal::default_host().default_output_device().ok_or(AudioError::NoDevice)?;
+        let config = cpal::StreamConfig {
+            channels: 2,
+            sample_rate: cpal::SampleRate(sample_rate),
+            buffer_size: cpal::BufferSize::Default,
+        };
+
+        let capacity = (sample_rate as f32 * BUFFER_SECONDS) as usize * 2;
+        let buffer = Arc::new(Mutex::new(VecDeque::with_capacity(capacity)));
+        let callback_buffer = Arc::clone(&buffer);
+        let stream = device
+            .build_output_stream(
+                &config,
+                move |data: &mut [f32], _: &cpal::OutputCallbackInfo| {
+                    let mut buffer = callback_buffer.lock().unwrap();
+                    for sample in data.iter_mut() {
+                        *sample = buffer.pop_front().unwrap_or(0.0);
+                    }
+                },
+                |e| crate::warn!("⚠️ Audio stream error: {}", e),
+                None,
+            )
+            .map_err(AudioError::BuildStream)?;
+        stream.play().map_err(AudioError::PlayStream)?;
+
+        Ok(AudioSink { _stream: stream, buffer, capacity })
+    }
+
+    /// Queues interleaved left/right samples. Samples that do not fit are
+    /// dropped, which happens when emulation runs faster than real time.
+    pub fn push(&self, samples: &[f32]) {
+        let mut buffer = self.buffer.lock().unwrap();
+        let free = self.capacity.saturating_sub(buffer.len());
+        // Drop whole frames so left and right stay paired
+        let accepted = samples.len().min(free) & !1;
+        buffer.extend(&samples[..accepted]);
+    }
+
+    /// Returns the number of samples waiting to be played.
+    pub fn queued(&self) -> usize {
+        self.buffer.lock().unwrap().len()
+    }
+}
@@ -0,0 +1,227 @@
+//! Wave channel 3, which plays 32 4-bit samples from wave RAM
+//! (`0xFF30..=0xFF3F`).
+
+use serde::{Deserialize, Serialize};
+
+use super::Channel;
+use super::length::LengthCounter;
+
+const WAVE_RAM_SIZE: usize = 16;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct Wave {
+    /// The DAC power bit of NR30.
+    dac_enabled: bool,
+    enabled: bool,
+    length: LengthCounter,
+    /// Output level select of NR32: mute, 100%, 50% or 25%.
+    output_level: u8,
+    frequency: u16,
+    /// Clock ticks until the next sample.
+    timer: u16,
+    /// Index of the sample being played, 0–31.
+    position: u8,
+    /// The wave RAM byte holding the current sample.
+    sample_buffer: u8,
+    wave_ram: [u8; WAVE_RAM_SIZE],
+}
+
+impl Wave {
+    pub(super) fn new() -> Wave {
+        Wave {
+            dac_enabled: false,
+            enabled: false,
+            length: LengthCounter::new(256),
+            output_level: 0,
+            frequency: 0,
+            timer: 0,
+            position: 0,
+            sample_buffer: 0,
+            wave_ram: [0; WAVE_RAM_SIZE],
+        }
+    }
+
+    /// Returns a powered-off channel that keeps this one's wave RAM, which
+    /// survives turning the APU off.
+    pub(super) fn reset(&self) -> Wave {
+        Wave { wave_ram: self.wave_ram, ..Wave::new() }
+    }
+
+    /// Reads register `index` (0 for NR30 through 4 for NR34).
+    pub(super) fn read(&self, index: u16) -> u8 {
+        match index {
+            0 => 0x7F | (self.dac_enabled as u8) << 7,
+            1 | 3 => 0xFF,
+            2 => 0x9F | self.output_level << 5,
+            _ => 0xBF | (self.length.enabled as u8) << 6,
+        }
+    }
+
+    pub(super) fn write(&mut self, index: u16, value: u8) {
+        match index {
+            0 => {
+                self.dac_enabled = value & 0x80 != 0;
+                if !self.dac_enabled {
+                    self.enabled = false;
+                }
+            }
+            1 => self.length.load(value),
+            2 => self.output_level = (value >> 5) & 0x03,
+            3 => self.frequency = (self.frequency & 0x700) | value as u16,
+            _ => {
+                self.frequency = (self.frequency & 0xFF) | ((value & 0x07) as u16) << 8;
+                self.length.enabled = value & 0x40 != 0;
+                if value & 0x80 != 0 {
+                    self.trigger();
+                }
+            }
+        }
+    }
+
+    /// Reads wave RAM byte `index`. While the channel plays, the CPU only
+    /// sees the byte holding the current sample, whatever the address.
+    pub(super) fn read_wave_ram(&self, index: usize) -> u8 {
+        if self.enabled { self.sample_buffer } else { self.wave_ram[index] }
+    }
+
+    /// Writes wave RAM byte `index`, redirected to the current byte while
+    /// the channel plays.
+    pub(super) fn write_wave_ram(&mut self, index: usize, value: u8) {
+        if self.enabled {
+            self.wave_ram[self.position as usize / 2] = value;
+        } else {
+            self.wave_ram[index] = value;
+        }
+    }
+
+    /// Restarts playback from the first sample. The sample buffer is not
+    /// refilled until the next step, so the previous sample plays first.
+    fn trigger(&mut self) {
+        self.enabled = self.dac_enabled;
+        self.length.trigger();
+        self.timer = self.period();
+        self.position = 0;
+    }
+
+    /// Clock ticks per sample.
+    fn period(&self) -> u16 {
+        (2048 - self.frequency) * 2
+    }
+
+    /// Advances the channel by one clock tick.
+    pub(super) fn tick(&mut self) {
+        if self.timer > 0 {
+            self.timer -= 1;
+        }
+        if self.timer == 0 {
+            self.timer = self.period();
+            if self.enabled {
+                self.position = (self.position + 1) % 32;
+                self.sample_buffer = self.wave_ram[self.position as usize / 2];
+            }
+        }
+    }
+
+    pub(super) fn clock_length(&mut self) {
+        if self.length.clock() {
+            self.enabled = false;
+        }
+    }
+}
+
+impl Channel for Wave {
+    fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn dac_enabled(&self) -> bool {
+        self.dac_enabled
+    }
+
+    fn output(&self) -> u8 {
+        if !self.enabled || self.output_level == 0 {
+            return 0;
+        }
+        // The first sample of each byte is in the upper nibble
+        let sample = if self.position.is_multiple_of(2) { self.sample_buffer >> 4 } else { self.sample_buffer & 0x0F };
+        sample >> (self.output_level - 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Wave RAM holding the samples 0 to 15, twice.
+    const RAMP: [u8; WAVE_RAM_SIZE] = [
+        0x01, 0x23, 0x45, 0x67, 0x89, 0xAB, 0xCD, 0xEF, 0x01, 0x23, 0x45, 0x67, 0x89, 0xAB, 0xCD, 0xEF,
+    ];
+
+    /// Returns a channel playing [`RAMP`] at output level `level` and the
+    /// highest frequency, with `stale` left in the sample buffer.
+    fn triggered(level: u8, stale: u8) -> Wave {
+        let mut wave = Wave::new();
+        for (index, &value) in RAMP.iter().enumerate() {
+            wave.write_wave_ram(index, value);
+        }
+        wave.sample_buffer = stale;
+        wave.write(0, 0x80);
+        wave.write(2, level << 5);
+        wave.write(3, 0xFF);
+        wave.write(4, 0x87);
+        wave
+    }
+
+    /// Returns the next `count` samples, stepping one sample at a time.
+    fn play(wave: &mut Wave, count: usize) -> Vec<u8> {
+        (0..count)
+            .map(|_| {
+                for _ in 0..wave.period() {
+                    wave.tick();
+                }
+                wave.output()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn plays_the_wave_ram_in_order() {
+        let mut wave = triggered(1, 0x00);
+        let ramp: Vec<u8> = (1..=32).map(|sample| sample % 16).collect();
+
+        assert_eq!(play(&mut wave, 32), ramp);
+        // Playback wraps around to the first sample
+        assert_eq!(play(&mut wave, 2), [1, 2]);
+    }
+
+    #[test]
+    fn plays_the_stale_sample_buffer_first() {
+        let wave = triggered(1, 0xA0);
+
+        assert_eq!(wave.output(), 0x0A);
+    }
+
+    #[test]
+    fn output_level_shifts_the_samples() {
+        for (level, shift) in [(2, 1), (3, 2)] {
+            let mut wave = triggered(level, 0x00);
+            let ramp: Vec<u8> = (1..16).map(|sample| sample >> shift).collect();
+            assert_eq!(play(&mut wave, 15), ramp, "level {}", level);
+        }
+        let mut wave = triggered(0, 0x00);
+        assert_eq!(play(&mut wave, 15), [0; 15]);
+    }
+
+    #[test]
+    fn wave_ram_access_follows_the_current_sample_while_playing() {
+        let mut wave = triggered(1, 0x00);
+        play(&mut wave, 5);
+
+        // Sample 5 is the low nibble of byte 2
+        assert_eq!(wave.read_wave_ram(0), 0x45);
+        wave.write_wave_ram(0, 0x99);
+        wave.write(0, 0x00);
+        assert_eq!(wave.read_wave_ram(0), 0x01);
+        assert_eq!(wave.read_wave_ram(2), 0x99);
+    }
+}
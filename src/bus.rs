@@ -0,0 +1,441 @@
+//! The memory bus connecting the CPU to the cartridge, RAM and I/O registers.
+//!
+//! Address map (see the "Memory Map" chapter of Pan Docs):
+//!
+//! | Range         | Contents                               |
+//! |---------------|----------------------------------------|
+//! | `0000..=7FFF` | cartridge ROM, banked by the MBC       |
+//! | `8000..=9FFF` | video RAM                              |
+//! | `A000..=BFFF` | cartridge RAM                          |
+//! | `C000..=DFFF` | work RAM                               |
+//! | `E000..=FDFF` | echo of `C000..=DDFF`                  |
+//! | `FE00..=FE9F` | object attribute memory (OAM)          |
+//! | `FEA0..=FEFF` | unusable                               |
+//! | `FF00..=FF7F` | I/O registers                          |
+//! | `FF80..=FFFE` | high RAM                               |
+//! | `FFFF`        | interrupt enable register (IE)         |
+//!
+//! In CGB mode, `D000..=DFFF` is switchable between work RAM banks 1–7
+//! (SVBK, `0xFF70`) and the CPU can run at double speed (KEY1, `0xFF4D`).
+//!
+//! While an OAM DMA transfer runs, the CPU only reaches `FF00..=FFFF`: other
+//! reads return `0xFF` and other writes are ignored.
+
+use std::collections::BTreeMap;
+use std::ops::RangeInclusive;
+
+use serde::{Deserialize, Serialize};
+
+use crate::apu::Apu;
+use crate::cartridge::Cartridge;
+use crate::cartridge::header::CgbSupport;
+use crate::debugger::{Watch, WatchHit};
+use crate::dma::OamDma;
+use crate::interrupt::Interrupt;
+use crate::joypad::{Button, Joypad};
+use crate::ppu::Ppu;
+use crate::serial::Serial;
+use crate::state::{boxed_byte_array, byte_array};
+use crate::timer::Timer;
+
+const WRAM_BANK_SIZE: usize = 0x1000;
+/// All eight work RAM banks; the DMG only uses the first two.
+const WRAM_SIZE: usize = WRAM_BANK_SIZE * 8;
+const IO_SIZE: usize = 0x80;
+const HRAM_SIZE: usize = 0x7F;
+/// Size of the DMG boot ROM, mapped over `0x0000..=0x00FF` at power-on.
+pub const BOOT_ROM_SIZE: usize = 0x100;
+
+/// A fixed area of the address space, for dumping memory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Region {
+    /// Video RAM, in the currently mapped bank.
+    Vram,
+    /// Object attribute memory.
+    Oam,
+    /// Work RAM, with the currently mapped bank at `D000..=DFFF`.
+    Wram,
+    /// High RAM.
+    Hram,
+    /// The I/O registers.
+    Io,
+}
+
+impl Region {
+    pub const ALL: [Region; 5] = [Region::Vram, Region::Oam, Region::Wram, Region::Hram, Region::Io];
+
+    /// Returns the bus addresses the region covers.
+    pub fn range(self) -> RangeInclusive<u16> {
+        match self {
+            Region::Vram => 0x8000..=0x9FFF,
+            Region::Oam => 0xFE00..=0xFE9F,
+            Region::Wram => 0xC000..=0xDFFF,
+            Region::Hram => 0xFF80..=0xFFFE,
+            Region::Io => 0xFF00..=0xFF7F,
+        }
+    }
+}
+
+/// Everything the CPU can address.
+///
+/// Serializing the bus leaves out the cartridge, which is saved separately
+/// as a [`CartridgeState`](crate::cartridge::CartridgeState).
+#[derive(Serialize, Deserialize)]
+pub struct Bus {
+    #[serde(skip)]
+    cartridge: Cartridge,
+    /// Like the cartridge ROM, not part of save states.
+    #[serde(skip)]
+    boot_rom: Option<Box<[u8; BOOT_ROM_SIZE]>>,
+    /// Cleared for good by the boot ROM writing to `0xFF50`.
+    boot_rom_mapped: bool,
+    /// Set for cartridges that declare CGB support.
+    cgb: bool,
+    /// Whether the CPU runs at double speed, switched by `STOP`.
+    double_speed: bool,
+    /// KEY1 bit 0: the next `STOP` switches speed.
+    speed_switch_armed: bool,
+    ppu: Ppu,
+    apu: Apu,
+    timer: Timer,
+    dma: OamDma,
+    joypad: Joypad,
+    serial: Serial,
+    #[serde(with = "boxed_byte_array")]
+    wram: Box<[u8; WRAM_SIZE]>,
+    /// Work RAM bank mapped at `D000..=DFFF`, from 1 to 7.
+    wram_bank: u8,
+    #[serde(with = "byte_array")]
+    io: [u8; IO_SIZE],
+    #[serde(with = "byte_array")]
+    hram: [u8; HRAM_SIZE],
+    interrupt_enable: u8,
+    /// Requested interrupts (IF), one bit per [`Interrupt`].
+    interrupt_flag: u8,
+    /// M-cycles elapsed since power-on.
+    cycles: u64,
+    /// Debugger watchpoints, checked on CPU accesses only.
+    #[serde(skip)]
+    watchpoints: BTreeMap<u16, Watch>,
+    /// The first watchpoint triggered since the last
+    /// [`take_watch_hit`](Bus::take_watch_hit).
+    #[serde(skip)]
+    watch_hit: Option<WatchHit>,
+    /// Value LY reads as instead of the PPU's, see
+    /// [`stub_ly`](Bus::stub_ly).
+    #[serde(skip)]
+    ly_stub: Option<u8>,
+}
+
+impl Bus {
+    /// Returns a bus with `cartridge` inserted, in CGB mode if its header
+    /// declares CGB support.
+    pub fn new(cartridge: Cartridge) -> Bus {
+        let cgb = cartridge.header().cgb != CgbSupport::None;
+        Bus {
+            cartridge,
+            boot_rom: None,
+            boot_rom_mapped: false,
+            cgb,
+            double_speed: false,
+            speed_switch_armed: false,
+            ppu: Ppu::new(cgb),
+            apu: Apu::default(),
+            timer: Timer::new(),
+            dma: OamDma::new(),
+            joypad: Joypad::new(),
+            serial: Serial::new(),
+            wram: Box::new([0; WRAM_SIZE]),
+            wram_bank: 1,
+            io: [0; IO_SIZE],
+            hram: [0; HRAM_SIZE],
+            interrupt_enable: 0,
+            interrupt_flag: 0,
+            cycles: 0,
+            watchpoints: BTreeMap::new(),
+            watch_hit: None,
+            ly_stub: None,
+        }
+    }
+
+    /// Maps `boot_rom` over the start of the cartridge ROM until it unmaps
+    /// itself.
+    pub fn map_boot_rom(&mut self, boot_rom: [u8; BOOT_ROM_SIZE]) {
+        self.boot_rom = Some(Box::new(boot_rom));
+        self.boot_rom_mapped = true;
+    }
+
+    /// Sets the I/O registers to the values the DMG boot ROM leaves behind.
+    pub fn skip_boot_rom(&mut self) {
+        self.boot_rom_mapped = false;
+        self.timer.set_counter(0xABCC);
+        // Both joypad groups selected
+        self.write(0xFF00, 0x00);
+        self.write(0xFF0F, Interrupt::VBlank.bit());
+        // The boot sound on channel 1 has faded out by now, so the channel
+        // is left off and NR52 reads 0xF0 rather than 0xF1
+        self.write(0xFF26, 0x80);
+        self.write(0xFF11, 0x80);
+        self.write(0xFF12, 0xF3);
+        self.write(0xFF24, 0x77);
+        self.write(0xFF25, 0xF3);
+        self.write(0xFF47, 0xFC);
+        self.write(0xFF40, 0x91);
+    }
+
+    /// Replaces the machine state with `state`, a deserialized bus, keeping
+    /// the inserted cartridge, the serial callback, the audio settings, the
+    /// watchpoints and the LY stub.
+    pub(crate) fn restore(&mut self, mut state: Bus) {
+        std::mem::swap(&mut state.cartridge, &mut self.cartridge);
+        state.boot_rom = self.boot_rom.take();
+        if let Some(callback) = self.serial.take_callback() {
+            state.serial.set_callback(callback);
+        }
+        state.apu.set_sample_rate(self.apu.sample_rate());
+        state.apu.set_speed(self.apu.speed());
+        state.watchpoints = std::mem::take(&mut self.watchpoints);
+        state.ly_stub = self.ly_stub;
+        *self = state;
+    }
+
+    /// Makes LY (`0xFF44`) read as `ly`, or as the PPU's current line again
+    /// for `None`. Gameboy Doctor reference logs were made with LY stubbed to
+    /// `0x90`, so games waiting for VBlank take the same path every time.
+    pub fn stub_ly(&mut self, ly: Option<u8>) {
+        self.ly_stub = ly;
+    }
+
+    pub(crate) fn watchpoints(&self) -> &BTreeMap<u16, Watch> {
+        &self.watchpoints
+    }
+
+    pub(crate) fn watchpoints_mut(&mut self) -> &mut BTreeMap<u16, Watch> {
+        &mut self.watchpoints
+    }
+
+    /// Records a CPU access of `value` at `addr` if it triggers a
+    /// watchpoint.
+    pub(crate) fn watch(&mut self, addr: u16, value: u8, write: bool) {
+        if self.watch_hit.is_none()
+            && let Some(watch) = self.watchpoints.get(&addr)
+            && watch.matches(write)
+        {
+            self.watch_hit = Some(WatchHit { addr, value, write });
+        }
+    }
+
+    pub(crate) fn take_watch_hit(&mut self) -> Option<WatchHit> {
+        self.watch_hit.take()
+    }
+
+    /// Resets every component to its power-on state, keeping what
+    /// [`restore`](Bus::restore) keeps. Cartridge RAM and clock survive; the
+    /// boot ROM, if any, is mapped again and otherwise skipped.
+    pub(crate) fn reset(&mut self) {
+        let mut power_on = Bus::new(std::mem::take(&mut self.cartridge));
+        std::mem::swap(&mut power_on.cartridge, &mut self.cartridge);
+        self.restore(power_on);
+        self.cartridge.reset();
+        if self.boot_rom.is_some() {
+            self.boot_rom_mapped = true;
+        } else {
+            self.skip_boot_rom();
+        }
+    }
+
+    /// Returns whether the boot ROM is mapped over the cartridge ROM.
+    pub fn boot_rom_mapped(&self) -> bool {
+        self.boot_rom_mapped
+    }
+
+    /// Returns the number of M-cycles elapsed since power-on.
+    pub fn cycles(&self) -> u64 {
+        self.cycles
+    }
+
+    pub fn is_cgb(&self) -> bool {
+        self.cgb
+    }
+
+    pub fn double_speed(&self) -> bool {
+        self.double_speed
+    }
+
+    /// Switches CPU speed if KEY1 armed a switch, as `STOP` does in CGB mode.
+    /// Returns whether the speed changed.
+    pub fn switch_speed(&mut self) -> bool {
+        if !self.speed_switch_armed {
+            return false;
+        }
+        self.speed_switch_armed = false;
+        self.double_speed = !self.double_speed;
+        true
+    }
+
+    /// Advances the hardware other than the CPU by one M-cycle. The CPU calls
+    /// this once per memory access or internal delay.
+    ///
+    /// The timer and serial port follow the CPU clock, while the PPU, APU and
+    /// cartridge clock keep their speed, so at double speed they only advance
+    /// every other M-cycle.
+    pub fn tick(&mut self) {
+        self.cycles += 1;
+        if self.timer.tick() {
+            self.request_interrupt(Interrupt::Timer);
+        }
+        if self.serial.tick() {
+            self.request_interrupt(Interrupt::Serial);
+        }
+        if let Some((source, index)) = self.dma.tick() {
+            let value = self.read(source);
+            self.ppu.write(0xFE00 + index as u16, value);
+        }
+        if self.double_speed && !self.cycles.is_multiple_of(2) {
+            return;
+        }
+        self.interrupt_flag |= self.ppu.tick();
+        self.apu.tick();
+        self.cartridge.tick();
+    }
+
+    /// Returns the offset in `wram` of `addr`, in `C000..=FDFF`.
+    fn wram_index(&self, addr: u16) -> usize {
+        // The echo area mirrors work RAM, 0x2000 bytes down
+        let offset = (addr & 0x1FFF) as usize;
+        if offset < WRAM_BANK_SIZE {
+            offset
+        } else {
+            self.wram_bank as usize * WRAM_BANK_SIZE + offset - WRAM_BANK_SIZE
+        }
+    }
+
+    /// Sets the IF bit of `interrupt`.
+    pub fn request_interrupt(&mut self, interrupt: Interrupt) {
+        self.interrupt_flag |= interrupt.bit();
+    }
+
+    /// Clears the IF bit of `interrupt` once the CPU services it.
+    pub fn acknowledge_interrupt(&mut self, interrupt: Interrupt) {
+        self.interrupt_flag &= !interrupt.bit();
+    }
+
+    /// Returns the interrupts that are both requested and enabled.
+    pub fn pending_interrupts(&self) -> u8 {
+        self.interrupt_enable & self.interrupt_flag & 0x1F
+    }
+
+    /// Presses `button`, requesting a Joypad interrupt if its line is
+    /// selected.
+    pub fn press(&mut self, button: Button) {
+        if self.joypad.press(button) {
+            self.request_interrupt(Interrupt::Joypad);
+        }
+    }
+
+    pub fn release(&mut self, button: Button) {
+        self.joypad.release(button);
+    }
+
+    pub fn ppu(&self) -> &Ppu {
+        &self.ppu
+    }
+
+    pub fn apu(&self) -> &Apu {
+        &self.apu
+    }
+
+    pub fn apu_mut(&mut self) -> &mut Apu {
+        &mut self.apu
+    }
+
+    pub fn serial_mut(&mut self) -> &mut Serial {
+        &mut self.serial
+    }
+
+    pub fn cartridge(&self) -> &Cartridge {
+        &self.cartridge
+    }
+
+    pub fn cartridge_mut(&mut self) -> &mut Cartridge {
+        &mut self.cartridge
+    }
+
+    /// Reads `addr` on behalf of the CPU, which is cut off from everything
+    /// below `0xFF00` during OAM DMA.
+    pub(crate) fn cpu_read(&self, addr: u16) -> u8 {
+        if self.dma.is_active() && addr < 0xFF00 { 0xFF } else { self.read(addr) }
+    }
+
+    /// Writes `addr` on behalf of the CPU, see [`cpu_read`](Bus::cpu_read).
+    pub(crate) fn cpu_write(&mut self, addr: u16, value: u8) {
+        if !self.dma.is_active() || addr >= 0xFF00 {
+            self.write(addr, value);
+        }
+    }
+
+    pub fn read(&self, addr: u16) -> u8 {
+        match addr {
+            0x0000..=0x00FF if self.boot_rom_mapped => match &self.boot_rom {
+                Some(boot_rom) => boot_rom[addr as usize],
+                None => self.cartridge.read(addr),
+            },
+            0x0000..=0x7FFF | 0xA000..=0xBFFF => self.cartridge.read(addr),
+            0x8000..=0x9FFF => self.ppu.read(addr),
+            0xC000..=0xFDFF => self.wram[self.wram_index(addr)],
+            0xFE00..=0xFE9F => self.ppu.read(addr),
+            0xFEA0..=0xFEFF => 0xFF,
+            0xFF00 => self.joypad.read(),
+            0xFF01..=0xFF02 => self.serial.read(addr),
+            0xFF04..=0xFF07 => self.timer.read(addr),
+            0xFF10..=0xFF26 | 0xFF30..=0xFF3F => self.apu.read(addr),
+            0xFF44 => self.ly_stub.unwrap_or_else(|| self.ppu.read(addr)),
+            0xFF46 => self.dma.read(),
+            0xFF40..=0xFF45 | 0xFF47..=0xFF4B | 0xFF4F | 0xFF68..=0xFF6B => self.ppu.read(addr),
+            0xFF4D if self.cgb => {
+                let speed = if self.double_speed { 0x80 } else { 0x00 };
+                0x7E | speed | self.speed_switch_armed as u8
+            }
+            0xFF70 if self.cgb => 0xF8 | self.wram_bank,
+            // The upper three bits of IF are unused and read as 1
+            0xFF0F => 0xE0 | self.interrupt_flag,
+            0xFF50 => 0xFF,
+            0xFF03..=0xFF7F => self.io[(addr - 0xFF00) as usize],
+            0xFF80..=0xFFFE => self.hram[(addr - 0xFF80) as usize],
+            0xFFFF => self.interrupt_enable,
+        }
+    }
+
+    pub fn write(&mut self, addr: u16, value: u8) {
+        match addr {
+            0x0000..=0x7FFF | 0xA000..=0xBFFF => self.cartridge.write(addr, value),
+            0x8000..=0x9FFF => self.ppu.write(addr, value),
+            0xC000..=0xFDFF => self.wram[self.wram_index(addr)] = value,
+            0xFE00..=0xFE9F => self.ppu.write(addr, value),
+            0xFEA0..=0xFEFF => {}
+            0xFF00 => {
+                if self.joypad.write(value) {
+                    self.request_interrupt(Interrupt::Joypad);
+                }
+            }
+            0xFF01..=0xFF02 => self.serial.write(addr, value),
+            0xFF04..=0xFF07 => self.timer.write(addr, value),
+            0xFF10..=0xFF26 | 0xFF30..=0xFF3F => self.apu.write(addr, value),
+            0xFF41 => self.interrupt_flag |= self.ppu.write_stat(value),
+            0xFF46 => self.dma.write(value),
+            0xFF40 | 0xFF42..=0xFF45 | 0xFF47..=0xFF4B | 0xFF4F | 0xFF68..=0xFF6B => self.ppu.write(addr, value),
+            0xFF4D if self.cgb => self.speed_switch_armed = value & 0x01 != 0,
+            // Selecting bank 0 selects bank 1
+            0xFF70 if self.cgb => self.wram_bank = (value & 0x07).max(1),
+            0xFF0F => self.interrupt_flag = value & 0x1F,
+            0xFF50 => {
+                if value != 0 {
+                    self.boot_rom_mapped = false;
+                }
+            }
+            0xFF03..=0xFF7F => self.io[(addr - 0xFF00) as usize] = value,
+            0xFF80..=0xFFFE => self.hram[(addr - 0xFF80) as usize] = value,
+            0xFFFF => self.interrupt_enable = value,
+        }
+    }
+}
@@ -0,0 +1,289 @@
+//! Parsing of the cartridge header stored at `0x0100..=0x014F`, as documented
+//! in the "The Cartridge Header" chapter of Pan Docs.
+
+use std::fmt;
+
+/// Address of the first byte of the title.
+const TITLE_START: usize = 0x0134;
+/// Address one past the last byte of the title.
+const TITLE_END: usize = 0x0144;
+const CGB_FLAG: usize = 0x0143;
+const CARTRIDGE_TYPE: usize = 0x0147;
+const ROM_SIZE: usize = 0x0148;
+const RAM_SIZE: usize = 0x0149;
+const HEADER_CHECKSUM: usize = 0x014D;
+
+/// Smallest ROM that contains a complete header.
+pub const HEADER_END: usize = 0x0150;
+
+/// Memory bank controller selected by the cartridge type byte.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MbcKind {
+    /// 32 KiB ROM mapped directly, optionally with up to 8 KiB of RAM.
+    #[default]
+    RomOnly,
+    Mbc1,
+    Mbc2,
+    Mbc3,
+    Mbc5,
+}
+
+/// Game Boy Color support declared at `0x0143`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CgbSupport {
+    /// A DMG game; the byte is part of the title.
+    #[default]
+    None,
+    /// Runs on both the DMG and the CGB (`0x80`).
+    Enhanced,
+    /// Runs only on the CGB (`0xC0`).
+    Only,
+}
+
+/// The decoded cartridge header. The default is an empty header, as seen
+/// with no cartridge inserted.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CartridgeHeader {
+    /// Game title in upper-case ASCII, without trailing padding.
+    pub title: String,
+    pub cgb: CgbSupport,
+    /// Raw cartridge type byte at `0x0147`.
+    pub cartridge_type: u8,
+    pub mbc: MbcKind,
+    /// Whether the cartridge has external RAM.
+    pub has_ram: bool,
+    /// Whether the external RAM (and clock) is battery-backed.
+    pub has_battery: bool,
+    /// Whether the cartridge has an MBC3 real-time clock.
+    pub has_timer: bool,
+    /// ROM size in bytes.
+    pub rom_size: usize,
+    /// External RAM size in bytes. MBC2's built-in RAM is not included.
+    pub ram_size: usize,
+    pub header_checksum: u8,
+}
+
+/// Errors that can occur while parsing a cartridge header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HeaderError {
+    /// The ROM is too short to contain a header; holds the ROM length.
+    TooShort(usize),
+    /// The checksum at `0x014D` does not match the header bytes.
+    BadChecksum { expected: u8, actual: u8 },
+    /// The cartridge type byte names hardware the emulator does not support.
+    UnsupportedCartridgeType(u8),
+    /// The ROM size byte is not a known size code.
+    InvalidRomSize(u8),
+    /// The RAM size byte is not a known size code.
+    InvalidRamSize(u8),
+}
+
+impl fmt::Display for HeaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HeaderError::TooShort(len) => {
+                write!(f, "ROM is {} bytes long, too short to contain a header", len)
+            }
+            HeaderError::BadChecksum { expected, actual } => write!(
+                f,
+                "header checksum mismatch: header says {:#04X}, computed {:#04X}",
+                expected, actual
+            ),
+            HeaderError::UnsupportedCartridgeType(byte) => {
+                write!(f, "unsupported cartridge type {:#04X}", byte)
+            }
+            HeaderError::InvalidRomSize(byte) => write!(f, "invalid ROM size code {:#04X}", byte),
+            HeaderError::InvalidRamSize(byte) => write!(f, "invalid RAM size code {:#04X}", byte),
+        }
+    }
+}
+
+impl std::error::Error for HeaderError {}
+
+impl CartridgeHeader {
+    /// Parses and validates the header of `rom`.
+    pub fn parse(rom: &[u8]) -> Result<CartridgeHeader, HeaderError> {
+        if rom.len() < HEADER_END {
+            return Err(HeaderError::TooShort(rom.len()));
+        }
+
+        let expected = rom[HEADER_CHECKSUM];
+        let actual = header_checksum(rom);
+        if expected != actual {
+            return Err(HeaderError::BadChecksum { expected, actual });
+        }
+
+        let cgb = match rom[CGB_FLAG] {
+            0x80 => CgbSupport::Enhanced,
+            0xC0 => CgbSupport::Only,
+            _ => CgbSupport::None,
+        };
+        // CGB games reuse the last title byte for the flag
+        let title_end = if cgb == CgbSupport::None { TITLE_END } else { CGB_FLAG };
+        let title = rom[TITLE_START..title_end]
+            .iter()
+            .take_while(|&&byte| byte != 0)
+            .map(|&byte| byte as char)
+            .collect::<String>()
+            .trim_end()
+            .to_string();
+
+        let cartridge_type = rom[CARTRIDGE_TYPE];
+        let (mbc, has_ram, has_battery, has_timer) = match cartridge_type {
+            0x00 => (MbcKind::RomOnly, false, false, false),
+            0x01 => (MbcKind::Mbc1, false, false, false),
+            0x02 => (MbcKind::Mbc1, true, false, false),
+            0x03 => (MbcKind::Mbc1, true, true, false),
+            0x05 => (MbcKind::Mbc2, false, false, false),
+            0x06 => (MbcKind::Mbc2, false, true, false),
+            0x08 => (MbcKind::RomOnly, true, false, false),
+            0x09 => (MbcKind::RomOnly, true, true, false),
+            0x0F => (MbcKind::Mbc3, false, true, true),
+            0x10 => (MbcKind::Mbc3, true, true, true),
+            0x11 => (MbcKind::Mbc3, false, false, false),
+            0x12 => (MbcKind::Mbc3, true, false, false),
+            0x13 => (MbcKind::Mbc3, true, true, false),
+            // Rumble variants (0x1C-0x1E) behave like plain MBC5 without the motor
+            0x19 | 0x1C => (MbcKind::Mbc5, false, false, false),
+            0x1A | 0x1D => (MbcKind::Mbc5, true, false, false),
+            0x1B | 0x1E => (MbcKind::Mbc5, true, true, false),
+            other => return Err(HeaderError::UnsupportedCartridgeType(other)),
+        };
+
+        let rom_size = match rom[ROM_SIZE] {
+            code @ 0x00..=0x08 => (32 * 1024) << code,
+            other => return Err(HeaderError::InvalidRomSize(other)),
+        };
+
+        let ram_size = match rom[RAM_SIZE] {
+            0x00 => 0,
+            // Unofficial 2 KiB size, listed by Pan Docs for some homebrew
+            0x01 => 2 * 1024,
+            0x02 => 8 * 1024,
+            0x03 => 32 * 1024,
+            0x04 => 128 * 1024,
+            0x05 => 64 * 1024,
+            other => return Err(HeaderError::InvalidRamSize(other)),
+        };
+
+        Ok(CartridgeHeader {
+            title,
+            cgb,
+            cartridge_type,
+            mbc,
+            has_ram,
+            has_battery,
+            has_timer,
+            rom_size,
+            ram_size: if has_ram { ram_size } else { 0 },
+            header_checksum: expected,
+        })
+    }
+}
+
+/// Computes the checksum of `0x0134..=0x014C` the same way the boot ROM does.
+fn header_checksum(rom: &[u8]) -> u8 {
+    rom[TITLE_START..HEADER_CHECKSUM]
+        .iter()
+        .fold(0u8, |sum, &byte| sum.wrapping_sub(byte).wrapping_sub(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::rom;
+
+    /// Sets header byte `addr` of a [`rom`] to `value`, keeping the checksum
+    /// valid.
+    fn patched(mut rom: Vec<u8>, addr: usize, value: u8) -> Vec<u8> {
+        rom[addr] = value;
+        rom[HEADER_CHECKSUM] = header_checksum(&rom);
+        rom
+    }
+
+    #[test]
+    fn parses_a_rom_only_header() {
+        let header = CartridgeHeader::parse(&rom(0x00, 2, 0x00)).unwrap();
+
+        assert_eq!(header.title, "TEST");
+        assert_eq!(header.cgb, CgbSupport::None);
+        assert_eq!(header.mbc, MbcKind::RomOnly);
+        assert!(!header.has_ram && !header.has_battery && !header.has_timer);
+        assert_eq!(header.rom_size, 32 * 1024);
+        assert_eq!(header.ram_size, 0);
+    }
+
+    #[test]
+    fn cartridge_type_selects_the_hardware() {
+        let header = CartridgeHeader::parse(&rom(0x03, 4, 0x02)).unwrap();
+        assert_eq!(header.mbc, MbcKind::Mbc1);
+        assert!(header.has_ram && header.has_battery && !header.has_timer);
+        assert_eq!(header.rom_size, 64 * 1024);
+        assert_eq!(header.ram_size, 8 * 1024);
+
+        let header = CartridgeHeader::parse(&rom(0x10, 2, 0x03)).unwrap();
+        assert_eq!(header.mbc, MbcKind::Mbc3);
+        assert!(header.has_ram && header.has_battery && header.has_timer);
+        assert_eq!(header.ram_size, 32 * 1024);
+
+        let header = CartridgeHeader::parse(&rom(0x1B, 2, 0x04)).unwrap();
+        assert_eq!(header.mbc, MbcKind::Mbc5);
+        assert!(header.has_ram && header.has_battery && !header.has_timer);
+        assert_eq!(header.ram_size, 128 * 1024);
+    }
+
+    #[test]
+    fn ram_size_is_ignored_without_ram() {
+        let header = CartridgeHeader::parse(&rom(0x01, 2, 0x02)).unwrap();
+        assert!(!header.has_ram);
+        assert_eq!(header.ram_size, 0);
+    }
+
+    #[test]
+    fn cgb_flag_shortens_the_title() {
+        let mut long_title = rom(0x00, 2, 0x00);
+        long_title[TITLE_START..TITLE_END].copy_from_slice(b"ABCDEFGHIJKLMNO\x80");
+        let header = CartridgeHeader::parse(&patched(long_title.clone(), CGB_FLAG, 0x80)).unwrap();
+        assert_eq!(header.cgb, CgbSupport::Enhanced);
+        assert_eq!(header.title, "ABCDEFGHIJKLMNO");
+
+        let header = CartridgeHeader::parse(&patched(long_title.clone(), CGB_FLAG, 0xC0)).unwrap();
+        assert_eq!(header.cgb, CgbSupport::Only);
+        assert_eq!(header.title, "ABCDEFGHIJKLMNO");
+
+        // On DMG cartridges the byte belongs to the title
+        let header = CartridgeHeader::parse(&patched(long_title, CGB_FLAG, b'P')).unwrap();
+        assert_eq!(header.cgb, CgbSupport::None);
+        assert_eq!(header.title, "ABCDEFGHIJKLMNOP");
+    }
+
+    #[test]
+    fn rejects_a_truncated_rom() {
+        let rom = rom(0x00, 2, 0x00);
+        assert_eq!(CartridgeHeader::parse(&rom[..HEADER_END - 1]), Err(HeaderError::TooShort(HEADER_END - 1)));
+        assert_eq!(CartridgeHeader::parse(&[]), Err(HeaderError::TooShort(0)));
+        assert!(CartridgeHeader::parse(&rom[..HEADER_END]).is_ok());
+    }
+
+    #[test]
+    fn rejects_a_bad_checksum() {
+        let mut rom = rom(0x00, 2, 0x00);
+        let actual = rom[HEADER_CHECKSUM];
+        rom[HEADER_CHECKSUM] = actual.wrapping_add(1);
+
+        assert_eq!(
+            CartridgeHeader::parse(&rom),
+            Err(HeaderError::BadChecksum { expected: actual.wrapping_add(1), actual })
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_codes() {
+        assert_eq!(CartridgeHeader::parse(&rom(0x20, 2, 0x00)), Err(HeaderError::UnsupportedCartridgeType(0x20)));
+        assert_eq!(
+            CartridgeHeader::parse(&patched(rom(0x00, 2, 0x00), ROM_SIZE, 0x09)),
+            Err(HeaderError::InvalidRomSize(0x09))
+        );
+        assert_eq!(CartridgeHeader::parse(&rom(0x00, 2, 0x06)), Err(HeaderError::InvalidRamSize(0x06)));
+    }
+}
@@ -0,0 +1,188 @@
+use super::{Mbc, ram_offset, read_rom_bank};
+
+/// MBC1, supporting up to 2 MiB of ROM and 32 KiB of RAM.
+///
+/// The 2-bit secondary register at `0x4000..=0x5FFF` either extends the ROM
+/// bank number or selects the RAM bank. In mode 1 (`0x6000..=0x7FFF` = 1) it
+/// also applies to the `0x0000..=0x3FFF` ROM window and to RAM, in mode 0 both
+/// stay on bank 0.
+pub struct Mbc1 {
+    rom: Vec<u8>,
+    ram: Vec<u8>,
+    ram_enabled: bool,
+    /// Lower 5 bits of the ROM bank number, never 0.
+    rom_bank: u8,
+    /// Upper ROM bank bits or RAM bank number.
+    secondary_bank: u8,
+    advanced_mode: bool,
+}
+
+impl Mbc1 {
+    pub fn new(rom: Vec<u8>, ram_size: usize) -> Mbc1 {
+        Mbc1 {
+            rom,
+            ram: vec![0; ram_size],
+            ram_enabled: false,
+            rom_bank: 1,
+            secondary_bank: 0,
+            advanced_mode: false,
+        }
+    }
+
+    fn ram_bank(&self) -> usize {
+        if self.advanced_mode { self.secondary_bank as usize } else { 0 }
+    }
+}
+
+impl Mbc for Mbc1 {
+    fn read(&self, addr: u16) -> u8 {
+        match addr {
+            0x0000..=0x3FFF => {
+                let bank = if self.advanced_mode { (self.secondary_bank as usize) << 5 } else { 0 };
+                read_rom_bank(&self.rom, bank, addr)
+            }
+            0x4000..=0x7FFF => {
+                let bank = ((self.secondary_bank as usize) << 5) | self.rom_bank as usize;
+                read_rom_bank(&self.rom, bank, addr)
+            }
+            _ => match ram_offset(&self.ram, self.ram_bank(), addr) {
+                Some(offset) if self.ram_enabled => self.ram[offset],
+                _ => 0xFF,
+            },
+        }
+    }
+
+    fn write(&mut self, addr: u16, value: u8) {
+        match addr {
+            0x0000..=0x1FFF => self.ram_enabled = value & 0x0F == 0x0A,
+            0x2000..=0x3FFF => {
+                // Bank 0 cannot be mapped at 0x4000, writing it selects bank 1
+                self.rom_bank = (value & 0x1F).max(1);
+            }
+            0x4000..=0x5FFF => self.secondary_bank = value & 0x03,
+            0x6000..=0x7FFF => self.advanced_mode = value & 0x01 != 0,
+            _ => {
+                if self.ram_enabled
+                    && let Some(offset) = ram_offset(&self.ram, self.ram_bank(), addr)
+                {
+                    self.ram[offset] = value;
+                }
+            }
+        }
+    }
+
+    fn ram(&self) -> &[u8] {
+        &self.ram
+    }
+
+    fn ram_mut(&mut self) -> &mut [u8] {
+        &mut self.ram
+    }
+
+    fn registers(&self) -> Vec<u8> {
+        vec![self.ram_enabled as u8, self.rom_bank, self.secondary_bank, self.advanced_mode as u8]
+    }
+
+    fn set_registers(&mut self, registers: &[u8]) {
+        if let [ram_enabled, rom_bank, secondary_bank, advanced_mode] = *registers {
+            self.ram_enabled = ram_enabled != 0;
+            self.rom_bank = rom_bank;
+            self.secondary_bank = secondary_bank;
+            self.advanced_mode = advanced_mode != 0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cartridge::mbc::{RAM_BANK_SIZE, ROM_BANK_SIZE};
+
+    /// A 2 MiB ROM whose banks each start with their own bank number.
+    fn numbered_rom() -> Vec<u8> {
+        let mut rom = vec![0; 128 * ROM_BANK_SIZE];
+        for (bank, chunk) in rom.chunks_exact_mut(ROM_BANK_SIZE).enumerate() {
+            chunk[0] = bank as u8;
+        }
+        rom
+    }
+
+    #[test]
+    fn bank_0_selects_bank_1() {
+        let mut mbc = Mbc1::new(numbered_rom(), 0);
+        assert_eq!(mbc.read(0x4000), 1);
+
+        mbc.write(0x2000, 0x00);
+        assert_eq!(mbc.read(0x4000), 1);
+        mbc.write(0x2000, 0x05);
+        assert_eq!(mbc.read(0x4000), 5);
+        // Only the lower 5 bits are compared with 0
+        mbc.write(0x2000, 0x20);
+        assert_eq!(mbc.read(0x4000), 1);
+    }
+
+    #[test]
+    fn upper_bank_bits_in_mode_0() {
+        let mut mbc = Mbc1::new(numbered_rom(), 0);
+        mbc.write(0x4000, 0x01);
+        mbc.write(0x2000, 0x00);
+        assert_eq!(mbc.read(0x4000), 0x21);
+        mbc.write(0x2000, 0x03);
+        assert_eq!(mbc.read(0x4000), 0x23);
+        // The lower window stays on bank 0
+        assert_eq!(mbc.read(0x0000), 0x00);
+    }
+
+    #[test]
+    fn upper_bank_bits_in_mode_1() {
+        let mut mbc = Mbc1::new(numbered_rom(), 0);
+        mbc.write(0x4000, 0x02);
+        mbc.write(0x6000, 0x01);
+        assert_eq!(mbc.read(0x0000), 0x40);
+        assert_eq!(mbc.read(0x4000), 0x41);
+
+        mbc.write(0x6000, 0x00);
+        assert_eq!(mbc.read(0x0000), 0x00);
+        assert_eq!(mbc.read(0x4000), 0x41);
+    }
+
+    #[test]
+    fn ram_reads_0xff_while_disabled() {
+        let mut mbc = Mbc1::new(numbered_rom(), RAM_BANK_SIZE);
+        mbc.write(0xA000, 0x12);
+        assert_eq!(mbc.read(0xA000), 0xFF);
+
+        mbc.write(0x0000, 0x0A);
+        mbc.write(0xA000, 0x12);
+        assert_eq!(mbc.read(0xA000), 0x12);
+
+        mbc.write(0x0000, 0x00);
+        assert_eq!(mbc.read(0xA000), 0xFF);
+        mbc.write(0xA000, 0x34);
+        mbc.write(0x0000, 0x0A);
+        assert_eq!(mbc.read(0xA000), 0x12);
+    }
+
+    #[test]
+    fn ram_banks_follow_the_mode() {
+        let mut mbc = Mbc1::new(numbered_rom(), 4 * RAM_BANK_SIZE);
+        mbc.write(0x0000, 0x0A);
+        mbc.write(0xA000, 0x10);
+        mbc.write(0x4000, 0x02);
+        mbc.write(0x6000, 0x01);
+        mbc.write(0xA000, 0x12);
+        assert_eq!(mbc.ram()[2 * RAM_BANK_SIZE], 0x12);
+
+        // Mode 0 always maps RAM bank 0
+        mbc.write(0x6000, 0x00);
+        assert_eq!(mbc.read(0xA000), 0x10);
+    }
+
+    #[test]
+    fn cartridge_without_ram_reads_0xff() {
+        let mut mbc = Mbc1::new(numbered_rom(), 0);
+        mbc.write(0x0000, 0x0A);
+        mbc.write(0xA000, 0x12);
+        assert_eq!(mbc.read(0xA000), 0xFF);
+    }
+}
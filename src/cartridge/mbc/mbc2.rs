@@ -0,0 +1,162 @@
+use super::{Mbc, read_rom_bank};
+
+/// Size of the built-in RAM, in 4-bit cells.
+const RAM_SIZE: usize = 512;
+
+/// Address bit that tells the two registers in `0x0000..=0x3FFF` apart.
+const REGISTER_SELECT: u16 = 0x0100;
+
+/// MBC2, supporting up to 256 KiB of ROM, with 512 half-bytes of RAM built
+/// into the controller.
+///
+/// Only the low nibble of each RAM cell exists, the upper four bits read as
+/// 1. The 512 cells repeat throughout `0xA000..=0xBFFF`.
+pub struct Mbc2 {
+    rom: Vec<u8>,
+    /// One cell per byte, only the low nibble is used.
+    ram: Vec<u8>,
+    ram_enabled: bool,
+    /// 4-bit ROM bank number, never 0.
+    rom_bank: u8,
+}
+
+impl Mbc2 {
+    pub fn new(rom: Vec<u8>) -> Mbc2 {
+        Mbc2 { rom, ram: vec![0; RAM_SIZE], ram_enabled: false, rom_bank: 1 }
+    }
+
+    fn ram_offset(addr: u16) -> usize {
+        (addr as usize - 0xA000) % RAM_SIZE
+    }
+}
+
+impl Mbc for Mbc2 {
+    fn read(&self, addr: u16) -> u8 {
+        match addr {
+            0x0000..=0x3FFF => read_rom_bank(&self.rom, 0, addr),
+            0x4000..=0x7FFF => read_rom_bank(&self.rom, self.rom_bank as usize, addr),
+            _ if self.ram_enabled => 0xF0 | self.ram[Mbc2::ram_offset(addr)],
+            _ => 0xFF,
+        }
+    }
+
+    fn write(&mut self, addr: u16, value: u8) {
+        match addr {
+            // A single register range, split by address bit 8
+            0x0000..=0x3FFF if addr & REGISTER_SELECT == 0 => self.ram_enabled = value & 0x0F == 0x0A,
+            0x0000..=0x3FFF => self.rom_bank = (value & 0x0F).max(1),
+            0x4000..=0x7FFF => {}
+            _ => {
+                if self.ram_enabled {
+                    self.ram[Mbc2::ram_offset(addr)] = value & 0x0F;
+                }
+            }
+        }
+    }
+
+    fn ram(&self) -> &[u8] {
+        &self.ram
+    }
+
+    fn ram_mut(&mut self) -> &mut [u8] {
+        &mut self.ram
+    }
+
+    fn registers(&self) -> Vec<u8> {
+        vec![self.ram_enabled as u8, self.rom_bank]
+    }
+
+    fn set_registers(&mut self, registers: &[u8]) {
+        if let [ram_enabled, rom_bank] = *registers {
+            self.ram_enabled = ram_enabled != 0;
+            self.rom_bank = rom_bank;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cartridge::mbc::ROM_BANK_SIZE;
+
+    /// A 256 KiB ROM whose banks each start with their own bank number.
+    fn numbered_rom() -> Vec<u8> {
+        let mut rom = vec![0; 16 * ROM_BANK_SIZE];
+        for (bank, chunk) in rom.chunks_exact_mut(ROM_BANK_SIZE).enumerate() {
+            chunk[0] = bank as u8;
+        }
+        rom
+    }
+
+    fn enabled_mbc() -> Mbc2 {
+        let mut mbc = Mbc2::new(numbered_rom());
+        mbc.write(0x0000, 0x0A);
+        mbc
+    }
+
+    #[test]
+    fn ram_reads_the_low_nibble_with_the_upper_bits_set() {
+        let mut mbc = enabled_mbc();
+        mbc.write(0xA000, 0x5C);
+        mbc.write(0xA001, 0xF0);
+
+        assert_eq!(mbc.read(0xA000), 0xFC);
+        assert_eq!(mbc.read(0xA001), 0xF0);
+        assert_eq!(mbc.ram()[0], 0x0C);
+    }
+
+    #[test]
+    fn ram_repeats_through_the_ram_area() {
+        let mut mbc = enabled_mbc();
+        mbc.write(0xA1FF, 0x07);
+        mbc.write(0xBE00, 0x03);
+
+        for addr in [0xA1FF, 0xA3FF, 0xB5FF, 0xBFFF] {
+            assert_eq!(mbc.read(addr), 0xF7, "{:#06X}", addr);
+        }
+        for addr in [0xA000, 0xA200, 0xBE00] {
+            assert_eq!(mbc.read(addr), 0xF3, "{:#06X}", addr);
+        }
+    }
+
+    #[test]
+    fn ram_reads_0xff_while_disabled() {
+        let mut mbc = enabled_mbc();
+        mbc.write(0xA000, 0x05);
+        mbc.write(0x0000, 0x00);
+
+        assert_eq!(mbc.read(0xA000), 0xFF);
+        mbc.write(0xA000, 0x09);
+        // The enable register only looks at the low nibble
+        mbc.write(0x0000, 0xFA);
+        assert_eq!(mbc.read(0xA000), 0xF5);
+    }
+
+    #[test]
+    fn address_bit_8_selects_the_register() {
+        let mut mbc = Mbc2::new(numbered_rom());
+        mbc.write(0x0100, 0x0A);
+        assert_eq!(mbc.read(0xA000), 0xFF);
+        assert_eq!(mbc.read(0x4000), 0x0A);
+
+        mbc.write(0x3E00, 0x0A);
+        assert_eq!(mbc.read(0xA000), 0xF0);
+        assert_eq!(mbc.read(0x4000), 0x0A);
+        mbc.write(0x21FF, 0x03);
+        assert_eq!(mbc.read(0x4000), 0x03);
+    }
+
+    #[test]
+    fn rom_bank_uses_4_bits_and_maps_0_to_1() {
+        let mut mbc = Mbc2::new(numbered_rom());
+        assert_eq!(mbc.read(0x4000), 1);
+
+        mbc.write(0x0100, 0x00);
+        assert_eq!(mbc.read(0x4000), 1);
+        mbc.write(0x0100, 0xFF);
+        assert_eq!(mbc.read(0x4000), 15);
+        mbc.write(0x0100, 0x10);
+        assert_eq!(mbc.read(0x4000), 1);
+        assert_eq!(mbc.read(0x0000), 0);
+    }
+}
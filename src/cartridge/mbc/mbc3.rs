@@ -0,0 +1,177 @@
+use super::{Mbc, Rtc, ram_offset, read_rom_bank};
+
+/// MBC3, supporting up to 2 MiB of ROM, 32 KiB of RAM and an optional
+/// real-time clock.
+pub struct Mbc3 {
+    rom: Vec<u8>,
+    ram: Vec<u8>,
+    rtc: Option<Rtc>,
+    /// Enables both RAM and the clock registers.
+    ram_enabled: bool,
+    /// 7-bit ROM bank number, never 0.
+    rom_bank: u8,
+    /// RAM bank `0x00..=0x03`, or clock register `0x08..=0x0C`.
+    ram_bank: u8,
+}
+
+impl Mbc3 {
+    pub fn new(rom: Vec<u8>, ram_size: usize, has_timer: bool) -> Mbc3 {
+        let rtc = has_timer.then(Rtc::new);
+        Mbc3 { rom, ram: vec![0; ram_size], rtc, ram_enabled: false, rom_bank: 1, ram_bank: 0 }
+    }
+}
+
+impl Mbc for Mbc3 {
+    fn read(&self, addr: u16) -> u8 {
+        match addr {
+            0x0000..=0x3FFF => read_rom_bank(&self.rom, 0, addr),
+            0x4000..=0x7FFF => read_rom_bank(&self.rom, self.rom_bank as usize, addr),
+            _ if !self.ram_enabled => 0xFF,
+            _ => match (&self.rtc, self.ram_bank) {
+                (Some(rtc), 0x08..=0x0C) => rtc.read(self.ram_bank),
+                (_, 0x00..=0x03) => match ram_offset(&self.ram, self.ram_bank as usize, addr) {
+                    Some(offset) => self.ram[offset],
+                    None => 0xFF,
+                },
+                _ => 0xFF,
+            },
+        }
+    }
+
+    fn write(&mut self, addr: u16, value: u8) {
+        match addr {
+            0x0000..=0x1FFF => self.ram_enabled = value & 0x0F == 0x0A,
+            0x2000..=0x3FFF => self.rom_bank = (value & 0x7F).max(1),
+            0x4000..=0x5FFF => self.ram_bank = value,
+            0x6000..=0x7FFF => {
+                if let Some(rtc) = &mut self.rtc {
+                    rtc.write_latch(value);
+                }
+            }
+            _ if !self.ram_enabled => {}
+            _ => match (&mut self.rtc, self.ram_bank) {
+                (Some(rtc), 0x08..=0x0C) => rtc.write(self.ram_bank, value),
+                (_, 0x00..=0x03) => {
+                    if let Some(offset) = ram_offset(&self.ram, self.ram_bank as usize, addr) {
+                        self.ram[offset] = value;
+                    }
+                }
+                _ => {}
+            },
+        }
+    }
+
+    fn ram(&self) -> &[u8] {
+        &self.ram
+    }
+
+    fn ram_mut(&mut self) -> &mut [u8] {
+        &mut self.ram
+    }
+
+    fn tick(&mut self) {
+        if let Some(rtc) = &mut self.rtc {
+            rtc.tick();
+        }
+    }
+
+    fn registers(&self) -> Vec<u8> {
+        let mut registers = vec![self.ram_enabled as u8, self.rom_bank, self.ram_bank];
+        if let Some(rtc) = &self.rtc {
+            registers.extend(rtc.registers());
+        }
+        registers
+    }
+
+    fn set_registers(&mut self, registers: &[u8]) {
+        if let [ram_enabled, rom_bank, ram_bank, ref clock @ ..] = *registers {
+            self.ram_enabled = ram_enabled != 0;
+            self.rom_bank = rom_bank;
+            self.ram_bank = ram_bank;
+            if let Some(rtc) = &mut self.rtc {
+                rtc.set_registers(clock);
+            }
+        }
+    }
+
+    fn rtc(&self) -> Option<&Rtc> {
+        self.rtc.as_ref()
+    }
+
+    fn rtc_mut(&mut self) -> Option<&mut Rtc> {
+        self.rtc.as_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cartridge::mbc::{RAM_BANK_SIZE, ROM_BANK_SIZE};
+
+    /// A 2 MiB ROM whose banks each start with their own bank number.
+    fn numbered_rom() -> Vec<u8> {
+        let mut rom = vec![0; 128 * ROM_BANK_SIZE];
+        for (bank, chunk) in rom.chunks_exact_mut(ROM_BANK_SIZE).enumerate() {
+            chunk[0] = bank as u8;
+        }
+        rom
+    }
+
+    #[test]
+    fn bank_0_selects_bank_1() {
+        let mut mbc = Mbc3::new(numbered_rom(), 0, false);
+        assert_eq!(mbc.read(0x4000), 1);
+
+        mbc.write(0x2000, 0x00);
+        assert_eq!(mbc.read(0x4000), 1);
+        mbc.write(0x2000, 0x05);
+        assert_eq!(mbc.read(0x4000), 5);
+        assert_eq!(mbc.read(0x0000), 0);
+    }
+
+    #[test]
+    fn rom_bank_number_is_7_bits() {
+        let mut mbc = Mbc3::new(numbered_rom(), 0, false);
+        mbc.write(0x2000, 0x7F);
+        assert_eq!(mbc.read(0x4000), 0x7F);
+        mbc.write(0x2000, 0x85);
+        assert_eq!(mbc.read(0x4000), 0x05);
+        // Bit 7 is ignored before the 0 check too
+        mbc.write(0x2000, 0x80);
+        assert_eq!(mbc.read(0x4000), 1);
+    }
+
+    #[test]
+    fn ram_bank_register_selects_ram_or_clock() {
+        let mut mbc = Mbc3::new(numbered_rom(), 4 * RAM_BANK_SIZE, true);
+        mbc.write(0x0000, 0x0A);
+        mbc.write(0x4000, 0x02);
+        mbc.write(0xA000, 0x22);
+        // Hours register
+        mbc.write(0x4000, 0x0A);
+        mbc.write(0xA000, 5);
+
+        mbc.write(0x4000, 0x02);
+        assert_eq!(mbc.read(0xA000), 0x22);
+        assert_eq!(mbc.ram()[2 * RAM_BANK_SIZE], 0x22);
+        mbc.write(0x6000, 0x00);
+        mbc.write(0x6000, 0x01);
+        mbc.write(0x4000, 0x0A);
+        assert_eq!(mbc.read(0xA000), 5);
+        assert_eq!(mbc.ram().iter().filter(|&&byte| byte != 0).count(), 1);
+
+        // Unmapped selections read open bus
+        mbc.write(0x4000, 0x05);
+        assert_eq!(mbc.read(0xA000), 0xFF);
+    }
+
+    #[test]
+    fn clock_registers_need_a_timer() {
+        let mut mbc = Mbc3::new(numbered_rom(), RAM_BANK_SIZE, false);
+        mbc.write(0x0000, 0x0A);
+        mbc.write(0x4000, 0x08);
+        mbc.write(0xA000, 30);
+        assert_eq!(mbc.read(0xA000), 0xFF);
+        assert!(mbc.rtc().is_none());
+    }
+}
@@ -0,0 +1,131 @@
+use super::{Mbc, ram_offset, read_rom_bank};
+
+/// MBC5, supporting up to 8 MiB of ROM and 128 KiB of RAM. Unlike MBC1 and
+/// MBC3, bank 0 can be mapped at `0x4000..=0x7FFF`.
+pub struct Mbc5 {
+    rom: Vec<u8>,
+    ram: Vec<u8>,
+    ram_enabled: bool,
+    /// 9-bit ROM bank number.
+    rom_bank: u16,
+    ram_bank: u8,
+}
+
+impl Mbc5 {
+    pub fn new(rom: Vec<u8>, ram_size: usize) -> Mbc5 {
+        Mbc5 { rom, ram: vec![0; ram_size], ram_enabled: false, rom_bank: 1, ram_bank: 0 }
+    }
+}
+
+impl Mbc for Mbc5 {
+    fn read(&self, addr: u16) -> u8 {
+        match addr {
+            0x0000..=0x3FFF => read_rom_bank(&self.rom, 0, addr),
+            0x4000..=0x7FFF => read_rom_bank(&self.rom, self.rom_bank as usize, addr),
+            _ => match ram_offset(&self.ram, self.ram_bank as usize, addr) {
+                Some(offset) if self.ram_enabled => self.ram[offset],
+                _ => 0xFF,
+            },
+        }
+    }
+
+    fn write(&mut self, addr: u16, value: u8) {
+        match addr {
+            0x0000..=0x1FFF => self.ram_enabled = value == 0x0A,
+            0x2000..=0x2FFF => self.rom_bank = (self.rom_bank & 0x100) | value as u16,
+            0x3000..=0x3FFF => self.rom_bank = (self.rom_bank & 0xFF) | ((value as u16 & 0x01) << 8),
+            0x4000..=0x5FFF => self.ram_bank = value & 0x0F,
+            0x6000..=0x7FFF => {}
+            _ => {
+                if self.ram_enabled
+                    && let Some(offset) = ram_offset(&self.ram, self.ram_bank as usize, addr)
+                {
+                    self.ram[offset] = value;
+                }
+            }
+        }
+    }
+
+    fn ram(&self) -> &[u8] {
+        &self.ram
+    }
+
+    fn ram_mut(&mut self) -> &mut [u8] {
+        &mut self.ram
+    }
+
+    fn registers(&self) -> Vec<u8> {
+        let [rom_bank_low, rom_bank_high] = self.rom_bank.to_le_bytes();
+        vec![self.ram_enabled as u8, rom_bank_low, rom_bank_high, self.ram_bank]
+    }
+
+    fn set_registers(&mut self, registers: &[u8]) {
+        if let [ram_enabled, rom_bank_low, rom_bank_high, ram_bank] = *registers {
+            self.ram_enabled = ram_enabled != 0;
+            self.rom_bank = u16::from_le_bytes([rom_bank_low, rom_bank_high]);
+            self.ram_bank = ram_bank;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cartridge::mbc::{RAM_BANK_SIZE, ROM_BANK_SIZE};
+
+    /// An 8 MiB ROM whose banks each start with their own 16-bit bank number.
+    fn numbered_rom() -> Vec<u8> {
+        let mut rom = vec![0; 512 * ROM_BANK_SIZE];
+        for (bank, chunk) in rom.chunks_exact_mut(ROM_BANK_SIZE).enumerate() {
+            chunk[..2].copy_from_slice(&(bank as u16).to_le_bytes());
+        }
+        rom
+    }
+
+    /// Returns the number of the bank mapped at `0x4000..=0x7FFF`.
+    fn upper_bank(mbc: &Mbc5) -> u16 {
+        u16::from_le_bytes([mbc.read(0x4000), mbc.read(0x4001)])
+    }
+
+    #[test]
+    fn bank_0_can_be_mapped_high() {
+        let mut mbc = Mbc5::new(numbered_rom(), 0);
+        assert_eq!(upper_bank(&mbc), 1);
+
+        mbc.write(0x2000, 0x00);
+        assert_eq!(upper_bank(&mbc), 0);
+        mbc.write(0x2000, 0xFF);
+        assert_eq!(upper_bank(&mbc), 0xFF);
+    }
+
+    #[test]
+    fn ninth_bank_bit_is_written_separately() {
+        let mut mbc = Mbc5::new(numbered_rom(), 0);
+        mbc.write(0x2000, 0x23);
+        mbc.write(0x3000, 0x01);
+        assert_eq!(upper_bank(&mbc), 0x123);
+        // Each register keeps the other's bits
+        mbc.write(0x2000, 0x45);
+        assert_eq!(upper_bank(&mbc), 0x145);
+        // Only bit 0 of the upper register is used
+        mbc.write(0x3000, 0xFE);
+        assert_eq!(upper_bank(&mbc), 0x045);
+        assert_eq!(mbc.read(0x0000), 0);
+    }
+
+    #[test]
+    fn ram_banks_are_switchable() {
+        let mut mbc = Mbc5::new(numbered_rom(), 4 * RAM_BANK_SIZE);
+        mbc.write(0x0000, 0x0A);
+        mbc.write(0x4000, 0x03);
+        mbc.write(0xA000, 0x33);
+        mbc.write(0x4000, 0x00);
+        assert_eq!(mbc.read(0xA000), 0x00);
+        mbc.write(0x4000, 0x03);
+        assert_eq!(mbc.read(0xA000), 0x33);
+        assert_eq!(mbc.ram()[3 * RAM_BANK_SIZE], 0x33);
+
+        mbc.write(0x0000, 0x00);
+        assert_eq!(mbc.read(0xA000), 0xFF);
+    }
+}
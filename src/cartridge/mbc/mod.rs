@@ -0,0 +1,115 @@
+//! Memory bank controllers, which map banks of the cartridge ROM and external
+//! RAM into `0x0000..=0x7FFF` and `0xA000..=0xBFFF`.
+
+mod mbc1;
+mod mbc2;
+mod mbc3;
+mod mbc5;
+mod rtc;
+
+pub use mbc1::Mbc1;
+pub use mbc2::Mbc2;
+pub use mbc3::Mbc3;
+pub use mbc5::Mbc5;
+pub use rtc::Rtc;
+
+/// Size of a switchable ROM bank.
+pub const ROM_BANK_SIZE: usize = 0x4000;
+/// Size of a switchable external RAM bank.
+pub const RAM_BANK_SIZE: usize = 0x2000;
+
+/// A memory bank controller as seen from the CPU bus.
+///
+/// Addresses are absolute bus addresses: `read` is only called for ROM
+/// (`0x0000..=0x7FFF`) and external RAM (`0xA000..=0xBFFF`), while writes to
+/// the ROM area program the controller's registers.
+pub trait Mbc: Send {
+    fn read(&self, addr: u16) -> u8;
+    fn write(&mut self, addr: u16, value: u8);
+
+    /// The whole external RAM, all banks in order.
+    fn ram(&self) -> &[u8];
+    fn ram_mut(&mut self) -> &mut [u8];
+
+    /// The controller registers packed into bytes, for save states.
+    fn registers(&self) -> Vec<u8>;
+    /// Restores registers returned by [`registers`](Mbc::registers). Data of
+    /// the wrong length is ignored.
+    fn set_registers(&mut self, registers: &[u8]);
+
+    /// Advances controller hardware that keeps time, such as the MBC3 clock,
+    /// by one M-cycle.
+    fn tick(&mut self) {}
+
+    /// The real-time clock, for controllers that have one.
+    fn rtc(&self) -> Option<&Rtc> {
+        None
+    }
+
+    fn rtc_mut(&mut self) -> Option<&mut Rtc> {
+        None
+    }
+}
+
+/// A cartridge without a controller: 32 KiB of ROM and optionally up to
+/// 8 KiB of always-enabled RAM.
+pub struct RomOnly {
+    rom: Vec<u8>,
+    ram: Vec<u8>,
+}
+
+impl RomOnly {
+    pub fn new(rom: Vec<u8>, ram_size: usize) -> RomOnly {
+        RomOnly { rom, ram: vec![0; ram_size] }
+    }
+}
+
+impl Mbc for RomOnly {
+    fn read(&self, addr: u16) -> u8 {
+        match addr {
+            0x0000..=0x7FFF => self.rom.get(addr as usize).copied().unwrap_or(0xFF),
+            _ => self.ram.get((addr - 0xA000) as usize).copied().unwrap_or(0xFF),
+        }
+    }
+
+    fn write(&mut self, addr: u16, value: u8) {
+        if let 0xA000..=0xBFFF = addr
+            && let Some(byte) = self.ram.get_mut((addr - 0xA000) as usize)
+        {
+            *byte = value;
+        }
+    }
+
+    fn ram(&self) -> &[u8] {
+        &self.ram
+    }
+
+    fn ram_mut(&mut self) -> &mut [u8] {
+        &mut self.ram
+    }
+
+    fn registers(&self) -> Vec<u8> {
+        Vec::new()
+    }
+
+    fn set_registers(&mut self, _registers: &[u8]) {}
+}
+
+/// Reads `addr` (`0x0000..=0x3FFF` or `0x4000..=0x7FFF`) from ROM `bank`,
+/// wrapping the bank number around the actual ROM size like the hardware,
+/// which ignores bank bits beyond the connected address lines.
+pub(crate) fn read_rom_bank(rom: &[u8], bank: usize, addr: u16) -> u8 {
+    let bank_count = (rom.len() / ROM_BANK_SIZE).max(1);
+    let offset = (bank % bank_count) * ROM_BANK_SIZE + (addr as usize & (ROM_BANK_SIZE - 1));
+    rom.get(offset).copied().unwrap_or(0xFF)
+}
+
+/// Returns the offset of `addr` (`0xA000..=0xBFFF`) within RAM `bank`, or
+/// `None` when the cartridge has no RAM.
+pub(crate) fn ram_offset(ram: &[u8], bank: usize, addr: u16) -> Option<usize> {
+    if ram.is_empty() {
+        return None;
+    }
+    let offset = bank * RAM_BANK_SIZE + (addr as usize - 0xA000);
+    Some(offset % ram.len())
+}
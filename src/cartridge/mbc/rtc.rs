@@ -0,0 +1,339 @@
+//! The MBC3 real-time clock, following the "MBC3" chapter of Pan Docs.
+//!
+//! The clock counts emulated time while the game runs, one second every
+//! 2^20 M-cycles, and catches up on the wall-clock time that passed while the
+//! emulator was closed when it is loaded from a save file.
+
+/// M-cycles in one second of emulated time.
+const M_CYCLES_PER_SECOND: u32 = 1 << 20;
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+/// The day counter is 9 bits wide.
+const DAYS: u64 = 512;
+
+/// Day high register: bit 8 of the day counter, the halt bit and the day
+/// counter carry bit.
+const DAY_HIGH_MSB: u8 = 0x01;
+const DAY_HIGH_HALT: u8 = 0x40;
+const DAY_HIGH_CARRY: u8 = 0x80;
+
+/// Length of the clock data appended to `.sav` files: the current and
+/// latched registers as 32-bit values, then a 64-bit UNIX timestamp. This is
+/// the layout used by BGB and VBA-M, whose 32-bit timestamp variant is
+/// 4 bytes shorter.
+const SAVE_DATA_SIZE: usize = 48;
+const SAVE_DATA_SIZE_SHORT: usize = 44;
+
+/// The five clock registers, in RAM bank selection order `0x08..=0x0C`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Registers {
+    seconds: u8,
+    minutes: u8,
+    hours: u8,
+    day_low: u8,
+    day_high: u8,
+}
+
+impl Registers {
+    fn to_bytes(self) -> [u8; 5] {
+        [self.seconds, self.minutes, self.hours, self.day_low, self.day_high]
+    }
+
+    fn from_bytes(bytes: [u8; 5]) -> Registers {
+        let [seconds, minutes, hours, day_low, day_high] = bytes;
+        Registers { seconds, minutes, hours, day_low, day_high }
+    }
+
+    fn days(&self) -> u64 {
+        ((self.day_high & DAY_HIGH_MSB) as u64) << 8 | self.day_low as u64
+    }
+
+    fn set_days(&mut self, days: u64) {
+        self.day_low = days as u8;
+        self.day_high = (self.day_high & !DAY_HIGH_MSB) | ((days >> 8) as u8 & DAY_HIGH_MSB);
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Rtc {
+    /// The running clock.
+    current: Registers,
+    /// The copy the CPU reads, updated by the latch sequence.
+    latched: Registers,
+    /// M-cycles into the current second.
+    cycles: u32,
+    /// The last write to `0x6000..=0x7FFF` was 0, so writing 1 latches.
+    latch_armed: bool,
+}
+
+impl Rtc {
+    pub fn new() -> Rtc {
+        Rtc::default()
+    }
+
+    /// Handles a write to the latch register: writing 0 then 1 copies the
+    /// running clock into the readable registers.
+    pub fn write_latch(&mut self, value: u8) {
+        if self.latch_armed && value == 0x01 {
+            self.latched = self.current;
+        }
+        self.latch_armed = value == 0x00;
+    }
+
+    /// Reads latched register `register` (`0x08..=0x0C`).
+    pub fn read(&self, register: u8) -> u8 {
+        let latched = &self.latched;
+        match register {
+            0x08 => latched.seconds,
+            0x09 => latched.minutes,
+            0x0A => latched.hours,
+            0x0B => latched.day_low,
+            // The unused bits read as 1
+            0x0C => latched.day_high | 0x3E,
+            _ => 0xFF,
+        }
+    }
+
+    /// Writes running register `register` (`0x08..=0x0C`). Writing the
+    /// seconds also restarts the current second.
+    pub fn write(&mut self, register: u8, value: u8) {
+        let current = &mut self.current;
+        match register {
+            0x08 => {
+                current.seconds = value & 0x3F;
+                self.cycles = 0;
+            }
+            0x09 => current.minutes = value & 0x3F,
+            0x0A => current.hours = value & 0x1F,
+            0x0B => current.day_low = value,
+            0x0C => current.day_high = value & (DAY_HIGH_MSB | DAY_HIGH_HALT | DAY_HIGH_CARRY),
+            _ => {}
+        }
+    }
+
+    fn halted(&self) -> bool {
+        self.current.day_high & DAY_HIGH_HALT != 0
+    }
+
+    /// Advances the clock by one M-cycle.
+    pub fn tick(&mut self) {
+        if self.halted() {
+            return;
+        }
+        self.cycles += 1;
+        if self.cycles == M_CYCLES_PER_SECOND {
+            self.cycles = 0;
+            self.tick_second();
+        }
+    }
+
+    /// Counts one second. Like the hardware, counters holding out-of-range
+    /// values keep incrementing up to their bit width and wrap to 0 without
+    /// a carry.
+    fn tick_second(&mut self) {
+        let current = &mut self.current;
+        if current.seconds != 59 {
+            current.seconds = (current.seconds + 1) & 0x3F;
+            return;
+        }
+        current.seconds = 0;
+        if current.minutes != 59 {
+            current.minutes = (current.minutes + 1) & 0x3F;
+            return;
+        }
+        current.minutes = 0;
+        if current.hours != 23 {
+            current.hours = (current.hours + 1) & 0x1F;
+            return;
+        }
+        current.hours = 0;
+        self.advance_days(1);
+    }
+
+    fn advance_days(&mut self, days: u64) {
+        let days = self.current.days() + days;
+        if days >= DAYS {
+            self.current.day_high |= DAY_HIGH_CARRY;
+        }
+        self.current.set_days(days % DAYS);
+    }
+
+    /// Advances a running clock by `seconds`, as if that much time passed
+    /// with the emulator closed.
+    pub fn advance(&mut self, seconds: u64) {
+        if self.halted() || seconds == 0 {
+            return;
+        }
+        let current = &mut self.current;
+        // Out-of-range values are normalized along the way
+        let time = current.hours as u64 * 3600 + current.minutes as u64 * 60 + current.seconds as u64 + seconds;
+        current.seconds = (time % 60) as u8;
+        current.minutes = (time / 60 % 60) as u8;
+        current.hours = (time / 3600 % 24) as u8;
+        self.advance_days(time / SECONDS_PER_DAY);
+    }
+
+    /// The clock state for save states.
+    pub(crate) fn registers(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(15);
+        bytes.extend(self.current.to_bytes());
+        bytes.extend(self.latched.to_bytes());
+        bytes.extend(self.cycles.to_le_bytes());
+        bytes.push(self.latch_armed as u8);
+        bytes
+    }
+
+    /// Restores a state returned by [`registers`](Rtc::registers). Data of
+    /// the wrong length is ignored.
+    pub(crate) fn set_registers(&mut self, bytes: &[u8]) {
+        let Ok(bytes) = <[u8; 15]>::try_from(bytes) else {
+            return;
+        };
+        self.current = Registers::from_bytes(bytes[0..5].try_into().expect("5 bytes"));
+        self.latched = Registers::from_bytes(bytes[5..10].try_into().expect("5 bytes"));
+        self.cycles = u32::from_le_bytes(bytes[10..14].try_into().expect("4 bytes"));
+        self.latch_armed = bytes[14] != 0;
+    }
+
+    /// Encodes the clock for the end of a `.sav` file, stamped with
+    /// `timestamp` (seconds since the UNIX epoch).
+    pub fn save_data(&self, timestamp: u64) -> [u8; SAVE_DATA_SIZE] {
+        let mut data = [0; SAVE_DATA_SIZE];
+        let registers = self.current.to_bytes().into_iter().chain(self.latched.to_bytes());
+        for (chunk, register) in data.chunks_exact_mut(4).zip(registers) {
+            chunk[0] = register;
+        }
+        data[40..].copy_from_slice(&timestamp.to_le_bytes());
+        data
+    }
+
+    /// Restores a clock saved by [`save_data`](Rtc::save_data) and advances
+    /// it by the time elapsed between the save's timestamp and `now`. Returns
+    /// `false`, leaving the clock untouched, if `data` is too short.
+    pub fn load_save_data(&mut self, data: &[u8], now: u64) -> bool {
+        if data.len() < SAVE_DATA_SIZE_SHORT {
+            return false;
+        }
+
+        let register = |index: usize| data[index * 4];
+        self.current = Registers::from_bytes([register(0), register(1), register(2), register(3), register(4)]);
+        self.latched = Registers::from_bytes([register(5), register(6), register(7), register(8), register(9)]);
+        self.cycles = 0;
+        let timestamp = match data.get(40..SAVE_DATA_SIZE) {
+            Some(bytes) => u64::from_le_bytes(bytes.try_into().expect("8 bytes")),
+            None => u32::from_le_bytes(data[40..44].try_into().expect("4 bytes")) as u64,
+        };
+        self.advance(now.saturating_sub(timestamp));
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn latch(rtc: &mut Rtc) {
+        rtc.write_latch(0x00);
+        rtc.write_latch(0x01);
+    }
+
+    fn tick_seconds(rtc: &mut Rtc, seconds: u32) {
+        for _ in 0..seconds * M_CYCLES_PER_SECOND {
+            rtc.tick();
+        }
+    }
+
+    /// Returns the latched registers `0x08..=0x0C`.
+    fn latched(rtc: &mut Rtc) -> [u8; 5] {
+        latch(rtc);
+        [0x08, 0x09, 0x0A, 0x0B, 0x0C].map(|register| rtc.read(register))
+    }
+
+    #[test]
+    fn reads_stay_frozen_until_the_next_latch() {
+        let mut rtc = Rtc::new();
+        rtc.write(0x08, 10);
+        latch(&mut rtc);
+        tick_seconds(&mut rtc, 2);
+
+        assert_eq!(rtc.read(0x08), 10);
+        latch(&mut rtc);
+        assert_eq!(rtc.read(0x08), 12);
+    }
+
+    #[test]
+    fn latching_needs_a_0_then_a_1() {
+        let mut rtc = Rtc::new();
+        rtc.write(0x08, 10);
+        rtc.write_latch(0x01);
+        assert_eq!(rtc.read(0x08), 0);
+
+        rtc.write_latch(0x00);
+        rtc.write_latch(0x02);
+        rtc.write_latch(0x01);
+        assert_eq!(rtc.read(0x08), 0);
+    }
+
+    #[test]
+    fn seconds_carry_into_minutes_hours_and_days() {
+        let mut rtc = Rtc::new();
+        for (register, value) in [(0x08, 59), (0x09, 59), (0x0A, 23), (0x0B, 0xFF)] {
+            rtc.write(register, value);
+        }
+        tick_seconds(&mut rtc, 1);
+
+        assert_eq!(latched(&mut rtc), [0, 0, 0, 0x00, 0x3F]);
+    }
+
+    #[test]
+    fn day_counter_overflow_sets_the_carry() {
+        let mut rtc = Rtc::new();
+        rtc.write(0x0B, 0xFF);
+        rtc.write(0x0C, DAY_HIGH_MSB);
+        rtc.advance(SECONDS_PER_DAY);
+
+        assert_eq!(latched(&mut rtc)[3..], [0x00, 0xBE]);
+        // The carry stays set until written
+        rtc.advance(SECONDS_PER_DAY);
+        assert_eq!(latched(&mut rtc)[3..], [0x01, 0xBE]);
+        rtc.write(0x0C, 0x00);
+        assert_eq!(latched(&mut rtc)[4], 0x3E);
+    }
+
+    #[test]
+    fn halt_bit_stops_the_clock() {
+        let mut rtc = Rtc::new();
+        rtc.write(0x0C, DAY_HIGH_HALT);
+        tick_seconds(&mut rtc, 2);
+        rtc.advance(100);
+
+        assert_eq!(latched(&mut rtc), [0, 0, 0, 0, 0x7E]);
+        rtc.write(0x0C, 0x00);
+        tick_seconds(&mut rtc, 1);
+        assert_eq!(latched(&mut rtc)[0], 1);
+    }
+
+    #[test]
+    fn writing_the_seconds_restarts_the_second() {
+        let mut rtc = Rtc::new();
+        for _ in 0..M_CYCLES_PER_SECOND - 1 {
+            rtc.tick();
+        }
+        rtc.write(0x08, 0);
+        rtc.tick();
+
+        assert_eq!(latched(&mut rtc)[0], 0);
+    }
+
+    #[test]
+    fn save_data_catches_up_on_elapsed_time() {
+        let mut rtc = Rtc::new();
+        rtc.write(0x0A, 23);
+        let data = rtc.save_data(1_000);
+
+        let mut loaded = Rtc::new();
+        assert!(loaded.load_save_data(&data, 1_000 + 3_661));
+        assert_eq!(latched(&mut loaded), [1, 1, 0, 1, 0x3E]);
+        assert!(!loaded.load_save_data(&data[..SAVE_DATA_SIZE_SHORT - 1], 0));
+    }
+}
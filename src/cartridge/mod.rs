@@ -0,0 +1,277 @@
+//! Game Boy cartridges: the ROM header and the memory bank controllers that
+//! map ROM and external RAM into the address space.
+
+pub mod header;
+pub mod mbc;
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use header::{CartridgeHeader, HeaderError, MbcKind};
+use mbc::{Mbc, Mbc1, Mbc2, Mbc3, Mbc5, RomOnly, Rtc};
+
+/// A loaded cartridge: its parsed header and the controller serving its ROM
+/// and external RAM.
+pub struct Cartridge {
+    header: CartridgeHeader,
+    mbc: Box<dyn Mbc>,
+    /// The controller registers at power-on, restored by
+    /// [`reset`](Cartridge::reset).
+    initial_registers: Vec<u8>,
+    /// Whether external RAM was written since it was last loaded or saved.
+    ram_modified: bool,
+}
+
+/// The mutable part of a cartridge saved in save states: external RAM and
+/// the controller registers, but not the ROM.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CartridgeState {
+    ram: Vec<u8>,
+    registers: Vec<u8>,
+}
+
+/// An empty cartridge slot: every read returns `0xFF`.
+impl Default for Cartridge {
+    fn default() -> Cartridge {
+        Cartridge {
+            header: CartridgeHeader::default(),
+            mbc: Box::new(RomOnly::new(Vec::new(), 0)),
+            initial_registers: Vec::new(),
+            ram_modified: false,
+        }
+    }
+}
+
+impl Cartridge {
+    /// Parses the header of `rom` and sets up the memory bank controller
+    /// selected by its cartridge type byte.
+    pub fn new(rom: Vec<u8>) -> Result<Cartridge, HeaderError> {
+        let header = CartridgeHeader::parse(&rom)?;
+        let ram_size = header.ram_size;
+
+        let mbc: Box<dyn Mbc> = match header.mbc {
+            MbcKind::RomOnly => Box::new(RomOnly::new(rom, ram_size)),
+            MbcKind::Mbc1 => Box::new(Mbc1::new(rom, ram_size)),
+            MbcKind::Mbc2 => Box::new(Mbc2::new(rom)),
+            MbcKind::Mbc3 => Box::new(Mbc3::new(rom, ram_size, header.has_timer)),
+            MbcKind::Mbc5 => Box::new(Mbc5::new(rom, ram_size)),
+        };
+
+        let initial_registers = mbc.registers();
+        Ok(Cartridge { header, mbc, initial_registers, ram_modified: false })
+    }
+
+    /// Resets the controller registers to their power-on values. External
+    /// RAM and the real-time clock, being battery-backed, are kept.
+    pub fn reset(&mut self) {
+        let rtc = self.mbc.rtc().cloned();
+        self.mbc.set_registers(&self.initial_registers);
+        if let (Some(rtc), Some(current)) = (rtc, self.mbc.rtc_mut()) {
+            *current = rtc;
+        }
+    }
+
+    pub fn header(&self) -> &CartridgeHeader {
+        &self.header
+    }
+
+    /// Reads a byte of ROM (`0x0000..=0x7FFF`) or external RAM
+    /// (`0xA000..=0xBFFF`).
+    pub fn read(&self, addr: u16) -> u8 {
+        self.mbc.read(addr)
+    }
+
+    /// Advances the cartridge hardware by one M-cycle of real time.
+    pub fn tick(&mut self) {
+        self.mbc.tick();
+    }
+
+    /// The MBC3 real-time clock, if the cartridge has one.
+    pub fn rtc(&self) -> Option<&Rtc> {
+        self.mbc.rtc()
+    }
+
+    pub fn rtc_mut(&mut self) -> Option<&mut Rtc> {
+        self.mbc.rtc_mut()
+    }
+
+    /// Writes a controller register (`0x0000..=0x7FFF`) or a byte of external
+    /// RAM (`0xA000..=0xBFFF`).
+    pub fn write(&mut self, addr: u16, value: u8) {
+        self.mbc.write(addr, value);
+        if let 0xA000..=0xBFFF = addr {
+            self.ram_modified = true;
+        }
+    }
+
+    /// Returns `true` if the external RAM or clock survives power-off, i.e.
+    /// it should be persisted to a save file.
+    pub fn has_battery(&self) -> bool {
+        self.header.has_battery && (!self.mbc.ram().is_empty() || self.mbc.rtc().is_some())
+    }
+
+    /// Returns `true` if external RAM was written since the last
+    /// [`load_ram`](Cartridge::load_ram) or [`save_ram`](Cartridge::save_ram),
+    /// so a frontend can flush saves periodically without rewriting the file
+    /// every time.
+    pub fn ram_modified(&self) -> bool {
+        self.ram_modified
+    }
+
+    /// Loads battery-backed RAM from `path`.
+    ///
+    /// A missing file leaves the RAM zeroed, and cartridges without a battery
+    /// are left untouched. A file of the wrong size is loaded as far as it
+    /// goes, so saves from emulators that append extra data still work.
+    ///
+    /// The clock of an MBC3 cartridge is read from the end of the file and
+    /// advanced by the time elapsed since it was saved.
+    pub fn load_ram(&mut self, path: &Path) -> io::Result<()> {
+        if !self.has_battery() {
+            return Ok(());
+        }
+
+        let data = match fs::read(path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        let ram = self.mbc.ram_mut();
+        let len = ram.len().min(data.len());
+        ram[..len].copy_from_slice(&data[..len]);
+        if let Some(rtc) = self.mbc.rtc_mut() {
+            rtc.load_save_data(&data[len..], unix_time());
+        }
+        self.ram_modified = false;
+        Ok(())
+    }
+
+    pub fn save_state(&self) -> CartridgeState {
+        CartridgeState { ram: self.mbc.ram().to_vec(), registers: self.mbc.registers() }
+    }
+
+    /// Restores RAM and controller registers saved from the same cartridge.
+    pub fn load_state(&mut self, state: &CartridgeState) {
+        let ram = self.mbc.ram_mut();
+        let len = ram.len().min(state.ram.len());
+        ram[..len].copy_from_slice(&state.ram[..len]);
+        self.mbc.set_registers(&state.registers);
+        self.ram_modified = true;
+    }
+
+    /// Writes battery-backed RAM to `path`, followed by the clock of MBC3
+    /// cartridges. Does nothing for cartridges without a battery.
+    pub fn save_ram(&mut self, path: &Path) -> io::Result<()> {
+        if !self.has_battery() {
+            return Ok(());
+        }
+
+        let mut data = self.mbc.ram().to_vec();
+        if let Some(rtc) = self.mbc.rtc() {
+            data.extend(rtc.save_data(unix_time()));
+        }
+        fs::write(path, data)?;
+        self.ram_modified = false;
+        Ok(())
+    }
+}
+
+/// Returns the current time in seconds since the UNIX epoch.
+fn unix_time() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs())
+}
+
+/// Returns the save file used for the ROM at `rom_path`: the same path with a
+/// `.sav` extension.
+pub fn save_path(rom_path: &Path) -> PathBuf {
+    rom_path.with_extension("sav")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+    use crate::testing::rom;
+
+    /// MBC1+RAM+BATTERY, MBC1+RAM and MBC3+TIMER+RAM+BATTERY.
+    const MBC1_BATTERY: u8 = 0x03;
+    const MBC1_RAM: u8 = 0x02;
+    const MBC3_TIMER_BATTERY: u8 = 0x10;
+    /// RAM size code for a single 8 KiB bank.
+    const RAM_8K: u8 = 0x02;
+
+    fn cartridge(cartridge_type: u8) -> Cartridge {
+        Cartridge::new(rom(cartridge_type, 2, RAM_8K)).unwrap()
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        env::temp_dir().join(format!("rusty_boy_{}_{}.sav", name, std::process::id()))
+    }
+
+    /// Enables external RAM and writes `value` to `0xA000`.
+    fn write_ram(cartridge: &mut Cartridge, value: u8) {
+        cartridge.write(0x0000, 0x0A);
+        cartridge.write(0xA000, value);
+    }
+
+    #[test]
+    fn save_path_replaces_the_rom_extension() {
+        assert_eq!(save_path(Path::new("roms/tetris.gb")), PathBuf::from("roms/tetris.sav"));
+        assert_eq!(save_path(Path::new("roms/pokemon.gbc")), PathBuf::from("roms/pokemon.sav"));
+    }
+
+    #[test]
+    fn missing_save_file_leaves_ram_zeroed() {
+        let mut cartridge = cartridge(MBC1_BATTERY);
+        cartridge.load_ram(&temp_path("missing")).unwrap();
+
+        assert!(cartridge.mbc.ram().iter().all(|&byte| byte == 0));
+        assert!(!cartridge.ram_modified());
+    }
+
+    #[test]
+    fn cartridges_without_a_battery_are_not_saved() {
+        let path = temp_path("no_battery");
+        let mut cartridge = cartridge(MBC1_RAM);
+        assert!(!cartridge.has_battery());
+        write_ram(&mut cartridge, 0x42);
+        cartridge.save_ram(&path).unwrap();
+        assert!(!path.exists());
+
+        fs::write(&path, [0x99; 0x2000]).unwrap();
+        cartridge.load_ram(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(cartridge.read(0xA000), 0x42);
+    }
+
+    #[test]
+    fn ram_and_clock_survive_a_save_and_load() {
+        let path = temp_path("round_trip");
+        let mut saved = cartridge(MBC3_TIMER_BATTERY);
+        write_ram(&mut saved, 0x42);
+        // Hours register
+        saved.write(0x4000, 0x0A);
+        saved.write(0xA000, 5);
+        assert!(saved.ram_modified());
+        saved.save_ram(&path).unwrap();
+        assert!(!saved.ram_modified());
+
+        let mut loaded = cartridge(MBC3_TIMER_BATTERY);
+        loaded.load_ram(&path).unwrap();
+        let size = fs::metadata(&path).unwrap().len();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(size, 0x2000 + 48);
+
+        loaded.write(0x0000, 0x0A);
+        assert_eq!(loaded.read(0xA000), 0x42);
+        loaded.write(0x6000, 0x00);
+        loaded.write(0x6000, 0x01);
+        loaded.write(0x4000, 0x0A);
+        assert_eq!(loaded.read(0xA000), 5);
+    }
+}
@@ -0,0 +1,98 @@
+//! Project-wide settings shared by the scripts, loaded from an optional
+//! `rusty_boy.toml` in the project root.
+//!
+//! Every key is optional; missing keys keep their default value:
+//!
+//! ```toml
+//! pandocs_port = 3000
+//! dmg01_port = 3100
+//! resources_dir = "resources"
+//! browser = "Firefox"
+//! ```
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+/// Name of the configuration file looked up in the project root.
+pub const CONFIG_FILE: &str = "rusty_boy.toml";
+
+/// Settings for the documentation launchers and resource tooling.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// First port tried for the Pan Docs mdbook server.
+    pub pandocs_port: u16,
+    /// First port tried for the DMG-01 mdbook server.
+    pub dmg01_port: u16,
+    /// Directory the external resources are cloned into.
+    pub resources_dir: PathBuf,
+    /// Browser used to open documentation; the OS default when `None`.
+    pub browser: Option<String>,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            pandocs_port: 3000,
+            dmg01_port: 3100,
+            resources_dir: PathBuf::from("resources"),
+            browser: None,
+        }
+    }
+}
+
+/// Errors that can occur while loading the configuration file.
+#[derive(Debug)]
+pub enum ConfigError {
+    /// The file exists but could not be read.
+    Io(io::Error),
+    /// The file is not valid TOML or has unexpected keys.
+    Parse(toml::de::Error),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(e) => write!(f, "failed to read {}: {}", CONFIG_FILE, e),
+            ConfigError::Parse(e) => write!(f, "failed to parse {}: {}", CONFIG_FILE, e),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl Config {
+    /// Loads [`CONFIG_FILE`] from the current directory, falling back to the
+    /// defaults when it does not exist.
+    pub fn load() -> Result<Config, ConfigError> {
+        Config::load_from(Path::new(CONFIG_FILE))
+    }
+
+    /// Loads the configuration from `path`, falling back to the defaults when
+    /// it does not exist.
+    pub fn load_from(path: &Path) -> Result<Config, ConfigError> {
+        match fs::read_to_string(path) {
+            Ok(content) => toml::from_str(&content).map_err(ConfigError::Parse),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Config::default()),
+            Err(e) => Err(ConfigError::Io(e)),
+        }
+    }
+
+    /// Loads the configuration, or exits with an error message when the file
+    /// is present but invalid. Intended for the binaries under `scripts/`.
+    pub fn load_or_exit() -> Config {
+        Config::load().unwrap_or_else(|e| {
+            eprintln!("❌ {}", e);
+            std::process::exit(1);
+        })
+    }
+
+    /// Returns the path of a resource checkout, e.g. `resource_path("pandocs")`.
+    pub fn resource_path(&self, name: &str) -> PathBuf {
+        self.resources_dir.join(name)
+    }
+}
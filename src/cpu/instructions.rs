@@ -0,0 +1,456 @@
+//! Decoding and execution of the base and `CB`-prefixed opcode tables.
+//!
+//! Register operands are encoded as 3-bit indices in the order
+//! `B, C, D, E, H, L, (HL), A`, and register pairs as 2-bit indices in the
+//! order `BC, DE, HL, SP` (or `AF` instead of `SP` for `PUSH`/`POP`).
+
+use super::{Cpu, FLAG_C, FLAG_H, FLAG_N, FLAG_Z};
+use crate::bus::Bus;
+
+/// Register index of the `(HL)` memory operand.
+const HL_INDIRECT: u8 = 6;
+
+impl Cpu {
+    /// Executes `opcode`, whose byte has already been fetched.
+    ///
+    /// Every memory access ticks the bus through `read8`/`write8`, and the
+    /// internal delays documented by gb-ctr (16-bit arithmetic, taken
+    /// branches, stack pointer adjustment) tick it through `idle`, so the
+    /// instruction takes exactly its documented number of M-cycles.
+    pub(super) fn execute(&mut self, bus: &mut Bus, opcode: u8) {
+        // Middle and low octal digits, used by most of the regular opcode groups
+        let y = (opcode >> 3) & 0x07;
+        let z = opcode & 0x07;
+        let pair = (opcode >> 4) & 0x03;
+
+        match opcode {
+            0x00 => {}
+            0x10 => {
+                // STOP is followed by an ignored byte. In CGB mode it is also
+                // how the CPU switches speed, once KEY1 has armed the switch
+                self.fetch8(bus);
+                bus.switch_speed();
+            }
+            0x01 | 0x11 | 0x21 | 0x31 => {
+                let value = self.fetch16(bus);
+                self.set_pair(pair, value);
+            }
+            0x02 | 0x12 => {
+                let addr = self.pair(pair);
+                self.write8(bus, addr, self.a);
+            }
+            0x22 => {
+                let hl = self.hl();
+                self.write8(bus, hl, self.a);
+                self.set_hl(hl.wrapping_add(1));
+            }
+            0x32 => {
+                let hl = self.hl();
+                self.write8(bus, hl, self.a);
+                self.set_hl(hl.wrapping_sub(1));
+            }
+            0x0A | 0x1A => {
+                let addr = self.pair(pair);
+                self.a = self.read8(bus, addr);
+            }
+            0x2A => {
+                let hl = self.hl();
+                self.a = self.read8(bus, hl);
+                self.set_hl(hl.wrapping_add(1));
+            }
+            0x3A => {
+                let hl = self.hl();
+                self.a = self.read8(bus, hl);
+                self.set_hl(hl.wrapping_sub(1));
+            }
+            0x03 | 0x13 | 0x23 | 0x33 => {
+                self.set_pair(pair, self.pair(pair).wrapping_add(1));
+                self.idle(bus);
+            }
+            0x0B | 0x1B | 0x2B | 0x3B => {
+                self.set_pair(pair, self.pair(pair).wrapping_sub(1));
+                self.idle(bus);
+            }
+            0x09 | 0x19 | 0x29 | 0x39 => {
+                let hl = self.hl();
+                let value = self.pair(pair);
+                let (result, carry) = hl.overflowing_add(value);
+                self.set_flag(FLAG_N, false);
+                self.set_flag(FLAG_H, (hl & 0x0FFF) + (value & 0x0FFF) > 0x0FFF);
+                self.set_flag(FLAG_C, carry);
+                self.set_hl(result);
+                self.idle(bus);
+            }
+            0x04 | 0x0C | 0x14 | 0x1C | 0x24 | 0x2C | 0x34 | 0x3C => {
+                let value = self.read_register(bus, y);
+                let result = value.wrapping_add(1);
+                self.set_flag(FLAG_Z, result == 0);
+                self.set_flag(FLAG_N, false);
+                self.set_flag(FLAG_H, value & 0x0F == 0x0F);
+                self.write_register(bus, y, result);
+            }
+            0x05 | 0x0D | 0x15 | 0x1D | 0x25 | 0x2D | 0x35 | 0x3D => {
+                let value = self.read_register(bus, y);
+                let result = value.wrapping_sub(1);
+                self.set_flag(FLAG_Z, result == 0);
+                self.set_flag(FLAG_N, true);
+                self.set_flag(FLAG_H, value & 0x0F == 0);
+                self.write_register(bus, y, result);
+            }
+            0x06 | 0x0E | 0x16 | 0x1E | 0x26 | 0x2E | 0x36 | 0x3E => {
+                let value = self.fetch8(bus);
+                self.write_register(bus, y, value);
+            }
+            // RLCA, RRCA, RLA and RRA always clear Z, unlike their CB versions
+            0x07 | 0x0F | 0x17 | 0x1F => {
+                self.a = self.rotate_shift(y, self.a);
+                self.set_flag(FLAG_Z, false);
+            }
+            0x08 => {
+                let addr = self.fetch16(bus);
+                let [high, low] = self.sp.to_be_bytes();
+                self.write8(bus, addr, low);
+                self.write8(bus, addr.wrapping_add(1), high);
+            }
+            0x18 => {
+                let offset = self.fetch8(bus) as i8;
+                self.pc = self.pc.wrapping_add_signed(offset as i16);
+                self.idle(bus);
+            }
+            0x20 | 0x28 | 0x30 | 0x38 => {
+                let offset = self.fetch8(bus) as i8;
+                if self.condition(y - 4) {
+                    self.pc = self.pc.wrapping_add_signed(offset as i16);
+                    self.idle(bus);
+                }
+            }
+            0x27 => self.daa(),
+            0x2F => {
+                self.a = !self.a;
+                self.set_flag(FLAG_N, true);
+                self.set_flag(FLAG_H, true);
+            }
+            0x37 => {
+                self.set_flag(FLAG_N, false);
+                self.set_flag(FLAG_H, false);
+                self.set_flag(FLAG_C, true);
+            }
+            0x3F => {
+                let carry = self.flag(FLAG_C);
+                self.set_flag(FLAG_N, false);
+                self.set_flag(FLAG_H, false);
+                self.set_flag(FLAG_C, !carry);
+            }
+            0x76 => {
+                // With IME clear, a pending interrupt would end HALT at once;
+                // the CPU skips halting and trips over the next fetch instead
+                if !self.ime && bus.pending_interrupts() != 0 {
+                    self.halt_bug = true;
+                } else {
+                    self.halted = true;
+                }
+            }
+            0x40..=0x7F => {
+                let value = self.read_register(bus, z);
+                self.write_register(bus, y, value);
+            }
+            0x80..=0xBF => {
+                let value = self.read_register(bus, z);
+                self.alu(y, value);
+            }
+            0xC6 | 0xCE | 0xD6 | 0xDE | 0xE6 | 0xEE | 0xF6 | 0xFE => {
+                let value = self.fetch8(bus);
+                self.alu(y, value);
+            }
+            0xC0 | 0xC8 | 0xD0 | 0xD8 => {
+                // Evaluating the condition takes a cycle of its own
+                self.idle(bus);
+                if self.condition(y) {
+                    self.pc = self.pop16(bus);
+                    self.idle(bus);
+                }
+            }
+            0xC9 => {
+                self.pc = self.pop16(bus);
+                self.idle(bus);
+            }
+            0xD9 => {
+                self.pc = self.pop16(bus);
+                self.idle(bus);
+                self.ime = true;
+            }
+            0xC1 | 0xD1 | 0xE1 | 0xF1 => {
+                let value = self.pop16(bus);
+                self.set_stack_pair(pair, value);
+            }
+            0xC5 | 0xD5 | 0xE5 | 0xF5 => {
+                let value = self.stack_pair(pair);
+                self.idle(bus);
+                self.push16(bus, value);
+            }
+            0xC2 | 0xCA | 0xD2 | 0xDA => {
+                let addr = self.fetch16(bus);
+                if self.condition(y) {
+                    self.pc = addr;
+                    self.idle(bus);
+                }
+            }
+            0xC3 => {
+                self.pc = self.fetch16(bus);
+                self.idle(bus);
+            }
+            0xE9 => self.pc = self.hl(),
+            0xC4 | 0xCC | 0xD4 | 0xDC => {
+                let addr = self.fetch16(bus);
+                if self.condition(y) {
+                    self.idle(bus);
+                    self.push16(bus, self.pc);
+                    self.pc = addr;
+                }
+            }
+            0xCD => {
+                let addr = self.fetch16(bus);
+                self.idle(bus);
+                self.push16(bus, self.pc);
+                self.pc = addr;
+            }
+            0xC7 | 0xCF | 0xD7 | 0xDF | 0xE7 | 0xEF | 0xF7 | 0xFF => {
+                self.idle(bus);
+                self.push16(bus, self.pc);
+                self.pc = (y as u16) * 8;
+            }
+            0xCB => {
+                let opcode = self.fetch8(bus);
+                self.execute_cb(bus, opcode);
+            }
+            0xE0 => {
+                let offset = self.fetch8(bus);
+                self.write8(bus, 0xFF00 | offset as u16, self.a);
+            }
+            0xF0 => {
+                let offset = self.fetch8(bus);
+                self.a = self.read8(bus, 0xFF00 | offset as u16);
+            }
+            0xE2 => self.write8(bus, 0xFF00 | self.c as u16, self.a),
+            0xF2 => self.a = self.read8(bus, 0xFF00 | self.c as u16),
+            0xEA => {
+                let addr = self.fetch16(bus);
+                self.write8(bus, addr, self.a);
+            }
+            0xFA => {
+                let addr = self.fetch16(bus);
+                self.a = self.read8(bus, addr);
+            }
+            0xE8 => {
+                let offset = self.fetch8(bus);
+                self.sp = self.add_sp_offset(offset);
+                self.idle(bus);
+                self.idle(bus);
+            }
+            0xF8 => {
+                let offset = self.fetch8(bus);
+                let value = self.add_sp_offset(offset);
+                self.set_hl(value);
+                self.idle(bus);
+            }
+            0xF9 => {
+                self.sp = self.hl();
+                self.idle(bus);
+            }
+            0xF3 => {
+                self.ime = false;
+                self.ime_scheduled = false;
+            }
+            0xFB => self.ime_scheduled = true,
+            // 0xD3, 0xDB, 0xDD, 0xE3, 0xE4, 0xEB, 0xEC, 0xED, 0xF4, 0xFC, 0xFD
+            _ => self.locked = true,
+        }
+    }
+
+    /// Executes a `CB`-prefixed opcode whose second byte has been fetched.
+    fn execute_cb(&mut self, bus: &mut Bus, opcode: u8) {
+        let y = (opcode >> 3) & 0x07;
+        let z = opcode & 0x07;
+        let value = self.read_register(bus, z);
+
+        match opcode >> 6 {
+            0 => {
+                let result = self.rotate_shift(y, value);
+                self.write_register(bus, z, result);
+            }
+            1 => {
+                self.set_flag(FLAG_Z, value & (1 << y) == 0);
+                self.set_flag(FLAG_N, false);
+                self.set_flag(FLAG_H, true);
+            }
+            2 => self.write_register(bus, z, value & !(1 << y)),
+            _ => self.write_register(bus, z, value | (1 << y)),
+        }
+    }
+
+    fn read_register(&mut self, bus: &mut Bus, index: u8) -> u8 {
+        match index {
+            0 => self.b,
+            1 => self.c,
+            2 => self.d,
+            3 => self.e,
+            4 => self.h,
+            5 => self.l,
+            HL_INDIRECT => {
+                let hl = self.hl();
+                self.read8(bus, hl)
+            }
+            _ => self.a,
+        }
+    }
+
+    fn write_register(&mut self, bus: &mut Bus, index: u8, value: u8) {
+        match index {
+            0 => self.b = value,
+            1 => self.c = value,
+            2 => self.d = value,
+            3 => self.e = value,
+            4 => self.h = value,
+            5 => self.l = value,
+            HL_INDIRECT => {
+                let hl = self.hl();
+                self.write8(bus, hl, value);
+            }
+            _ => self.a = value,
+        }
+    }
+
+    fn pair(&self, index: u8) -> u16 {
+        match index {
+            0 => self.bc(),
+            1 => self.de(),
+            2 => self.hl(),
+            _ => self.sp,
+        }
+    }
+
+    fn set_pair(&mut self, index: u8, value: u16) {
+        match index {
+            0 => self.set_bc(value),
+            1 => self.set_de(value),
+            2 => self.set_hl(value),
+            _ => self.sp = value,
+        }
+    }
+
+    fn stack_pair(&self, index: u8) -> u16 {
+        if index == 3 { self.af() } else { self.pair(index) }
+    }
+
+    fn set_stack_pair(&mut self, index: u8, value: u16) {
+        if index == 3 {
+            self.set_af(value);
+        } else {
+            self.set_pair(index, value);
+        }
+    }
+
+    /// Evaluates condition `index`: NZ, Z, NC or C.
+    fn condition(&self, index: u8) -> bool {
+        match index {
+            0 => !self.flag(FLAG_Z),
+            1 => self.flag(FLAG_Z),
+            2 => !self.flag(FLAG_C),
+            _ => self.flag(FLAG_C),
+        }
+    }
+
+    /// Applies arithmetic/logic operation `op` (ADD, ADC, SUB, SBC, AND, XOR,
+    /// OR, CP) to A and `value`.
+    fn alu(&mut self, op: u8, value: u8) {
+        let a = self.a;
+        let carry_in = (op == 1 || op == 3) && self.flag(FLAG_C);
+        let carry = carry_in as u8;
+
+        match op {
+            0 | 1 => {
+                let result = a as u16 + value as u16 + carry as u16;
+                let half = (a & 0x0F) + (value & 0x0F) + carry > 0x0F;
+                self.a = result as u8;
+                self.set_flags(self.a == 0, false, half, result > 0xFF);
+            }
+            2 | 3 | 7 => {
+                let result = a as i16 - value as i16 - carry as i16;
+                let half = ((a & 0x0F) as i8) - ((value & 0x0F) as i8) - (carry as i8) < 0;
+                self.set_flags(result as u8 == 0, true, half, result < 0);
+                if op != 7 {
+                    self.a = result as u8;
+                }
+            }
+            4 => {
+                self.a = a & value;
+                self.set_flags(self.a == 0, false, true, false);
+            }
+            5 => {
+                self.a = a ^ value;
+                self.set_flags(self.a == 0, false, false, false);
+            }
+            _ => {
+                self.a = a | value;
+                self.set_flags(self.a == 0, false, false, false);
+            }
+        }
+    }
+
+    /// Applies rotate/shift operation `op` (RLC, RRC, RL, RR, SLA, SRA, SWAP,
+    /// SRL) to `value`, setting all flags.
+    fn rotate_shift(&mut self, op: u8, value: u8) -> u8 {
+        let carry_in = self.flag(FLAG_C) as u8;
+        let (result, carry) = match op {
+            0 => (value.rotate_left(1), value & 0x80 != 0),
+            1 => (value.rotate_right(1), value & 0x01 != 0),
+            2 => (value << 1 | carry_in, value & 0x80 != 0),
+            3 => (value >> 1 | carry_in << 7, value & 0x01 != 0),
+            4 => (value << 1, value & 0x80 != 0),
+            5 => (value >> 1 | (value & 0x80), value & 0x01 != 0),
+            6 => (value.rotate_left(4), false),
+            _ => (value >> 1, value & 0x01 != 0),
+        };
+        self.set_flags(result == 0, false, false, carry);
+        result
+    }
+
+    /// Adds the signed `offset` to SP for `ADD SP,e8` and `LD HL,SP+e8`,
+    /// which set H and C from the unsigned addition of the low byte.
+    fn add_sp_offset(&mut self, offset: u8) -> u16 {
+        let sp = self.sp;
+        let half = (sp & 0x0F) + (offset as u16 & 0x0F) > 0x0F;
+        let carry = (sp & 0xFF) + offset as u16 > 0xFF;
+        self.set_flags(false, false, half, carry);
+        sp.wrapping_add_signed(offset as i8 as i16)
+    }
+
+    /// Adjusts A to binary-coded decimal after an addition or subtraction.
+    fn daa(&mut self) {
+        let mut adjust = 0;
+        let mut carry = self.flag(FLAG_C);
+
+        if self.flag(FLAG_N) {
+            if self.flag(FLAG_H) {
+                adjust |= 0x06;
+            }
+            if carry {
+                adjust |= 0x60;
+            }
+            self.a = self.a.wrapping_sub(adjust);
+        } else {
+            if self.flag(FLAG_H) || self.a & 0x0F > 0x09 {
+                adjust |= 0x06;
+            }
+            if carry || self.a > 0x99 {
+                adjust |= 0x60;
+                carry = true;
+            }
+            self.a = self.a.wrapping_add(adjust);
+        }
+
+        self.set_flag(FLAG_Z, self.a == 0);
+        self.set_flag(FLAG_H, false);
+        self.set_flag(FLAG_C, carry);
+    }
+}
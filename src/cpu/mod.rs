@@ -0,0 +1,453 @@
+//! The Sharp LR35902 CPU core.
+//!
+//! Opcodes are decoded in `instructions`, following the tables in the "CPU
+//! Instruction Set" chapter of Pan Docs and the opcode reference of gb-ctr.
+//! Cycle counts are in M-cycles (one M-cycle is four clock ticks). Every
+//! M-cycle the CPU spends ticks the [`Bus`] once, so the rest of the hardware
+//! advances in lockstep with each memory access.
+
+mod instructions;
+
+use serde::{Deserialize, Serialize};
+
+use crate::bus::Bus;
+use crate::interrupt::Interrupt;
+
+/// Zero flag, set when a result is 0.
+pub const FLAG_Z: u8 = 0x80;
+/// Subtraction flag, used by `DAA`.
+pub const FLAG_N: u8 = 0x40;
+/// Half-carry flag, the carry out of bit 3.
+pub const FLAG_H: u8 = 0x20;
+/// Carry flag.
+pub const FLAG_C: u8 = 0x10;
+
+/// The CPU registers and execution state.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Cpu {
+    pub a: u8,
+    /// Flags register; the lower nibble always reads as 0.
+    pub f: u8,
+    pub b: u8,
+    pub c: u8,
+    pub d: u8,
+    pub e: u8,
+    pub h: u8,
+    pub l: u8,
+    pub sp: u16,
+    pub pc: u16,
+    /// Interrupt master enable.
+    pub ime: bool,
+    /// Set by `EI`, whose effect is delayed until after the next instruction.
+    pub ime_scheduled: bool,
+    /// Set by `HALT` until an interrupt is pending.
+    pub halted: bool,
+    /// Set by `HALT` when it fails to halt because IME is clear and an
+    /// interrupt is already pending. The next opcode fetch then does not
+    /// advance PC, so the byte after `HALT` is read twice.
+    pub halt_bug: bool,
+    /// Set by an illegal opcode, which hangs the hardware for good.
+    pub locked: bool,
+}
+
+impl Default for Cpu {
+    fn default() -> Cpu {
+        Cpu::new()
+    }
+}
+
+impl Cpu {
+    /// Returns a CPU about to execute the cartridge entry point at `0x0100`,
+    /// with the stack at the top of high RAM.
+    pub fn new() -> Cpu {
+        Cpu {
+            a: 0,
+            f: 0,
+            b: 0,
+            c: 0,
+            d: 0,
+            e: 0,
+            h: 0,
+            l: 0,
+            sp: 0xFFFE,
+            pc: 0x0100,
+            ime: false,
+            ime_scheduled: false,
+            halted: false,
+            halt_bug: false,
+            locked: false,
+        }
+    }
+
+    /// Returns the CPU as the DMG boot ROM leaves it when jumping to the
+    /// cartridge, per the "Power Up Sequence" chapter of Pan Docs. H and C
+    /// are only set when the cartridge header checksum is non-zero.
+    pub fn post_boot(header_checksum: u8) -> Cpu {
+        let flags = if header_checksum == 0 { FLAG_Z } else { FLAG_Z | FLAG_H | FLAG_C };
+        Cpu { a: 0x01, f: flags, b: 0x00, c: 0x13, d: 0x00, e: 0xD8, h: 0x01, l: 0x4D, ..Cpu::new() }
+    }
+
+    /// Returns the CPU as the CGB boot ROM leaves it for a CGB cartridge.
+    /// Games tell the CGB apart from the DMG by A being `0x11`.
+    pub fn post_boot_cgb() -> Cpu {
+        Cpu { a: 0x11, f: FLAG_Z, b: 0x00, c: 0x00, d: 0xFF, e: 0x56, h: 0x00, l: 0x0D, ..Cpu::new() }
+    }
+
+    /// Formats the registers as a line of a Gameboy Doctor log
+    /// (https://github.com/robert/gameboy-doctor), with `pcmem` the four
+    /// bytes at PC:
+    ///
+    /// ```text
+    /// A:01 F:B0 B:00 C:13 D:00 E:D8 H:01 L:4D SP:FFFE PC:0100 PCMEM:00,C3,13,02
+    /// ```
+    pub fn doctor_line(&self, pcmem: [u8; 4]) -> String {
+        format!(
+            "A:{:02X} F:{:02X} B:{:02X} C:{:02X} D:{:02X} E:{:02X} H:{:02X} L:{:02X} SP:{:04X} PC:{:04X} PCMEM:{:02X},{:02X},{:02X},{:02X}",
+            self.a, self.f, self.b, self.c, self.d, self.e, self.h, self.l, self.sp, self.pc, pcmem[0], pcmem[1], pcmem[2], pcmem[3]
+        )
+    }
+
+    /// Services a pending interrupt or else fetches, decodes and executes one
+    /// instruction, returning the M-cycles it took. A halted or locked CPU
+    /// idles for one M-cycle.
+    pub fn step(&mut self, bus: &mut Bus) -> u8 {
+        let start = bus.cycles();
+
+        if self.locked {
+            self.idle(bus);
+        } else if self.service_interrupt(bus) {
+            // The handler starts executing on the next step
+        } else if self.halted {
+            self.idle(bus);
+        } else {
+            // EI takes effect now, after the instruction that followed it, so
+            // an immediate DI still cancels it
+            if self.ime_scheduled {
+                self.ime_scheduled = false;
+                self.ime = true;
+            }
+            let opcode = self.fetch8(bus);
+            self.execute(bus, opcode);
+        }
+
+        (bus.cycles() - start) as u8
+    }
+
+    /// Wakes the CPU from `HALT` when an enabled interrupt is requested and,
+    /// if IME is set, dispatches the highest-priority one: IME and its IF bit
+    /// are cleared, PC is pushed and execution jumps to its vector, taking 5
+    /// M-cycles. Returns whether an interrupt was dispatched.
+    fn service_interrupt(&mut self, bus: &mut Bus) -> bool {
+        let pending = bus.pending_interrupts();
+        if pending == 0 {
+            return false;
+        }
+
+        // A pending interrupt ends HALT even when IME is clear
+        self.halted = false;
+        if !self.ime {
+            return false;
+        }
+
+        self.ime = false;
+        self.idle(bus);
+        self.idle(bus);
+        self.push16(bus, self.pc);
+        let interrupt = Interrupt::highest_priority(pending).expect("pending interrupt");
+        bus.acknowledge_interrupt(interrupt);
+        self.pc = interrupt.vector();
+        self.idle(bus);
+        true
+    }
+
+    pub fn af(&self) -> u16 {
+        u16::from_be_bytes([self.a, self.f])
+    }
+
+    pub fn bc(&self) -> u16 {
+        u16::from_be_bytes([self.b, self.c])
+    }
+
+    pub fn de(&self) -> u16 {
+        u16::from_be_bytes([self.d, self.e])
+    }
+
+    pub fn hl(&self) -> u16 {
+        u16::from_be_bytes([self.h, self.l])
+    }
+
+    pub fn set_af(&mut self, value: u16) {
+        let [a, f] = value.to_be_bytes();
+        self.a = a;
+        self.f = f & 0xF0;
+    }
+
+    pub fn set_bc(&mut self, value: u16) {
+        [self.b, self.c] = value.to_be_bytes();
+    }
+
+    pub fn set_de(&mut self, value: u16) {
+        [self.d, self.e] = value.to_be_bytes();
+    }
+
+    pub fn set_hl(&mut self, value: u16) {
+        [self.h, self.l] = value.to_be_bytes();
+    }
+
+    /// Returns `true` if every bit of `mask` (e.g. [`FLAG_Z`]) is set.
+    pub fn flag(&self, mask: u8) -> bool {
+        self.f & mask == mask
+    }
+
+    pub fn set_flag(&mut self, mask: u8, on: bool) {
+        if on {
+            self.f |= mask;
+        } else {
+            self.f &= !mask;
+        }
+    }
+
+    /// Replaces all four flags at once.
+    fn set_flags(&mut self, z: bool, n: bool, h: bool, c: bool) {
+        self.f = (z as u8) << 7 | (n as u8) << 6 | (h as u8) << 5 | (c as u8) << 4;
+    }
+
+    /// Reads `addr` during one M-cycle.
+    fn read8(&mut self, bus: &mut Bus, addr: u16) -> u8 {
+        bus.tick();
+        let value = bus.cpu_read(addr);
+        bus.watch(addr, value, false);
+        value
+    }
+
+    /// Writes `addr` during one M-cycle.
+    fn write8(&mut self, bus: &mut Bus, addr: u16, value: u8) {
+        bus.tick();
+        bus.watch(addr, value, true);
+        bus.cpu_write(addr, value);
+    }
+
+    /// Spends one M-cycle without accessing memory.
+    fn idle(&mut self, bus: &mut Bus) {
+        bus.tick();
+    }
+
+    fn fetch8(&mut self, bus: &mut Bus) -> u8 {
+        let value = self.read8(bus, self.pc);
+        if self.halt_bug {
+            self.halt_bug = false;
+        } else {
+            self.pc = self.pc.wrapping_add(1);
+        }
+        value
+    }
+
+    fn fetch16(&mut self, bus: &mut Bus) -> u16 {
+        let low = self.fetch8(bus);
+        let high = self.fetch8(bus);
+        u16::from_le_bytes([low, high])
+    }
+
+    fn push16(&mut self, bus: &mut Bus, value: u16) {
+        let [high, low] = value.to_be_bytes();
+        self.sp = self.sp.wrapping_sub(1);
+        self.write8(bus, self.sp, high);
+        self.sp = self.sp.wrapping_sub(1);
+        self.write8(bus, self.sp, low);
+    }
+
+    fn pop16(&mut self, bus: &mut Bus) -> u16 {
+        let low = self.read8(bus, self.sp);
+        self.sp = self.sp.wrapping_add(1);
+        let high = self.read8(bus, self.sp);
+        self.sp = self.sp.wrapping_add(1);
+        u16::from_le_bytes([low, high])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{PROGRAM_START, program_bus};
+
+    /// Returns a CPU about to run `program` and the bus holding it.
+    fn setup(program: &[u8]) -> (Cpu, Bus) {
+        (Cpu { pc: PROGRAM_START, ..Cpu::new() }, program_bus(program))
+    }
+
+    #[test]
+    fn runs_a_small_program() {
+        #[rustfmt::skip]
+        let program = [
+            0x3E, 0x3C,       // LD A,$3C
+            0x06, 0xC6,       // LD B,$C6
+            0x80,             // ADD A,B
+            0x4F,             // LD C,A
+            0x11, 0x34, 0x12, // LD DE,$1234
+            0x21, 0x00, 0xC0, // LD HL,$C000
+            0x36, 0x81,       // LD (HL),$81
+            0xCB, 0x36,       // SWAP (HL)
+            0xCB, 0x02,       // RLC D
+            0x18, 0x02,       // JR +2
+            0x3E, 0xFF,       // LD A,$FF (skipped)
+            0x7E,             // LD A,(HL)
+            0x31, 0xF0, 0xDF, // LD SP,$DFF0
+            0xD5,             // PUSH DE
+            0xE1,             // POP HL
+            0xCB, 0x7F,       // BIT 7,A
+        ];
+        let (mut cpu, mut bus) = setup(&program);
+
+        for _ in 0..3 {
+            cpu.step(&mut bus);
+        }
+        // 0x3C + 0xC6 carries out of both bit 3 and bit 7
+        assert_eq!((cpu.a, cpu.f), (0x02, FLAG_H | FLAG_C));
+
+        for _ in 3..15 {
+            cpu.step(&mut bus);
+        }
+        assert_eq!(cpu.a, 0x18);
+        assert_eq!(cpu.f, FLAG_Z | FLAG_H);
+        assert_eq!(cpu.bc(), 0xC602);
+        assert_eq!(cpu.de(), 0x2434);
+        assert_eq!(cpu.hl(), 0x2434);
+        assert_eq!(cpu.sp, 0xDFF0);
+        assert_eq!(cpu.pc, PROGRAM_START + program.len() as u16);
+        assert_eq!(bus.read(0xC000), 0x18);
+    }
+
+    #[test]
+    fn branch_cycle_counts_match_gb_ctr() {
+        let mut program = vec![0x00; 0x30];
+        #[rustfmt::skip]
+        let main = [
+            0xAF,             // 0150: XOR A (sets Z)
+            0x20, 0x00,       // 0151: JR NZ,+0
+            0x28, 0x00,       // 0153: JR Z,+0
+            0xC2, 0x00, 0x00, // 0155: JP NZ,$0000
+            0xCA, 0x5B, 0x01, // 0158: JP Z,$015B
+            0xC4, 0x00, 0x00, // 015B: CALL NZ,$0000
+            0xCC, 0x70, 0x01, // 015E: CALL Z,$0170
+            0xCD, 0x72, 0x01, // 0161: CALL $0172
+            0x00,             // 0164: NOP
+        ];
+        program[..main.len()].copy_from_slice(&main);
+        // 0170: RET NZ; RET Z; 0172: RET
+        program[0x20..0x23].copy_from_slice(&[0xC0, 0xC8, 0xC9]);
+        let (mut cpu, mut bus) = setup(&program);
+
+        // M-cycles from the gb-ctr opcode reference, taken or not taken
+        let expected = [1, 2, 3, 3, 4, 3, 6, 2, 5, 6, 4, 1];
+        let cycles: Vec<u8> = expected.iter().map(|_| cpu.step(&mut bus)).collect();
+        assert_eq!(cycles, expected);
+        assert_eq!(cycles.iter().map(|&cycles| cycles as u32).sum::<u32>(), 40);
+        assert_eq!(cpu.pc, 0x0165);
+        assert_eq!(cpu.sp, 0xFFFE);
+    }
+
+    #[test]
+    fn dispatches_an_interrupt_in_5_cycles() {
+        let (mut cpu, mut bus) = setup(&[0x00]);
+        cpu.ime = true;
+        bus.write(0xFFFF, Interrupt::VBlank.bit() | Interrupt::Timer.bit());
+        bus.write(0xFF0F, Interrupt::VBlank.bit() | Interrupt::Timer.bit());
+
+        assert_eq!(cpu.step(&mut bus), 5);
+        assert_eq!(cpu.pc, 0x0040);
+        assert!(!cpu.ime);
+        assert_eq!(cpu.sp, 0xFFFC);
+        assert_eq!((bus.read(0xFFFC), bus.read(0xFFFD)), (0x50, 0x01));
+        // Only the serviced interrupt is acknowledged
+        assert_eq!(bus.read(0xFF0F) & 0x1F, Interrupt::Timer.bit());
+    }
+
+    #[test]
+    fn does_not_dispatch_with_ime_clear() {
+        let (mut cpu, mut bus) = setup(&[0x00]);
+        bus.write(0xFFFF, Interrupt::VBlank.bit());
+        bus.write(0xFF0F, Interrupt::VBlank.bit());
+
+        assert_eq!(cpu.step(&mut bus), 1);
+        assert_eq!(cpu.pc, PROGRAM_START + 1);
+        assert_eq!(bus.read(0xFF0F) & 0x1F, Interrupt::VBlank.bit());
+    }
+
+    /// Returns a CPU about to run `program` with IME clear and the timer
+    /// interrupt enabled, requested if `pending`.
+    fn setup_halt(program: &[u8], pending: bool) -> (Cpu, Bus) {
+        let (cpu, mut bus) = setup(program);
+        bus.write(0xFFFF, Interrupt::Timer.bit());
+        bus.write(0xFF0F, if pending { Interrupt::Timer.bit() } else { 0 });
+        (cpu, bus)
+    }
+
+    #[test]
+    fn halt_bug_executes_the_next_byte_twice() {
+        // HALT; INC B; NOP
+        let (mut cpu, mut bus) = setup_halt(&[0x76, 0x04, 0x00], true);
+
+        cpu.step(&mut bus);
+        assert!(!cpu.halted);
+        assert_eq!(cpu.pc, PROGRAM_START + 1);
+        cpu.step(&mut bus);
+        assert_eq!(cpu.pc, PROGRAM_START + 1);
+        cpu.step(&mut bus);
+        assert_eq!((cpu.b, cpu.pc), (2, PROGRAM_START + 2));
+    }
+
+    #[test]
+    fn halt_bug_reads_an_opcode_as_its_own_operand() {
+        // HALT; LD A,0x14, which runs as LD A,0x3E then INC D
+        let (mut cpu, mut bus) = setup_halt(&[0x76, 0x3E, 0x14, 0x00], true);
+        for _ in 0..3 {
+            cpu.step(&mut bus);
+        }
+
+        assert_eq!((cpu.a, cpu.d, cpu.pc), (0x3E, 1, PROGRAM_START + 3));
+    }
+
+    #[test]
+    fn halt_without_a_pending_interrupt_halts() {
+        let (mut cpu, mut bus) = setup_halt(&[0x76, 0x04, 0x00], false);
+        cpu.step(&mut bus);
+        assert!(cpu.halted);
+        assert_eq!(cpu.step(&mut bus), 1);
+        assert_eq!(cpu.pc, PROGRAM_START + 1);
+
+        // A pending interrupt wakes the CPU without servicing it
+        bus.write(0xFF0F, Interrupt::Timer.bit());
+        cpu.step(&mut bus);
+        assert!(!cpu.halted);
+        assert_eq!((cpu.b, cpu.pc), (1, PROGRAM_START + 2));
+    }
+
+    #[test]
+    fn halt_with_ime_set_services_the_interrupt() {
+        let (mut cpu, mut bus) = setup_halt(&[0x76, 0x04, 0x00], false);
+        cpu.ime = true;
+        cpu.step(&mut bus);
+        assert!(cpu.halted);
+
+        bus.write(0xFF0F, Interrupt::Timer.bit());
+        cpu.step(&mut bus);
+        assert_eq!(cpu.pc, 0x0050);
+        assert_eq!((bus.read(0xFFFC), bus.read(0xFFFD)), (0x51, 0x01));
+        assert!(!cpu.halt_bug);
+    }
+
+    #[test]
+    fn formats_a_gameboy_doctor_line() {
+        let cpu = Cpu::post_boot(0x33);
+
+        assert_eq!(
+            cpu.doctor_line([0x00, 0xC3, 0x13, 0x02]),
+            "A:01 F:B0 B:00 C:13 D:00 E:D8 H:01 L:4D SP:FFFE PC:0100 PCMEM:00,C3,13,02"
+        );
+        let cpu = Cpu { a: 0xAB, f: 0x00, h: 0xC0, sp: 0xDFF0, pc: 0x0A0B, ..Cpu::new() };
+        assert_eq!(
+            cpu.doctor_line([0xFF, 0x0E, 0x00, 0x7F]),
+            "A:AB F:00 B:00 C:00 D:00 E:00 H:C0 L:00 SP:DFF0 PC:0A0B PCMEM:FF,0E,00,7F"
+        );
+    }
+}
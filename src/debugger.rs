@@ -0,0 +1,218 @@
+//! A debugging layer over [`Emulator`]: breakpoints, memory watchpoints and
+//! single-stepping.
+
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::emulator::Emulator;
+
+/// The CPU accesses a watchpoint triggers on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Watch {
+    Read,
+    Write,
+    ReadWrite,
+}
+
+impl Watch {
+    pub(crate) fn matches(self, write: bool) -> bool {
+        match self {
+            Watch::Read => !write,
+            Watch::Write => write,
+            Watch::ReadWrite => true,
+        }
+    }
+}
+
+/// A CPU access that triggered a watchpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WatchHit {
+    pub addr: u16,
+    /// The byte read, or the byte being written.
+    pub value: u8,
+    pub write: bool,
+}
+
+/// Why execution stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stop {
+    /// PC reached a breakpoint; holds the address.
+    Breakpoint(u16),
+    /// The last instruction accessed a watched address.
+    Watchpoint(WatchHit),
+    /// The flag returned by [`Debugger::interrupt_flag`] was set.
+    Interrupted,
+}
+
+/// Runs an [`Emulator`] under control of breakpoints and watchpoints.
+pub struct Debugger {
+    emulator: Emulator,
+    breakpoints: BTreeSet<u16>,
+    interrupted: Arc<AtomicBool>,
+}
+
+impl Debugger {
+    pub fn new(emulator: Emulator) -> Debugger {
+        Debugger { emulator, breakpoints: BTreeSet::new(), interrupted: Arc::new(AtomicBool::new(false)) }
+    }
+
+    pub fn emulator(&self) -> &Emulator {
+        &self.emulator
+    }
+
+    pub fn emulator_mut(&mut self) -> &mut Emulator {
+        &mut self.emulator
+    }
+
+    pub fn into_emulator(self) -> Emulator {
+        self.emulator
+    }
+
+    /// Returns a flag that stops [`continue_until_break`] when set, e.g.
+    /// from a Ctrl+C handler.
+    ///
+    /// [`continue_until_break`]: Debugger::continue_until_break
+    pub fn interrupt_flag(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.interrupted)
+    }
+
+    /// Adds a breakpoint at `addr`. Returns `false` if there already was one.
+    pub fn add_breakpoint(&mut self, addr: u16) -> bool {
+        self.breakpoints.insert(addr)
+    }
+
+    /// Removes the breakpoint at `addr`. Returns `false` if there was none.
+    pub fn remove_breakpoint(&mut self, addr: u16) -> bool {
+        self.breakpoints.remove(&addr)
+    }
+
+    /// Returns the breakpoint addresses in ascending order.
+    pub fn breakpoints(&self) -> impl Iterator<Item = u16> + '_ {
+        self.breakpoints.iter().copied()
+    }
+
+    /// Stops execution after any instruction that accesses `addr` as
+    /// `watch` selects, replacing an existing watchpoint at `addr`.
+    pub fn add_watchpoint(&mut self, addr: u16, watch: Watch) {
+        self.emulator.bus_mut().watchpoints_mut().insert(addr, watch);
+    }
+
+    /// Removes the watchpoint at `addr`. Returns `false` if there was none.
+    pub fn remove_watchpoint(&mut self, addr: u16) -> bool {
+        self.emulator.bus_mut().watchpoints_mut().remove(&addr).is_some()
+    }
+
+    /// Returns the watchpoints in ascending address order.
+    pub fn watchpoints(&self) -> impl Iterator<Item = (u16, Watch)> + '_ {
+        self.emulator.bus().watchpoints().iter().map(|(&addr, &watch)| (addr, watch))
+    }
+
+    /// Executes one instruction, or services one interrupt. Returns the
+    /// watchpoint it triggered, if any.
+    pub fn step(&mut self) -> Option<Stop> {
+        self.emulator.bus_mut().take_watch_hit();
+        self.emulator.step();
+        self.emulator.bus_mut().take_watch_hit().map(Stop::Watchpoint)
+    }
+
+    /// Runs until PC reaches a breakpoint, a watchpoint triggers or the
+    /// interrupt flag is set. At least one instruction is executed, so
+    /// continuing from a breakpoint moves past it.
+    ///
+    /// Breakpoints are not checked while the CPU is halted, since it is not
+    /// executing the instruction at PC.
+    pub fn continue_until_break(&mut self) -> Stop {
+        self.interrupted.store(false, Ordering::Relaxed);
+        loop {
+            if let Some(stop) = self.step() {
+                return stop;
+            }
+            let cpu = self.emulator.cpu();
+            if !cpu.halted && self.breakpoints.contains(&cpu.pc) {
+                return Stop::Breakpoint(cpu.pc);
+            }
+            if self.interrupted.load(Ordering::Relaxed) {
+                return Stop::Interrupted;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::program_rom;
+
+    /// Increments `0xC000` forever.
+    const COUNTER: [u8; 6] = [
+        0x21, 0x00, 0xC0, // 0x0150: LD HL,0xC000
+        0x34, // 0x0153: INC (HL)
+        0x18, 0xFD, // 0x0154: JR 0x0153
+    ];
+
+    fn debugger() -> Debugger {
+        Debugger::new(Emulator::new(program_rom(&COUNTER)).unwrap())
+    }
+
+    #[test]
+    fn stops_at_a_breakpoint() {
+        let mut debugger = debugger();
+        debugger.add_breakpoint(0x0153);
+
+        assert_eq!(debugger.continue_until_break(), Stop::Breakpoint(0x0153));
+        assert_eq!(debugger.emulator().cpu().pc, 0x0153);
+        assert_eq!(debugger.emulator().read_memory(0xC000), 0x00);
+    }
+
+    #[test]
+    fn continuing_moves_past_the_breakpoint() {
+        let mut debugger = debugger();
+        debugger.add_breakpoint(0x0153);
+        debugger.continue_until_break();
+
+        // Once around the loop and back to the same breakpoint
+        assert_eq!(debugger.continue_until_break(), Stop::Breakpoint(0x0153));
+        assert_eq!(debugger.emulator().read_memory(0xC000), 0x01);
+
+        assert!(debugger.remove_breakpoint(0x0153));
+        debugger.add_breakpoint(0x0154);
+        assert_eq!(debugger.continue_until_break(), Stop::Breakpoint(0x0154));
+        assert_eq!(debugger.emulator().read_memory(0xC000), 0x02);
+    }
+
+    #[test]
+    fn write_watchpoint_stops_after_the_write() {
+        let mut debugger = debugger();
+        debugger.add_watchpoint(0xC000, Watch::Write);
+
+        let hit = WatchHit { addr: 0xC000, value: 0x01, write: true };
+        assert_eq!(debugger.continue_until_break(), Stop::Watchpoint(hit));
+        assert_eq!(debugger.emulator().cpu().pc, 0x0154);
+    }
+
+    #[test]
+    fn read_watchpoint_reports_the_value_read() {
+        let mut debugger = debugger();
+        debugger.add_watchpoint(0xC000, Watch::Read);
+        debugger.continue_until_break();
+
+        let hit = WatchHit { addr: 0xC000, value: 0x01, write: false };
+        assert_eq!(debugger.continue_until_break(), Stop::Watchpoint(hit));
+        assert!(debugger.remove_watchpoint(0xC000));
+        assert!(!debugger.remove_watchpoint(0xC000));
+    }
+
+    #[test]
+    fn step_reports_watchpoints() {
+        let mut debugger = debugger();
+        debugger.add_watchpoint(0xC000, Watch::ReadWrite);
+
+        // NOP, JP 0x0150, LD HL,0xC000
+        for _ in 0..3 {
+            assert_eq!(debugger.step(), None);
+        }
+        let hit = WatchHit { addr: 0xC000, value: 0x00, write: false };
+        assert_eq!(debugger.step(), Some(Stop::Watchpoint(hit)));
+    }
+}
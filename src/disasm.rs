@@ -0,0 +1,178 @@
+//! A disassembler for the LR35902 instruction set.
+//!
+//! Mnemonics follow the opcode tables of Pan Docs: register operands in the
+//! order `B, C, D, E, H, L, (HL), A`, immediates in hexadecimal with a `$`
+//! prefix and relative jumps resolved to their target address.
+
+const REGISTERS: [&str; 8] = ["B", "C", "D", "E", "H", "L", "(HL)", "A"];
+const PAIRS: [&str; 4] = ["BC", "DE", "HL", "SP"];
+/// Register pairs of `PUSH` and `POP`.
+const STACK_PAIRS: [&str; 4] = ["BC", "DE", "HL", "AF"];
+const CONDITIONS: [&str; 4] = ["NZ", "Z", "NC", "C"];
+const ALU_OPERATIONS: [&str; 8] = ["ADD A,", "ADC A,", "SUB ", "SBC A,", "AND ", "XOR ", "OR ", "CP "];
+const ROTATE_SHIFT_OPERATIONS: [&str; 8] = ["RLC", "RRC", "RL", "RR", "SLA", "SRA", "SWAP", "SRL"];
+const ACCUMULATOR_OPERATIONS: [&str; 8] = ["RLCA", "RRCA", "RLA", "RRA", "DAA", "CPL", "SCF", "CCF"];
+
+/// Decodes the instruction at the start of `bytes`, located at `addr`.
+/// Returns its text and length in bytes.
+///
+/// Illegal opcodes, and instructions cut short by the end of `bytes`, come
+/// out as a one-byte `DB $xx` directive. An empty slice returns length 0.
+pub fn disassemble(bytes: &[u8], addr: u16) -> (String, usize) {
+    let Some(&opcode) = bytes.first() else {
+        return (String::new(), 0);
+    };
+    let length = instruction_length(opcode);
+    if bytes.len() < length {
+        return (format!("DB ${:02X}", opcode), 1);
+    }
+
+    let d8 = bytes.get(1).copied().unwrap_or(0);
+    let d16 = u16::from_le_bytes([d8, bytes.get(2).copied().unwrap_or(0)]);
+    let relative = addr.wrapping_add(2).wrapping_add_signed(d8 as i8 as i16);
+
+    let y = ((opcode >> 3) & 0x07) as usize;
+    let z = (opcode & 0x07) as usize;
+    let pair = ((opcode >> 4) & 0x03) as usize;
+
+    let text = match opcode {
+        0x00 => "NOP".to_string(),
+        0x08 => format!("LD (${:04X}),SP", d16),
+        0x10 => "STOP".to_string(),
+        0x18 => format!("JR ${:04X}", relative),
+        0x20 | 0x28 | 0x30 | 0x38 => format!("JR {},${:04X}", CONDITIONS[y - 4], relative),
+        0x01 | 0x11 | 0x21 | 0x31 => format!("LD {},${:04X}", PAIRS[pair], d16),
+        0x09 | 0x19 | 0x29 | 0x39 => format!("ADD HL,{}", PAIRS[pair]),
+        0x02 | 0x12 => format!("LD ({}),A", PAIRS[pair]),
+        0x22 => "LD (HL+),A".to_string(),
+        0x32 => "LD (HL-),A".to_string(),
+        0x0A | 0x1A => format!("LD A,({})", PAIRS[pair]),
+        0x2A => "LD A,(HL+)".to_string(),
+        0x3A => "LD A,(HL-)".to_string(),
+        0x03 | 0x13 | 0x23 | 0x33 => format!("INC {}", PAIRS[pair]),
+        0x0B | 0x1B | 0x2B | 0x3B => format!("DEC {}", PAIRS[pair]),
+        0x04 | 0x0C | 0x14 | 0x1C | 0x24 | 0x2C | 0x34 | 0x3C => format!("INC {}", REGISTERS[y]),
+        0x05 | 0x0D | 0x15 | 0x1D | 0x25 | 0x2D | 0x35 | 0x3D => format!("DEC {}", REGISTERS[y]),
+        0x06 | 0x0E | 0x16 | 0x1E | 0x26 | 0x2E | 0x36 | 0x3E => format!("LD {},${:02X}", REGISTERS[y], d8),
+        0x07 | 0x0F | 0x17 | 0x1F | 0x27 | 0x2F | 0x37 | 0x3F => ACCUMULATOR_OPERATIONS[y].to_string(),
+        0x76 => "HALT".to_string(),
+        0x40..=0x7F => format!("LD {},{}", REGISTERS[y], REGISTERS[z]),
+        0x80..=0xBF => format!("{}{}", ALU_OPERATIONS[y], REGISTERS[z]),
+        0xC0 | 0xC8 | 0xD0 | 0xD8 => format!("RET {}", CONDITIONS[y]),
+        0xC9 => "RET".to_string(),
+        0xD9 => "RETI".to_string(),
+        0xE0 => format!("LDH ($FF{:02X}),A", d8),
+        0xF0 => format!("LDH A,($FF{:02X})", d8),
+        0xE8 => format!("ADD SP,{}", d8 as i8),
+        0xF8 => format!("LD HL,SP{:+}", d8 as i8),
+        0xC1 | 0xD1 | 0xE1 | 0xF1 => format!("POP {}", STACK_PAIRS[pair]),
+        0xC5 | 0xD5 | 0xE5 | 0xF5 => format!("PUSH {}", STACK_PAIRS[pair]),
+        0xE9 => "JP HL".to_string(),
+        0xF9 => "LD SP,HL".to_string(),
+        0xC2 | 0xCA | 0xD2 | 0xDA => format!("JP {},${:04X}", CONDITIONS[y], d16),
+        0xC3 => format!("JP ${:04X}", d16),
+        0xE2 => "LD ($FF00+C),A".to_string(),
+        0xF2 => "LD A,($FF00+C)".to_string(),
+        0xEA => format!("LD (${:04X}),A", d16),
+        0xFA => format!("LD A,(${:04X})", d16),
+        0xCB => disassemble_cb(d8),
+        0xF3 => "DI".to_string(),
+        0xFB => "EI".to_string(),
+        0xC4 | 0xCC | 0xD4 | 0xDC => format!("CALL {},${:04X}", CONDITIONS[y], d16),
+        0xCD => format!("CALL ${:04X}", d16),
+        0xC6 | 0xCE | 0xD6 | 0xDE | 0xE6 | 0xEE | 0xF6 | 0xFE => format!("{}${:02X}", ALU_OPERATIONS[y], d8),
+        0xC7 | 0xCF | 0xD7 | 0xDF | 0xE7 | 0xEF | 0xF7 | 0xFF => format!("RST ${:02X}", y * 8),
+        // 0xD3, 0xDB, 0xDD, 0xE3, 0xE4, 0xEB, 0xEC, 0xED, 0xF4, 0xFC and 0xFD
+        _ => format!("DB ${:02X}", opcode),
+    };
+    (text, length)
+}
+
+/// Decodes the `CB`-prefixed opcode `opcode`.
+fn disassemble_cb(opcode: u8) -> String {
+    let y = ((opcode >> 3) & 0x07) as usize;
+    let register = REGISTERS[(opcode & 0x07) as usize];
+    match opcode >> 6 {
+        0 => format!("{} {}", ROTATE_SHIFT_OPERATIONS[y], register),
+        1 => format!("BIT {},{}", y, register),
+        2 => format!("RES {},{}", y, register),
+        _ => format!("SET {},{}", y, register),
+    }
+}
+
+/// Returns the length in bytes of the instruction starting with `opcode`,
+/// counting illegal opcodes as one byte.
+fn instruction_length(opcode: u8) -> usize {
+    match opcode {
+        0x01 | 0x11 | 0x21 | 0x31 | 0x08 | 0xC2 | 0xC3 | 0xCA | 0xD2 | 0xDA | 0xEA | 0xFA => 3,
+        0xC4 | 0xCC | 0xCD | 0xD4 | 0xDC => 3,
+        0x06 | 0x0E | 0x16 | 0x1E | 0x26 | 0x2E | 0x36 | 0x3E => 2,
+        0x10 | 0x18 | 0x20 | 0x28 | 0x30 | 0x38 | 0xCB | 0xE0 | 0xE8 | 0xF0 | 0xF8 => 2,
+        0xC6 | 0xCE | 0xD6 | 0xDE | 0xE6 | 0xEE | 0xF6 | 0xFE => 2,
+        _ => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Asserts that `bytes`, located at `addr`, decode to a single
+    /// instruction reading `expected`.
+    fn assert_decodes(bytes: &[u8], addr: u16, expected: &str) {
+        assert_eq!(disassemble(bytes, addr), (expected.to_string(), bytes.len()));
+    }
+
+    #[test]
+    fn relative_jumps_resolve_their_target() {
+        assert_decodes(&[0x18, 0xFE], 0x0150, "JR $0150");
+        assert_decodes(&[0x20, 0x05], 0x0150, "JR NZ,$0157");
+        assert_decodes(&[0x38, 0x80], 0x0150, "JR C,$00D2");
+        // The target wraps around the address space
+        assert_decodes(&[0x18, 0x7F], 0xFFF0, "JR $0071");
+    }
+
+    #[test]
+    fn signed_stack_offsets() {
+        assert_decodes(&[0xF8, 0x05], 0, "LD HL,SP+5");
+        assert_decodes(&[0xF8, 0xFB], 0, "LD HL,SP-5");
+        assert_decodes(&[0xF8, 0x00], 0, "LD HL,SP+0");
+        assert_decodes(&[0xE8, 0x80], 0, "ADD SP,-128");
+    }
+
+    #[test]
+    fn cb_prefixed_operations() {
+        assert_decodes(&[0xCB, 0x11], 0, "RL C");
+        assert_decodes(&[0xCB, 0x37], 0, "SWAP A");
+        assert_decodes(&[0xCB, 0x7C], 0, "BIT 7,H");
+        assert_decodes(&[0xCB, 0x86], 0, "RES 0,(HL)");
+        assert_decodes(&[0xCB, 0xFF], 0, "SET 7,A");
+    }
+
+    #[test]
+    fn immediates_and_registers() {
+        assert_decodes(&[0x31, 0xFE, 0xFF], 0, "LD SP,$FFFE");
+        assert_decodes(&[0xE0, 0x40], 0, "LDH ($FF40),A");
+        assert_decodes(&[0xCD, 0x34, 0x12], 0, "CALL $1234");
+        assert_decodes(&[0xC5], 0, "PUSH BC");
+        assert_decodes(&[0xF1], 0, "POP AF");
+        assert_decodes(&[0x76], 0, "HALT");
+        assert_decodes(&[0x7E], 0, "LD A,(HL)");
+        assert_decodes(&[0xDF], 0, "RST $18");
+    }
+
+    #[test]
+    fn illegal_opcodes_are_data() {
+        for opcode in [0xD3, 0xDB, 0xDD, 0xE3, 0xE4, 0xEB, 0xEC, 0xED, 0xF4, 0xFC, 0xFD] {
+            assert_eq!(disassemble(&[opcode, 0x00], 0), (format!("DB ${:02X}", opcode), 1));
+        }
+    }
+
+    #[test]
+    fn truncated_instructions_are_data() {
+        assert_eq!(disassemble(&[0xC3, 0x50], 0), ("DB $C3".to_string(), 1));
+        assert_decodes(&[0xCB], 0, "DB $CB");
+        assert_decodes(&[0x3E], 0, "DB $3E");
+        assert_decodes(&[], 0, "");
+    }
+}
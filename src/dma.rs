@@ -0,0 +1,164 @@
+//! OAM DMA (`0xFF46`), following the "OAM DMA Transfer" chapter of Pan Docs.
+//!
+//! Writing the DMA register copies 160 bytes from `XX00..=XX9F` to OAM, one
+//! byte per M-cycle after a one-cycle startup delay. While the copy runs the
+//! CPU cannot reach the buses the transfer uses, so games wait for it in a
+//! routine running from HRAM.
+
+use serde::{Deserialize, Serialize};
+
+/// Bytes copied by a transfer: the whole of OAM.
+const TRANSFER_LENGTH: u8 = 160;
+
+/// M-cycles between the write to DMA and the first byte being copied.
+const STARTUP_CYCLES: u8 = 1;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OamDma {
+    /// The value last written to the DMA register, read back as is.
+    register: u8,
+    /// Address of the first byte to copy.
+    source: u16,
+    /// M-cycles since the transfer was started, or `None` when idle.
+    elapsed: Option<u8>,
+}
+
+impl OamDma {
+    pub fn new() -> OamDma {
+        OamDma::default()
+    }
+
+    pub fn read(&self) -> u8 {
+        self.register
+    }
+
+    /// Starts a transfer from `value * 0x100`, replacing a running one.
+    pub fn write(&mut self, value: u8) {
+        self.register = value;
+        // Sources above work RAM read the echo area instead
+        self.source = match (value as u16) << 8 {
+            source @ 0xFE00.. => source - 0x2000,
+            source => source,
+        };
+        self.elapsed = Some(0);
+    }
+
+    /// Whether bytes are being copied, which blocks most CPU accesses.
+    pub fn is_active(&self) -> bool {
+        self.elapsed.is_some_and(|elapsed| elapsed > STARTUP_CYCLES)
+    }
+
+    /// Advances the transfer by one M-cycle. Returns the source address and
+    /// OAM offset of the byte to copy during this cycle, if any.
+    pub fn tick(&mut self) -> Option<(u16, u8)> {
+        let elapsed = self.elapsed? + 1;
+        if elapsed > STARTUP_CYCLES + TRANSFER_LENGTH {
+            self.elapsed = None;
+            return None;
+        }
+        self.elapsed = Some(elapsed);
+        let index = elapsed.checked_sub(STARTUP_CYCLES + 1)?;
+        Some((self.source + index as u16, index))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bus::Bus;
+    use crate::cpu::Cpu;
+    use crate::testing::{PROGRAM_START, program_bus};
+
+    /// Returns a bus with `0xC000..=0xC09F` holding 1, 2, 3, ... and an
+    /// OAM DMA from there just started.
+    fn transferring_bus() -> Bus {
+        let mut bus = program_bus(&[]);
+        for offset in 0..160 {
+            bus.write(0xC000 + offset, offset as u8 + 1);
+        }
+        bus.write(0xFF46, 0xC0);
+        bus
+    }
+
+    fn oam(bus: &Bus) -> Vec<u8> {
+        (0xFE00..=0xFE9F).map(|addr| bus.read(addr)).collect()
+    }
+
+    #[test]
+    fn copies_one_byte_per_cycle_after_a_startup_delay() {
+        let mut bus = transferring_bus();
+        let source: Vec<u8> = (1..=160).collect();
+
+        bus.tick();
+        assert_eq!(oam(&bus), [0; 160]);
+        for _ in 0..80 {
+            bus.tick();
+        }
+        let mut partial = source[..80].to_vec();
+        partial.resize(160, 0);
+        assert_eq!(oam(&bus), partial);
+
+        for _ in 0..80 {
+            bus.tick();
+        }
+        assert_eq!(oam(&bus), source);
+        assert_eq!(bus.read(0xFF46), 0xC0);
+    }
+
+    #[test]
+    fn cpu_only_reaches_hram_and_io_during_the_transfer() {
+        let mut bus = transferring_bus();
+        bus.write(0xFF80, 0x42);
+        // The startup cycle leaves the buses free
+        assert_eq!(bus.cpu_read(0xC000), 0x01);
+        bus.tick();
+        bus.tick();
+
+        for addr in [0x0150, 0x8000, 0xC000, 0xFE00] {
+            assert_eq!(bus.cpu_read(addr), 0xFF, "{:#06X}", addr);
+        }
+        assert_eq!(bus.cpu_read(0xFF80), 0x42);
+        assert_eq!(bus.cpu_read(0xFF46), 0xC0);
+        bus.cpu_write(0xC0A0, 0x99);
+        bus.cpu_write(0xFF81, 0x99);
+        assert_eq!(bus.read(0xC0A0), 0x00);
+        assert_eq!(bus.read(0xFF81), 0x99);
+
+        // The last byte is copied on cycle 161, and the buses free up after
+        for _ in 0..159 {
+            bus.tick();
+        }
+        assert_eq!(bus.cpu_read(0xC000), 0xFF);
+        bus.tick();
+        assert_eq!(bus.cpu_read(0xC000), 0x01);
+    }
+
+    #[test]
+    fn sources_past_work_ram_read_the_echo_area() {
+        let mut bus = program_bus(&[]);
+        bus.write(0xDE00, 0x77);
+        bus.write(0xFF46, 0xFE);
+        bus.tick();
+        bus.tick();
+
+        assert_eq!(bus.read(0xFE00), 0x77);
+    }
+
+    #[test]
+    fn cpu_fetches_0xff_while_running_from_rom() {
+        #[rustfmt::skip]
+        let program = [
+            0x3E, 0xC0, // LD A,0xC0
+            0xE0, 0x46, // LDH (DMA),A
+            0x00, // NOP, fetched during the startup cycle
+            0x00, // Read as 0xFF: RST 0x38
+        ];
+        let mut bus = program_bus(&program);
+        let mut cpu = Cpu { pc: PROGRAM_START, ..Cpu::new() };
+        for _ in 0..4 {
+            cpu.step(&mut bus);
+        }
+
+        assert_eq!(cpu.pc, 0x0038);
+        assert_eq!(cpu.sp, 0xFFFC);
+    }
+}
@@ -0,0 +1,692 @@
+//! The whole machine: the CPU and everything behind its bus.
+
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use crate::bus::{BOOT_ROM_SIZE, Bus, Region};
+use crate::cartridge::header::HeaderError;
+use crate::cartridge::{Cartridge, CartridgeState};
+use crate::cpu::Cpu;
+use crate::joypad::Button;
+use crate::limiter::FrameLimiter;
+use crate::recording::{Player, Recorder};
+use crate::rewind::{self, Rewind};
+use crate::state::{StateError, StateHeader};
+
+/// Frames between flushes of modified battery-backed RAM to the save file:
+/// about 5 seconds of emulated time.
+const SAVE_FLUSH_INTERVAL: u64 = 300;
+
+pub struct Emulator {
+    cpu: Cpu,
+    bus: Bus,
+    /// Set while rewinding is enabled.
+    rewind: Option<Rewind>,
+    /// Set while frames are paced to real time.
+    limiter: Option<FrameLimiter>,
+    /// Receives a Gameboy Doctor log line before each instruction while
+    /// tracing is enabled.
+    trace: Option<Box<dyn Write + Send>>,
+    /// Set while [`run_frame`](Emulator::run_frame) is suspended.
+    paused: bool,
+    /// Set while input is being recorded.
+    recorder: Option<Recorder>,
+    /// Set while a recording is being played back.
+    player: Option<Player>,
+    /// The `.sav` file battery-backed RAM is flushed to, once attached.
+    save_file: Option<PathBuf>,
+}
+
+impl Emulator {
+    /// Creates a machine with the cartridge `rom` inserted, skipping the
+    /// boot ROM: execution starts at `0x0100` with the registers the boot ROM
+    /// would have left. Cartridges with CGB support run in CGB mode.
+    pub fn new(rom: Vec<u8>) -> Result<Emulator, HeaderError> {
+        let mut bus = Bus::new(Cartridge::new(rom)?);
+        bus.skip_boot_rom();
+        Ok(Emulator::with_bus(bus))
+    }
+
+    /// Creates a machine with the cartridge `rom` inserted that starts by
+    /// running `boot_rom` from `0x0000`, which scrolls the logo and hands
+    /// over to the cartridge at `0x0100`.
+    pub fn with_boot_rom(rom: Vec<u8>, boot_rom: [u8; BOOT_ROM_SIZE]) -> Result<Emulator, HeaderError> {
+        let mut bus = Bus::new(Cartridge::new(rom)?);
+        bus.map_boot_rom(boot_rom);
+        Ok(Emulator::with_bus(bus))
+    }
+
+    fn with_bus(bus: Bus) -> Emulator {
+        Emulator {
+            cpu: Emulator::power_on_cpu(&bus),
+            bus,
+            rewind: None,
+            limiter: None,
+            trace: None,
+            paused: false,
+            recorder: None,
+            player: None,
+            save_file: None,
+        }
+    }
+
+    /// Returns the CPU at power-on: about to run the boot ROM if it is mapped,
+    /// or in the state it leaves otherwise.
+    fn power_on_cpu(bus: &Bus) -> Cpu {
+        if bus.boot_rom_mapped() {
+            Cpu { pc: 0x0000, sp: 0x0000, ..Cpu::new() }
+        } else if bus.is_cgb() {
+            Cpu::post_boot_cgb()
+        } else {
+            Cpu::post_boot(bus.cartridge().header().header_checksum)
+        }
+    }
+
+    /// Resets the machine as the power switch would, keeping the cartridge
+    /// with its battery-backed RAM and clock. The boot ROM runs again if one
+    /// was given, otherwise the post-boot state is set up directly. Settings
+    /// such as the speed, rewind, tracing and pause state are kept.
+    pub fn reset(&mut self) {
+        self.bus.reset();
+        self.cpu = Emulator::power_on_cpu(&self.bus);
+    }
+
+    /// Suspends [`run_frame`](Emulator::run_frame) and
+    /// [`run_frames`](Emulator::run_frames), e.g. while a frontend shows a
+    /// menu. [`step`](Emulator::step) still executes, for debuggers.
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn cpu(&self) -> &Cpu {
+        &self.cpu
+    }
+
+    pub fn cpu_mut(&mut self) -> &mut Cpu {
+        &mut self.cpu
+    }
+
+    pub fn bus(&self) -> &Bus {
+        &self.bus
+    }
+
+    pub fn bus_mut(&mut self) -> &mut Bus {
+        &mut self.bus
+    }
+
+    /// Reads `addr` through the bus as the CPU would see it, with the current
+    /// bank mapping, without advancing the machine or triggering watchpoints.
+    pub fn read_memory(&self, addr: u16) -> u8 {
+        self.bus.read(addr)
+    }
+
+    /// Reads `len` bytes from `start` on, as [`read_memory`] does. Addresses
+    /// wrap around from `0xFFFF` to `0x0000`.
+    ///
+    /// [`read_memory`]: Emulator::read_memory
+    pub fn read_range(&self, start: u16, len: usize) -> Vec<u8> {
+        (0..len).map(|offset| self.bus.read(start.wrapping_add(offset as u16))).collect()
+    }
+
+    /// Reads the whole of `region`.
+    pub fn dump_region(&self, region: Region) -> Vec<u8> {
+        let range = region.range();
+        self.read_range(*range.start(), range.len())
+    }
+
+    /// Presses `button`, for frontends to call from input events. Ignored
+    /// while a recording is playing back.
+    pub fn press(&mut self, button: Button) {
+        if self.player.is_none() {
+            self.record(button, true);
+            self.bus.press(button);
+        }
+    }
+
+    pub fn release(&mut self, button: Button) {
+        if self.player.is_none() {
+            self.record(button, false);
+            self.bus.release(button);
+        }
+    }
+
+    /// Logs an input event if recording, stopping the recording if writing
+    /// fails.
+    fn record(&mut self, button: Button, pressed: bool) {
+        if let Some(recorder) = &mut self.recorder
+            && recorder.record(button, pressed).is_err()
+        {
+            self.recorder = None;
+        }
+    }
+
+    /// Starts recording input to the `.gbi` file at `path`, with frames
+    /// counted from now. See [`recording`](crate::recording) for replaying
+    /// the run deterministically.
+    pub fn start_recording(&mut self, path: &Path) -> io::Result<()> {
+        self.recorder = Some(Recorder::create(path)?);
+        Ok(())
+    }
+
+    /// Stops recording and flushes the file.
+    pub fn stop_recording(&mut self) -> io::Result<()> {
+        match self.recorder.take() {
+            Some(recorder) => recorder.finish(),
+            None => Ok(()),
+        }
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recorder.is_some()
+    }
+
+    /// Starts playing back the `.gbi` file at `path` from the next frame.
+    /// Input from [`press`](Emulator::press) and
+    /// [`release`](Emulator::release) is ignored until playback ends.
+    pub fn play_recording(&mut self, path: &Path) -> io::Result<()> {
+        self.player = Some(Player::open(path)?);
+        Ok(())
+    }
+
+    /// Stops playback, leaving the buttons as they are.
+    pub fn stop_playback(&mut self) {
+        self.player = None;
+    }
+
+    /// Returns `true` while a recording has events left to play.
+    pub fn is_playing(&self) -> bool {
+        self.player.is_some()
+    }
+
+    /// Loads battery-backed RAM from the `.sav` file at `path` (see
+    /// [`save_path`](crate::cartridge::save_path)) and keeps flushing it
+    /// there: every few seconds while it is modified, and when the emulator
+    /// is dropped. Cartridges without a battery never touch the file.
+    pub fn attach_save_file(&mut self, path: &Path) -> io::Result<()> {
+        self.bus.cartridge_mut().load_ram(path)?;
+        self.save_file = Some(path.to_path_buf());
+        Ok(())
+    }
+
+    /// Writes battery-backed RAM to the attached save file if it was modified
+    /// since the last flush.
+    pub fn flush_save_file(&mut self) -> io::Result<()> {
+        match &self.save_file {
+            Some(path) if self.bus.cartridge().ram_modified() => self.bus.cartridge_mut().save_ram(path),
+            _ => Ok(()),
+        }
+    }
+
+    /// Executes one instruction (or services one interrupt) and returns the
+    /// M-cycles it took.
+    pub fn step(&mut self) -> u8 {
+        if self.trace.is_some() {
+            self.trace_instruction();
+        }
+        self.cpu.step(&mut self.bus)
+    }
+
+    /// Starts writing a line to `writer` before each instruction, in the
+    /// Gameboy Doctor format of [`Cpu::doctor_line`].
+    ///
+    /// Nothing is logged for interrupt dispatches or while halted. To compare
+    /// against the reference logs, LY must read `0x90` as it did when they
+    /// were recorded: call [`Bus::stub_ly`] with `Some(0x90)`. Tracing stops
+    /// if writing fails.
+    pub fn enable_trace(&mut self, writer: Box<dyn Write + Send>) {
+        self.trace = Some(writer);
+    }
+
+    /// Stops tracing and flushes the trace writer.
+    pub fn disable_trace(&mut self) {
+        if let Some(mut writer) = self.trace.take() {
+            let _ = writer.flush();
+        }
+    }
+
+    /// Writes the trace line for the instruction about to execute, if the
+    /// next step executes one.
+    fn trace_instruction(&mut self) {
+        let cpu = &self.cpu;
+        let pending = self.bus.pending_interrupts() != 0;
+        if cpu.locked || (cpu.ime && pending) || (cpu.halted && !pending) {
+            return;
+        }
+
+        let pcmem = [0, 1, 2, 3].map(|offset| self.read_memory(cpu.pc.wrapping_add(offset)));
+        let line = cpu.doctor_line(pcmem);
+        if let Some(writer) = &mut self.trace
+            && writeln!(writer, "{}", line).is_err()
+        {
+            self.trace = None;
+        }
+    }
+
+    /// Runs until the PPU starts the next frame, then sleeps out the rest of
+    /// the frame's wall-clock time if the frame limiter is enabled. Does
+    /// nothing while paused.
+    pub fn run_frame(&mut self) {
+        if self.paused {
+            return;
+        }
+        if let Some(player) = &mut self.player {
+            for event in player.due_events() {
+                if event.pressed {
+                    self.bus.press(event.button);
+                } else {
+                    self.bus.release(event.button);
+                }
+            }
+        }
+
+        let frame = self.bus.ppu().frame_count();
+        while self.bus.ppu().frame_count() == frame {
+            self.step();
+        }
+
+        if self.rewind.as_mut().is_some_and(Rewind::frame_completed) {
+            let snapshot = self.save_state();
+            if let Some(rewind) = &mut self.rewind {
+                rewind.push(snapshot);
+            }
+        }
+
+        if let Some(recorder) = &mut self.recorder {
+            recorder.frame_completed();
+        }
+        if let Some(player) = &mut self.player {
+            player.frame_completed();
+            if player.is_finished() {
+                self.player = None;
+            }
+        }
+
+        if self.save_file.is_some()
+            && self.bus.ppu().frame_count().is_multiple_of(SAVE_FLUSH_INTERVAL)
+            && let Err(e) = self.flush_save_file()
+        {
+            crate::warn!("⚠️  Warning: Failed to write the save file: {}", e);
+        }
+
+        if let Some(limiter) = &mut self.limiter {
+            limiter.wait();
+        }
+    }
+
+    /// Runs `frames` frames and returns the last one rendered, as 160×144
+    /// RGBA pixels.
+    pub fn run_frames(&mut self, frames: u32) -> &[u8] {
+        for _ in 0..frames {
+            self.run_frame();
+        }
+        self.framebuffer()
+    }
+
+    /// Starts pacing [`run_frame`](Emulator::run_frame) to the real frame
+    /// rate times the current speed.
+    pub fn enable_frame_limiter(&mut self) {
+        self.limiter = Some(FrameLimiter::new(self.speed()));
+    }
+
+    /// Lets frames run as fast as the host allows.
+    pub fn disable_frame_limiter(&mut self) {
+        self.limiter = None;
+    }
+
+    pub fn speed(&self) -> f64 {
+        self.bus.apu().speed()
+    }
+
+    /// Sets the emulation speed multiplier, e.g. 2.0 or 0.5, or
+    /// [`limiter::UNCAPPED`](crate::limiter::UNCAPPED) for fast-forward. The
+    /// frame limiter paces frames accordingly and audio is resampled so it
+    /// keeps up, or muted when uncapped. Non-positive values are ignored.
+    pub fn set_speed(&mut self, speed: f64) {
+        if speed <= 0.0 {
+            return;
+        }
+        self.bus.apu_mut().set_speed(speed);
+        if let Some(limiter) = &mut self.limiter {
+            limiter.set_speed(speed);
+        }
+    }
+
+    /// Starts taking a snapshot every `interval` frames, keeping the latest
+    /// `depth` within [`rewind::DEFAULT_MAX_BYTES`].
+    pub fn enable_rewind(&mut self, interval: u32, depth: usize) {
+        self.rewind = Some(Rewind::new(interval, depth, rewind::DEFAULT_MAX_BYTES));
+    }
+
+    /// Stops rewinding and frees the snapshots.
+    pub fn disable_rewind(&mut self) {
+        self.rewind = None;
+    }
+
+    pub fn rewind(&self) -> Option<&Rewind> {
+        self.rewind.as_ref()
+    }
+
+    /// Restores the newest rewind snapshot and drops it, so repeated calls
+    /// step further back. Returns `false` when there is nothing to rewind to.
+    pub fn rewind_step(&mut self) -> bool {
+        let Some(snapshot) = self.rewind.as_mut().and_then(Rewind::pop) else {
+            return false;
+        };
+        self.load_state(&snapshot).is_ok()
+    }
+
+    /// Snapshots the whole machine, except the cartridge ROM, into a
+    /// versioned save state.
+    pub fn save_state(&self) -> Vec<u8> {
+        let header = StateHeader::new(self.bus.cartridge().header());
+        let state = (header, &self.cpu, &self.bus, self.bus.cartridge().save_state());
+        postcard::to_stdvec(&state).expect("serializing a save state cannot fail")
+    }
+
+    /// Restores a state returned by [`save_state`](Emulator::save_state) for
+    /// the same cartridge. On error the machine is left untouched.
+    pub fn load_state(&mut self, data: &[u8]) -> Result<(), StateError> {
+        let (header, rest) = postcard::take_from_bytes::<StateHeader>(data).map_err(|_| StateError::NotAState)?;
+        header.validate(self.bus.cartridge().header())?;
+
+        let (cpu, bus, cartridge): (Cpu, Bus, CartridgeState) = postcard::from_bytes(rest)?;
+        self.cpu = cpu;
+        self.bus.restore(bus);
+        self.bus.cartridge_mut().load_state(&cartridge);
+        Ok(())
+    }
+
+    /// The last rendered frame, as 160×144 RGBA pixels.
+    pub fn framebuffer(&self) -> &[u8] {
+        self.bus.ppu().framebuffer()
+    }
+}
+
+/// Flushes battery-backed RAM so the game is saved on exit.
+impl Drop for Emulator {
+    fn drop(&mut self) {
+        if let Err(e) = self.flush_save_file() {
+            crate::warn!("⚠️  Warning: Failed to write the save file: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::state::STATE_VERSION;
+    use crate::testing::{program_rom, rom};
+
+    /// Increments BGP in a tight loop, so every frame shows a different
+    /// pattern of bands.
+    const CYCLE_PALETTE: [u8; 6] = [
+        0x21, 0x47, 0xFF, // LD HL,0xFF47
+        0x34, // INC (HL)
+        0x18, 0xFD, // JR -3
+    ];
+
+    fn emulator() -> Emulator {
+        Emulator::new(program_rom(&CYCLE_PALETTE)).unwrap()
+    }
+
+    /// A stand-in for the DMG boot ROM: it sets up the registers the real one
+    /// leaves, without the logo, and unmaps itself from `0x00FE` like it.
+    fn boot_rom() -> [u8; BOOT_ROM_SIZE] {
+        let mut boot_rom = [0; BOOT_ROM_SIZE];
+        let program = [
+            0x3E, 0x80, 0xE0, 0x26, // LD A,0x80; LDH (NR52),A
+            0xE0, 0x11, // LDH (NR11),A
+            0x3E, 0xF3, 0xE0, 0x12, // LD A,0xF3; LDH (NR12),A
+            0xE0, 0x25, // LDH (NR51),A
+            0x3E, 0x77, 0xE0, 0x24, // LD A,0x77; LDH (NR50),A
+            0x3E, 0xFC, 0xE0, 0x47, // LD A,0xFC; LDH (BGP),A
+            0x3E, 0x91, 0xE0, 0x40, // LD A,0x91; LDH (LCDC),A
+            0x31, 0xFE, 0xFF, // LD SP,0xFFFE
+            0x01, 0xB0, 0x01, 0xC5, 0xF1, // LD BC,0x01B0; PUSH BC; POP AF
+            0x01, 0x13, 0x00, // LD BC,0x0013
+            0x11, 0xD8, 0x00, // LD DE,0x00D8
+            0x21, 0x4D, 0x01, // LD HL,0x014D
+            0xC3, 0xFE, 0x00, // JP 0x00FE
+        ];
+        boot_rom[..program.len()].copy_from_slice(&program);
+        // LDH (0xFF50),A
+        boot_rom[0xFE..].copy_from_slice(&[0xE0, 0x50]);
+        boot_rom
+    }
+
+    /// Asserts the state both power-on paths reach at `0x0100`.
+    fn assert_post_boot(emulator: &Emulator) {
+        let checksum = emulator.bus().cartridge().header().header_checksum;
+        assert_eq!(emulator.cpu(), &Cpu::post_boot(checksum));
+        assert!(!emulator.bus().boot_rom_mapped());
+        let io = [0xFF40, 0xFF47, 0xFF24, 0xFF25, 0xFF12].map(|addr| emulator.read_memory(addr));
+        assert_eq!(io, [0x91, 0xFC, 0x77, 0xF3, 0xF3]);
+        assert_eq!(emulator.read_memory(0xFF26) & 0xF0, 0xF0);
+        // The cartridge's entry point is visible again
+        assert_eq!(emulator.read_memory(0x0100), 0x00);
+    }
+
+    #[test]
+    fn load_state_resumes_identically() {
+        let mut emulator = emulator();
+        emulator.run_frames(3);
+        let state = emulator.save_state();
+        let expected = emulator.run_frames(5).to_vec();
+
+        emulator.load_state(&state).unwrap();
+        assert_ne!(emulator.framebuffer(), expected);
+        assert_eq!(emulator.run_frames(5), expected);
+    }
+
+    #[test]
+    fn load_state_rejects_truncated_data() {
+        let mut emulator = emulator();
+        let state = emulator.save_state();
+
+        assert!(matches!(emulator.load_state(&state[..state.len() / 2]), Err(StateError::Corrupt(_))));
+        assert!(matches!(emulator.load_state(&state[..2]), Err(StateError::NotAState)));
+    }
+
+    #[test]
+    fn load_state_rejects_other_versions() {
+        let mut emulator = emulator();
+        let mut state = emulator.save_state();
+        // The version follows the 4-byte magic as a single-byte varint
+        assert_eq!(state[4] as u16, STATE_VERSION);
+        state[4] -= 1;
+
+        let result = emulator.load_state(&state);
+        assert!(matches!(result, Err(StateError::UnsupportedVersion(version)) if version == STATE_VERSION - 1));
+    }
+
+    #[test]
+    fn load_state_rejects_other_cartridges_and_garbage() {
+        let mut emulator = emulator();
+        // Same title, but an MBC1 header with a different checksum
+        let state = Emulator::new(rom(0x01, 2, 0x00)).unwrap().save_state();
+
+        assert!(matches!(emulator.load_state(&state), Err(StateError::WrongCartridge(title)) if title == "TEST"));
+        assert!(matches!(emulator.load_state(b"not a save state"), Err(StateError::NotAState)));
+    }
+
+    #[test]
+    fn failed_load_leaves_the_machine_untouched() {
+        let mut emulator = emulator();
+        emulator.run_frames(2);
+        let state = emulator.save_state();
+
+        assert!(emulator.load_state(&state[..state.len() - 1]).is_err());
+        assert_eq!(emulator.save_state(), state);
+    }
+
+    #[test]
+    fn rewind_restores_previous_frames() {
+        let mut emulator = emulator();
+        emulator.enable_rewind(1, 10);
+        let first = emulator.run_frames(1).to_vec();
+        let second = emulator.run_frames(1).to_vec();
+        emulator.run_frames(1);
+        assert_eq!(emulator.rewind().map(Rewind::len), Some(3));
+
+        assert!(emulator.rewind_step());
+        assert!(emulator.rewind_step());
+        assert_eq!(emulator.framebuffer(), second);
+        assert!(emulator.rewind_step());
+        assert_eq!(emulator.framebuffer(), first);
+        assert!(!emulator.rewind_step());
+
+        // Execution continues from the restored frame
+        assert_eq!(emulator.run_frames(1), second);
+    }
+
+    #[test]
+    fn skipping_the_boot_rom_starts_at_the_entry_point() {
+        let emulator = emulator();
+
+        assert_post_boot(&emulator);
+        assert_eq!(emulator.read_memory(0xFF00), 0xCF);
+        assert_eq!(emulator.read_memory(0xFF0F), 0xE1);
+    }
+
+    #[test]
+    fn boot_rom_hands_over_at_the_entry_point() {
+        let mut emulator = Emulator::with_boot_rom(program_rom(&CYCLE_PALETTE), boot_rom()).unwrap();
+        assert_eq!(emulator.cpu().pc, 0x0000);
+        assert_eq!(emulator.read_memory(0x0000), 0x3E);
+
+        for _ in 0..100 {
+            if emulator.cpu().pc == 0x0100 {
+                break;
+            }
+            emulator.step();
+        }
+        assert_post_boot(&emulator);
+    }
+
+    /// A trace writer the test can read back.
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn traces_each_instruction() {
+        let mut emulator = emulator();
+        let buffer = SharedBuffer::default();
+        emulator.enable_trace(Box::new(buffer.clone()));
+        for _ in 0..4 {
+            emulator.step();
+        }
+        emulator.disable_trace();
+        emulator.step();
+
+        let trace = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let lines: Vec<&str> = trace.lines().collect();
+        assert_eq!(
+            lines,
+            [
+                "A:01 F:B0 B:00 C:13 D:00 E:D8 H:01 L:4D SP:FFFE PC:0100 PCMEM:00,C3,50,01",
+                "A:01 F:B0 B:00 C:13 D:00 E:D8 H:01 L:4D SP:FFFE PC:0101 PCMEM:C3,50,01,00",
+                "A:01 F:B0 B:00 C:13 D:00 E:D8 H:01 L:4D SP:FFFE PC:0150 PCMEM:21,47,FF,34",
+                "A:01 F:B0 B:00 C:13 D:00 E:D8 H:FF L:47 SP:FFFE PC:0153 PCMEM:34,18,FD,00",
+            ]
+        );
+    }
+
+    #[test]
+    fn paused_emulator_does_not_run_frames() {
+        let mut emulator = emulator();
+        emulator.run_frames(2);
+        let frame = emulator.framebuffer().to_vec();
+        let cycles = emulator.bus().cycles();
+
+        emulator.pause();
+        assert!(emulator.is_paused());
+        emulator.run_frames(3);
+        assert_eq!(emulator.bus().cycles(), cycles);
+        assert_eq!(emulator.bus().ppu().frame_count(), 2);
+        assert_eq!(emulator.framebuffer(), frame);
+
+        // Single steps still execute, for debuggers
+        assert!(emulator.step() > 0);
+        emulator.resume();
+        emulator.run_frames(1);
+        assert_eq!(emulator.bus().ppu().frame_count(), 3);
+    }
+
+    #[test]
+    fn reset_keeps_cartridge_ram() {
+        #[rustfmt::skip]
+        let program = [
+            0x3E, 0x0A, 0xEA, 0x00, 0x00, // LD A,0x0A; LD (0x0000),A
+            0x3E, 0x5A, 0xEA, 0x00, 0xA0, // LD A,0x5A; LD (0xA000),A
+            0xEA, 0x00, 0xC0, // LD (0xC000),A
+            0x18, 0xFE, // JR -2
+        ];
+        // MBC1 with 8 KiB of battery-backed RAM
+        let mut rom = rom(0x03, 2, 0x02);
+        rom[0x0150..0x0150 + program.len()].copy_from_slice(&program);
+        let mut emulator = Emulator::new(rom).unwrap();
+        let power_on = (emulator.cpu().clone(), emulator.read_range(0xFF40, 12));
+        emulator.run_frames(2);
+        assert_eq!(emulator.read_memory(0xA000), 0x5A);
+
+        emulator.pause();
+        emulator.reset();
+        assert!(emulator.is_paused());
+        assert_eq!((emulator.cpu().clone(), emulator.read_range(0xFF40, 12)), power_on);
+        assert_eq!(emulator.bus().ppu().frame_count(), 0);
+        assert_eq!(emulator.read_memory(0xC000), 0x00);
+        // The MBC registers are reset, so RAM is disabled again
+        assert_eq!(emulator.read_memory(0xA000), 0xFF);
+        emulator.bus_mut().write(0x0000, 0x0A);
+        assert_eq!(emulator.read_memory(0xA000), 0x5A);
+    }
+
+    #[test]
+    fn save_file_is_flushed_on_drop() {
+        let path = std::env::temp_dir().join(format!("rusty_boy_emulator_{}.sav", std::process::id()));
+        // MBC1 with 8 KiB of battery-backed RAM
+        let mut emulator = Emulator::new(rom(0x03, 2, 0x02)).unwrap();
+        emulator.attach_save_file(&path).unwrap();
+        emulator.bus_mut().write(0x0000, 0x0A);
+        emulator.bus_mut().write(0xA000, 0x5A);
+        drop(emulator);
+
+        let mut emulator = Emulator::new(rom(0x03, 2, 0x02)).unwrap();
+        emulator.attach_save_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        emulator.bus_mut().write(0x0000, 0x0A);
+        assert_eq!(emulator.read_memory(0xA000), 0x5A);
+    }
+
+    #[test]
+    fn non_positive_speeds_are_ignored() {
+        let mut emulator = emulator();
+        emulator.enable_frame_limiter();
+        emulator.set_speed(2.0);
+        emulator.set_speed(0.0);
+        emulator.set_speed(-1.0);
+
+        assert_eq!(emulator.speed(), 2.0);
+        assert_eq!(emulator.limiter.as_ref().map(FrameLimiter::speed), Some(2.0));
+    }
+}
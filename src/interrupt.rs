@@ -0,0 +1,34 @@
+//! The five interrupt sources, their bits in the IE (`0xFFFF`) and IF
+//! (`0xFF0F`) registers and their vectors.
+
+/// An interrupt source, in priority order: when several are pending, the one
+/// declared first is serviced first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interrupt {
+    VBlank,
+    Stat,
+    Timer,
+    Serial,
+    Joypad,
+}
+
+impl Interrupt {
+    /// Every source, highest priority first.
+    pub const ALL: [Interrupt; 5] =
+        [Interrupt::VBlank, Interrupt::Stat, Interrupt::Timer, Interrupt::Serial, Interrupt::Joypad];
+
+    /// Returns the bit of this source in IE and IF.
+    pub fn bit(self) -> u8 {
+        1 << self as u8
+    }
+
+    /// Returns the address the CPU jumps to when servicing this source.
+    pub fn vector(self) -> u16 {
+        0x0040 + 8 * self as u16
+    }
+
+    /// Returns the highest-priority source set in `bits`, if any.
+    pub fn highest_priority(bits: u8) -> Option<Interrupt> {
+        Interrupt::ALL.into_iter().find(|interrupt| bits & interrupt.bit() != 0)
+    }
+}
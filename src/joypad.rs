@@ -0,0 +1,183 @@
+//! The joypad register P1/JOYP (`0xFF00`).
+//!
+//! The eight buttons are wired as a 2×4 matrix: writing 0 to bit 4 selects
+//! the direction keys and writing 0 to bit 5 the action buttons, whose states
+//! then read back in the lower nibble, 0 meaning pressed.
+
+use serde::{Deserialize, Serialize};
+
+const SELECT_DIRECTIONS: u8 = 0x10;
+const SELECT_ACTIONS: u8 = 0x20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+pub enum Button {
+    Right,
+    Left,
+    Up,
+    Down,
+    A,
+    B,
+    Select,
+    Start,
+}
+
+impl Button {
+    /// Every button, in P1 bit order.
+    pub const ALL: [Button; 8] = [
+        Button::Right,
+        Button::Left,
+        Button::Up,
+        Button::Down,
+        Button::A,
+        Button::B,
+        Button::Select,
+        Button::Start,
+    ];
+
+    /// Returns the bit of this button in `Joypad::pressed`: directions in the
+    /// lower nibble and actions in the upper one, each in P1 bit order.
+    fn bit(self) -> u8 {
+        1 << self as u8
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Joypad {
+    /// The select bits (4 and 5) last written to P1.
+    select: u8,
+    /// One bit per pressed [`Button`].
+    pressed: u8,
+}
+
+impl Joypad {
+    pub fn new() -> Joypad {
+        Joypad { select: SELECT_DIRECTIONS | SELECT_ACTIONS, pressed: 0 }
+    }
+
+    /// Presses `button`. Returns `true` when a Joypad interrupt should be
+    /// requested.
+    pub fn press(&mut self, button: Button) -> bool {
+        self.update(|joypad| joypad.pressed |= button.bit())
+    }
+
+    /// Releases `button`.
+    pub fn release(&mut self, button: Button) {
+        self.pressed &= !button.bit();
+    }
+
+    pub fn read(&self) -> u8 {
+        // The two unused upper bits read as 1
+        0xC0 | self.select | self.lines()
+    }
+
+    /// Writes the select bits. Returns `true` when a Joypad interrupt should
+    /// be requested, as selecting a group with a button already held pulls
+    /// a line low.
+    pub fn write(&mut self, value: u8) -> bool {
+        self.update(|joypad| joypad.select = value & (SELECT_DIRECTIONS | SELECT_ACTIONS))
+    }
+
+    /// Returns the active-low state of the four input lines. With both groups
+    /// selected, a line is low when either of its buttons is pressed.
+    fn lines(&self) -> u8 {
+        let mut pressed = 0;
+        if self.select & SELECT_DIRECTIONS == 0 {
+            pressed |= self.pressed & 0x0F;
+        }
+        if self.select & SELECT_ACTIONS == 0 {
+            pressed |= self.pressed >> 4;
+        }
+        !pressed & 0x0F
+    }
+
+    /// Applies `change` and reports whether any input line went from high to
+    /// low, which is what requests the interrupt.
+    fn update(&mut self, change: impl FnOnce(&mut Joypad)) -> bool {
+        let before = self.lines();
+        change(self);
+        before & !self.lines() != 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interrupt::Interrupt;
+    use crate::testing::program_bus;
+
+    /// Returns a joypad with Right, Down and Start held.
+    fn joypad() -> Joypad {
+        let mut joypad = Joypad::new();
+        for button in [Button::Right, Button::Down, Button::Start] {
+            joypad.press(button);
+        }
+        joypad
+    }
+
+    #[test]
+    fn reads_the_selected_group() {
+        let mut joypad = joypad();
+
+        joypad.write(0x20);
+        assert_eq!(joypad.read(), 0xE6);
+        joypad.write(0x10);
+        assert_eq!(joypad.read(), 0xD7);
+    }
+
+    #[test]
+    fn both_groups_selected_combine_their_lines() {
+        let mut joypad = joypad();
+
+        joypad.write(0x00);
+        assert_eq!(joypad.read(), 0xC6);
+    }
+
+    #[test]
+    fn no_group_selected_reads_released() {
+        let mut joypad = joypad();
+
+        joypad.write(0x30);
+        assert_eq!(joypad.read(), 0xFF);
+    }
+
+    #[test]
+    fn release_raises_the_line_again() {
+        let mut joypad = joypad();
+        joypad.write(0x20);
+
+        joypad.release(Button::Down);
+        assert_eq!(joypad.read(), 0xEE);
+    }
+
+    #[test]
+    fn pressing_a_selected_button_requests_an_interrupt() {
+        let mut joypad = Joypad::new();
+        joypad.write(0x10);
+
+        assert!(!joypad.press(Button::Up));
+        assert!(joypad.press(Button::A));
+        // The line is already low
+        assert!(!joypad.press(Button::A));
+    }
+
+    #[test]
+    fn selecting_a_held_group_requests_an_interrupt() {
+        let mut joypad = Joypad::new();
+        joypad.write(0x30);
+        joypad.press(Button::Left);
+
+        assert!(joypad.write(0x20));
+        assert!(!joypad.write(0x20));
+    }
+
+    #[test]
+    fn press_sets_the_joypad_bit_in_if() {
+        let mut bus = program_bus(&[]);
+        bus.write(0xFF0F, 0x00);
+        bus.write(0xFF00, 0x10);
+
+        bus.press(Button::Start);
+        assert_eq!(bus.read(0xFF0F) & Interrupt::Joypad.bit(), Interrupt::Joypad.bit());
+    }
+}
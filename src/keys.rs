@@ -0,0 +1,231 @@
+//! Host key bindings for the joypad, loaded from an optional `keys.toml` in
+//! the project root.
+//!
+//! Each button is bound to one host key name; missing buttons keep their
+//! default key:
+//!
+//! ```toml
+//! up = "Up"
+//! down = "Down"
+//! left = "Left"
+//! right = "Right"
+//! a = "Z"
+//! b = "X"
+//! start = "Enter"
+//! select = "Shift"
+//! ```
+//!
+//! Key names are matched case-insensitively, so frontends can pass the names
+//! their windowing library uses.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::joypad::Button;
+
+/// Name of the key bindings file looked up in the project root.
+pub const KEYS_FILE: &str = "keys.toml";
+
+/// The contents of [`KEYS_FILE`].
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct KeysFile {
+    right: Option<String>,
+    left: Option<String>,
+    up: Option<String>,
+    down: Option<String>,
+    a: Option<String>,
+    b: Option<String>,
+    select: Option<String>,
+    start: Option<String>,
+}
+
+impl KeysFile {
+    fn key(&self, button: Button) -> Option<&String> {
+        match button {
+            Button::Right => self.right.as_ref(),
+            Button::Left => self.left.as_ref(),
+            Button::Up => self.up.as_ref(),
+            Button::Down => self.down.as_ref(),
+            Button::A => self.a.as_ref(),
+            Button::B => self.b.as_ref(),
+            Button::Select => self.select.as_ref(),
+            Button::Start => self.start.as_ref(),
+        }
+    }
+}
+
+/// Errors that can occur while loading key bindings.
+#[derive(Debug)]
+pub enum KeyBindingsError {
+    /// The file exists but could not be read.
+    Io(io::Error),
+    /// The file is not valid TOML or names an unknown button.
+    Parse(toml::de::Error),
+    /// One key is bound to two buttons.
+    Duplicate { key: String, first: Button, second: Button },
+}
+
+impl fmt::Display for KeyBindingsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeyBindingsError::Io(e) => write!(f, "failed to read {}: {}", KEYS_FILE, e),
+            KeyBindingsError::Parse(e) => write!(f, "failed to parse {}: {}", KEYS_FILE, e),
+            KeyBindingsError::Duplicate { key, first, second } => {
+                write!(f, "invalid {}: key \"{}\" is bound to both {:?} and {:?}", KEYS_FILE, key, first, second)
+            }
+        }
+    }
+}
+
+impl std::error::Error for KeyBindingsError {}
+
+/// Maps host key names to joypad buttons, one key per button.
+#[derive(Debug, Clone)]
+pub struct KeyBindings {
+    /// The key of each button, indexed by `Button as usize`.
+    keys: [String; 8],
+    /// Lowercase key names to buttons, for [`button`](KeyBindings::button).
+    buttons: HashMap<String, Button>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> KeyBindings {
+        KeyBindings::from_file(KeysFile::default()).expect("the default bindings are distinct")
+    }
+}
+
+impl KeyBindings {
+    /// Loads [`KEYS_FILE`] from the current directory, falling back to the
+    /// defaults when it does not exist.
+    pub fn load() -> Result<KeyBindings, KeyBindingsError> {
+        KeyBindings::load_from(Path::new(KEYS_FILE))
+    }
+
+    /// Loads key bindings from `path`, falling back to the defaults when it
+    /// does not exist.
+    pub fn load_from(path: &Path) -> Result<KeyBindings, KeyBindingsError> {
+        match fs::read_to_string(path) {
+            Ok(content) => KeyBindings::parse(&content),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(KeyBindings::default()),
+            Err(e) => Err(KeyBindingsError::Io(e)),
+        }
+    }
+
+    /// Parses key bindings in the format of [`KEYS_FILE`].
+    pub fn parse(content: &str) -> Result<KeyBindings, KeyBindingsError> {
+        KeyBindings::from_file(toml::from_str(content).map_err(KeyBindingsError::Parse)?)
+    }
+
+    fn from_file(file: KeysFile) -> Result<KeyBindings, KeyBindingsError> {
+        let keys = Button::ALL.map(|button| match file.key(button) {
+            Some(key) => key.clone(),
+            None => default_key(button).to_string(),
+        });
+
+        let mut buttons = HashMap::new();
+        for (button, key) in Button::ALL.into_iter().zip(&keys) {
+            if let Some(first) = buttons.insert(key.to_lowercase(), button) {
+                return Err(KeyBindingsError::Duplicate { key: key.clone(), first, second: button });
+            }
+        }
+        Ok(KeyBindings { keys, buttons })
+    }
+
+    /// Returns the button bound to the host key `key`, for input event
+    /// handlers.
+    pub fn button(&self, key: &str) -> Option<Button> {
+        self.buttons.get(&key.to_lowercase()).copied()
+    }
+
+    /// Returns the host key bound to `button`.
+    pub fn key(&self, button: Button) -> &str {
+        &self.keys[button as usize]
+    }
+}
+
+fn default_key(button: Button) -> &'static str {
+    match button {
+        Button::Right => "Right",
+        Button::Left => "Left",
+        Button::Up => "Up",
+        Button::Down => "Down",
+        Button::A => "Z",
+        Button::B => "X",
+        Button::Select => "Shift",
+        Button::Start => "Enter",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    #[test]
+    fn missing_buttons_keep_their_default_key() {
+        let bindings = KeyBindings::parse("a = \"J\"\nstart = \"Space\"\n").unwrap();
+
+        assert_eq!(bindings.key(Button::A), "J");
+        assert_eq!(bindings.key(Button::Start), "Space");
+        assert_eq!(bindings.key(Button::B), "X");
+        assert_eq!(bindings.key(Button::Up), "Up");
+        assert_eq!(bindings.button("Z"), None);
+    }
+
+    #[test]
+    fn empty_file_gives_the_defaults() {
+        let bindings = KeyBindings::parse("").unwrap();
+
+        for button in Button::ALL {
+            assert_eq!(bindings.key(button), default_key(button));
+            assert_eq!(bindings.button(default_key(button)), Some(button));
+        }
+    }
+
+    #[test]
+    fn lookup_ignores_case() {
+        let bindings = KeyBindings::parse("select = \"RShift\"\n").unwrap();
+
+        for key in ["RShift", "rshift", "RSHIFT"] {
+            assert_eq!(bindings.button(key), Some(Button::Select), "{}", key);
+        }
+        assert_eq!(bindings.button("enter"), Some(Button::Start));
+        // The key keeps the case it was written with
+        assert_eq!(bindings.key(Button::Select), "RShift");
+    }
+
+    #[test]
+    fn rejects_unknown_fields() {
+        let result = KeyBindings::parse("jump = \"Space\"\n");
+
+        assert!(matches!(result, Err(KeyBindingsError::Parse(_))));
+    }
+
+    #[test]
+    fn rejects_a_key_bound_twice() {
+        // B keeps its default "X"
+        let result = KeyBindings::parse("a = \"x\"\n");
+
+        let Err(KeyBindingsError::Duplicate { key, first, second }) = result else {
+            panic!("expected a duplicate key error, got {:?}", result);
+        };
+        assert_eq!((key.as_str(), first, second), ("X", Button::A, Button::B));
+    }
+
+    #[test]
+    fn missing_file_gives_the_defaults() {
+        let path = env::temp_dir().join("rusty_boy_missing_keys.toml");
+
+        let bindings = KeyBindings::load_from(&path).unwrap();
+        assert_eq!(bindings.key(Button::A), "Z");
+        // A directory exists but cannot be read as a file
+        assert!(matches!(KeyBindings::load_from(&env::temp_dir()), Err(KeyBindingsError::Io(_))));
+    }
+}
@@ -0,0 +1,294 @@
+//! Helpers shared by the documentation launchers and other tools in `scripts/`.
+
+use std::env;
+use std::fs;
+use std::io::{self, IsTerminal, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Stdio};
+use std::time::Duration;
+
+use crate::config::Config;
+
+/// Environment variable naming the browser used to open documentation.
+pub const BROWSER_ENV: &str = "RUSTY_BOY_BROWSER";
+
+/// Flag understood by every launcher to skip opening a browser, for CI use.
+pub const NO_OPEN_FLAG: &str = "--no-open";
+
+/// Returns `true` if `command` can be found on the `PATH`.
+pub fn command_exists(command: &str) -> bool {
+    let finder = if cfg!(target_os = "windows") { "where" } else { "which" };
+    
+    Command::new(finder)
+        .arg(command)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+/// Returns the browser configured through [`BROWSER_ENV`], or else the
+/// `browser` key of the project [`Config`], if any.
+pub fn preferred_browser() -> Option<String> {
+    env::var(BROWSER_ENV)
+        .ok()
+        .or_else(|| Config::load().ok()?.browser)
+        .filter(|browser| !browser.trim().is_empty())
+}
+
+/// Opens `url` in the [preferred browser](preferred_browser), or with the
+/// operating system's default handler when none is configured.
+pub fn open_in_browser(url: &str) -> io::Result<()> {
+    if let Some(browser) = preferred_browser() {
+        return open_with(&browser, url);
+    }
+    
+    let status = if cfg!(target_os = "macos") {
+        Command::new("open").arg(url).status()?
+    } else if cfg!(target_os = "linux") {
+        Command::new("xdg-open").arg(url).status()?
+    } else if cfg!(target_os = "windows") {
+        Command::new("cmd").args(["/C", "start", url]).status()?
+    } else {
+        return Err(io::Error::new(io::ErrorKind::Unsupported, "unsupported operating system"));
+    };
+    
+    check_status(status)
+}
+
+/// Opens `url` with a specific application: an application name for
+/// `open -a` on macOS, or the browser executable elsewhere.
+pub fn open_with(app: &str, url: &str) -> io::Result<()> {
+    let status = if cfg!(target_os = "macos") {
+        Command::new("open").args(["-a", app, url]).status()?
+    } else {
+        Command::new(app).arg(url).status()?
+    };
+    
+    check_status(status)
+}
+
+fn check_status(status: ExitStatus) -> io::Result<()> {
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!("browser command exited with {}", status)))
+    }
+}
+
+/// Returns the first port at or above `start_port` that can be bound on
+/// localhost.
+///
+/// The probing listener is dropped before returning so the caller (usually
+/// `mdbook serve`) can bind the port itself.
+pub fn find_available_port(start_port: u16) -> io::Result<u16> {
+    for port in start_port..=u16::MAX {
+        if !port_is_in_use(port) {
+            return Ok(port);
+        }
+        crate::warn!("⚠️  Port {} is in use, trying next port...", port);
+    }
+    
+    Err(io::Error::new(io::ErrorKind::AddrInUse, "no available ports found"))
+}
+
+/// Removes every occurrence of `flag` from `args`, returning whether it was
+/// present.
+pub fn take_flag(args: &mut Vec<String>, flag: &str) -> bool {
+    let before = args.len();
+    args.retain(|arg| arg != flag);
+    args.len() != before
+}
+
+/// Parses a `--port <N>` option out of the command-line `args`.
+///
+/// Returns `Ok(None)` when the flag is absent and an error message when the
+/// value is missing or not a valid port number.
+pub fn parse_port_arg(args: &[String]) -> Result<Option<u16>, String> {
+    let Some(index) = args.iter().position(|arg| arg == "--port") else {
+        return Ok(None);
+    };
+    
+    match args.get(index + 1) {
+        Some(value) => value
+            .parse::<u16>()
+            .map(Some)
+            .map_err(|_| format!("Invalid port number: {}", value)),
+        None => Err("Missing value for --port".to_string()),
+    }
+}
+
+/// Asks a yes/no question on stdin, defaulting to "no" when stdin is not a
+/// terminal so scripted runs never block.
+pub fn confirm(question: &str) -> bool {
+    if !io::stdin().is_terminal() {
+        return false;
+    }
+
+    print!("{} [y/N] ", question);
+    let _ = io::stdout().flush();
+
+    let mut answer = String::new();
+    if io::stdin().read_line(&mut answer).is_err() {
+        return false;
+    }
+    matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+/// Recursively collects every `.gb` file under `dir`.
+pub fn collect_roms(dir: &Path, roms: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+
+    for path in entries.flatten().map(|entry| entry.path()) {
+        if path.is_dir() {
+            collect_roms(&path, roms);
+        } else if path.extension().is_some_and(|ext| ext == "gb") {
+            roms.push(path);
+        }
+    }
+}
+
+/// Returns `true` if `port` cannot be bound on localhost.
+pub fn port_is_in_use(port: u16) -> bool {
+    TcpListener::bind(("127.0.0.1", port)).is_err()
+}
+
+/// Returns `true` if an HTTP server on `localhost:port` answers `GET /` with a
+/// 200 status.
+///
+/// Every address `localhost` resolves to is tried, since mdbook may end up
+/// listening on IPv6 only.
+pub fn http_responds(port: u16) -> bool {
+    let Ok(addrs) = ("localhost", port).to_socket_addrs() else {
+        return false;
+    };
+    
+    addrs.into_iter().any(|addr| {
+        let Ok(mut stream) = TcpStream::connect_timeout(&addr, Duration::from_secs(1)) else {
+            return false;
+        };
+        let _ = stream.set_read_timeout(Some(Duration::from_secs(2)));
+        
+        let request = format!("GET / HTTP/1.0\r\nHost: localhost:{}\r\n\r\n", port);
+        if stream.write_all(request.as_bytes()).is_err() {
+            return false;
+        }
+        
+        let mut status_line = [0u8; 12];
+        match stream.read_exact(&mut status_line) {
+            Ok(()) => {
+                let status_line = String::from_utf8_lossy(&status_line);
+                status_line.starts_with("HTTP/1.") && status_line.ends_with(" 200")
+            }
+            Err(_) => false,
+        }
+    })
+}
+
+/// Reduces a program path to the bare executable name, as `ps` and
+/// `tasklist` report it.
+pub fn command_name(program: &str) -> String {
+    let name = Path::new(program).file_stem().map_or(program.into(), |stem| stem.to_string_lossy());
+    // Linux truncates command names to 15 characters
+    name.chars().take(15).collect()
+}
+
+/// Returns `true` if `reported`, the command name of a running process,
+/// belongs to a binary launched through `cargo run` and recorded as
+/// `recorded`.
+///
+/// On Unix cargo execs the binary in place, so its own name shows up instead
+/// of `recorded`. On Windows cargo stays the parent of the binary, and
+/// everywhere it holds the PID while the binary is still being built.
+pub fn command_matches(reported: &str, recorded: &str) -> bool {
+    let reported = command_name(reported);
+    reported == command_name(recorded) || reported == "cargo"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn take_flag_removes_every_occurrence() {
+        let mut list = args(&["launcher", "--no-open", "build", "--no-open"]);
+        assert!(take_flag(&mut list, "--no-open"));
+        assert_eq!(list, args(&["launcher", "build"]));
+        assert!(!take_flag(&mut list, "--no-open"));
+        assert_eq!(list, args(&["launcher", "build"]));
+    }
+
+    #[test]
+    fn parse_port_arg_accepts_valid_ports() {
+        assert_eq!(parse_port_arg(&args(&["launcher"])), Ok(None));
+        assert_eq!(parse_port_arg(&args(&["launcher", "--port", "3001"])), Ok(Some(3001)));
+        assert_eq!(parse_port_arg(&args(&["launcher", "--port", "65535"])), Ok(Some(65535)));
+    }
+
+    #[test]
+    fn parse_port_arg_rejects_invalid_ports() {
+        assert_eq!(parse_port_arg(&args(&["launcher", "--port"])), Err("Missing value for --port".to_string()));
+        assert_eq!(
+            parse_port_arg(&args(&["launcher", "--port", "abc"])),
+            Err("Invalid port number: abc".to_string())
+        );
+        assert_eq!(
+            parse_port_arg(&args(&["launcher", "--port", "65536"])),
+            Err("Invalid port number: 65536".to_string())
+        );
+        assert_eq!(parse_port_arg(&args(&["launcher", "--port", "-1"])), Err("Invalid port number: -1".to_string()));
+    }
+
+    #[test]
+    fn find_available_port_skips_an_occupied_port() {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).expect("bind an ephemeral port");
+        let occupied = listener.local_addr().expect("local address").port();
+
+        assert!(port_is_in_use(occupied));
+        let port = find_available_port(occupied).expect("a free port");
+        assert!(port > occupied);
+        assert!(!port_is_in_use(port));
+    }
+
+    #[test]
+    fn command_name_strips_directories_and_extensions() {
+        assert_eq!(command_name("/usr/bin/cargo"), "cargo");
+        assert_eq!(command_name("cargo.exe"), "cargo");
+        assert_eq!(command_name("launch-pandocs"), "launch-pandocs");
+        assert_eq!(command_name("launch-all-docs-server"), "launch-all-docs");
+    }
+
+    #[test]
+    fn command_matches_the_binary_or_cargo() {
+        assert!(command_matches("launch-pandocs", "launch-pandocs"));
+        assert!(command_matches("launch-gb-ctr-b", "launch-gb-ctr-book"));
+        assert!(command_matches("cargo", "launch-pandocs"));
+        assert!(command_matches("cargo.exe", "launch-pandocs"));
+        assert!(!command_matches("launch-pandocs", "launch-gb-ctr-book"));
+        assert!(!command_matches("bash", "launch-pandocs"));
+    }
+
+    #[test]
+    fn collect_roms_finds_nested_gb_files() {
+        let dir = env::temp_dir().join(format!("rusty_boy_roms_{}", std::process::id()));
+        fs::create_dir_all(dir.join("cpu/instrs")).unwrap();
+        for file in ["top.gb", "cpu/instrs/01.gb", "cpu/readme.txt", "cpu/color.gbc"] {
+            fs::write(dir.join(file), []).unwrap();
+        }
+
+        let mut roms = Vec::new();
+        collect_roms(&dir, &mut roms);
+        collect_roms(&dir.join("missing"), &mut roms);
+        fs::remove_dir_all(&dir).unwrap();
+        roms.sort();
+        assert_eq!(roms, [dir.join("cpu/instrs/01.gb"), dir.join("top.gb")]);
+    }
+}
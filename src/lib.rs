@@ -0,0 +1,31 @@
+//! Rusty Boy, a Game Boy emulator written in Rust.
+//!
+//! The library also hosts the helpers shared by the development scripts under
+//! `scripts/`.
+
+pub mod apu;
+pub mod bus;
+pub mod cartridge;
+pub mod config;
+pub mod cpu;
+pub mod debugger;
+pub mod disasm;
+pub mod dma;
+pub mod emulator;
+pub mod interrupt;
+pub mod joypad;
+pub mod keys;
+pub mod launcher;
+pub mod limiter;
+pub mod log;
+pub mod ppu;
+pub mod recording;
+pub mod rewind;
+pub mod screenshot;
+pub mod serial;
+pub mod state;
+#[cfg(test)]
+mod testing;
+pub mod timer;
+#[cfg(feature = "wasm")]
+pub mod wasm;
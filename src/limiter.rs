@@ -0,0 +1,102 @@
+//! Frame pacing: sleeps away the rest of each frame's wall-clock budget so
+//! emulation runs at the speed of the real hardware, or a multiple of it.
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Frames per second of the DMG: 4194304 Hz over 70224 clocks per frame.
+pub const FRAME_RATE: f64 = 4_194_304.0 / 70_224.0;
+
+/// Speed multiplier that disables pacing, for fast-forward.
+pub const UNCAPPED: f64 = f64::INFINITY;
+
+/// How far emulation may fall behind before the limiter stops trying to
+/// catch up, e.g. after the process was suspended.
+const MAX_LAG: Duration = Duration::from_millis(100);
+
+/// Paces frames at [`FRAME_RATE`] times a speed multiplier.
+pub struct FrameLimiter {
+    speed: f64,
+    /// When the next frame is due, unset until the first frame.
+    deadline: Option<Instant>,
+}
+
+impl FrameLimiter {
+    pub fn new(speed: f64) -> FrameLimiter {
+        FrameLimiter { speed, deadline: None }
+    }
+
+    pub fn speed(&self) -> f64 {
+        self.speed
+    }
+
+    /// Sets the speed multiplier, e.g. 2.0 for double speed, 0.5 for half
+    /// speed or [`UNCAPPED`]. Non-positive values are ignored.
+    pub fn set_speed(&mut self, speed: f64) {
+        if speed > 0.0 {
+            self.speed = speed;
+            self.deadline = None;
+        }
+    }
+
+    /// Returns the wall-clock duration of a frame at the current speed, or
+    /// `None` when uncapped.
+    pub fn frame_duration(&self) -> Option<Duration> {
+        self.speed.is_finite().then(|| Duration::from_secs_f64(1.0 / (FRAME_RATE * self.speed)))
+    }
+
+    /// Sleeps until the current frame's budget is used up. Call once per
+    /// emulated frame.
+    pub fn wait(&mut self) {
+        let Some(frame_duration) = self.frame_duration() else {
+            return;
+        };
+
+        let now = Instant::now();
+        let deadline = match self.deadline {
+            Some(deadline) if now < deadline + MAX_LAG => deadline,
+            _ => now,
+        };
+        if now < deadline {
+            thread::sleep(deadline - now);
+        }
+        // Deadlines advance by exact frame durations so sleep overshoot does
+        // not accumulate
+        self.deadline = Some(deadline + frame_duration);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame_duration_follows_the_speed() {
+        let mut limiter = FrameLimiter::new(1.0);
+        let real_time = limiter.frame_duration().unwrap();
+        assert!((real_time.as_secs_f64() - 1.0 / 59.7275).abs() < 1e-6);
+
+        limiter.set_speed(2.0);
+        assert!((limiter.frame_duration().unwrap().as_secs_f64() - real_time.as_secs_f64() / 2.0).abs() < 1e-9);
+        limiter.set_speed(UNCAPPED);
+        assert_eq!(limiter.frame_duration(), None);
+    }
+
+    #[test]
+    fn non_positive_speeds_are_ignored() {
+        let mut limiter = FrameLimiter::new(2.0);
+        limiter.set_speed(0.0);
+        limiter.set_speed(-1.0);
+        assert_eq!(limiter.speed(), 2.0);
+    }
+
+    #[test]
+    fn uncapped_frames_do_not_wait() {
+        let mut limiter = FrameLimiter::new(UNCAPPED);
+        let start = Instant::now();
+        for _ in 0..1000 {
+            limiter.wait();
+        }
+        assert!(start.elapsed() < Duration::from_millis(100));
+    }
+}
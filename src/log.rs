@@ -0,0 +1,157 @@
+//! Output helpers shared by the binaries in `scripts/`.
+//!
+//! Every binary understands [`QUIET_FLAG`] (errors only) and [`VERBOSE_FLAG`]
+//! (also echo the external commands being run). Progress messages go through
+//! the [`info!`](crate::info) and [`warn!`](crate::warn) macros, errors keep
+//! using `eprintln!` so they are never silenced.
+
+use std::env;
+use std::process::Command;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use crate::launcher::take_flag;
+
+/// Flag limiting the output to errors.
+pub const QUIET_FLAG: &str = "--quiet";
+
+/// Flag echoing every external command before it runs.
+pub const VERBOSE_FLAG: &str = "--verbose";
+
+/// Environment variable carrying the verbosity to child binaries, e.g. the
+/// launchers started by `launch-all-docs`.
+pub const VERBOSITY_ENV: &str = "RUSTY_BOY_VERBOSITY";
+
+/// How much a binary prints, from least to most.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    Quiet,
+    Normal,
+    Verbose,
+}
+
+impl Verbosity {
+    /// Returns the name used in [`VERBOSITY_ENV`].
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Verbosity::Quiet => "quiet",
+            Verbosity::Normal => "normal",
+            Verbosity::Verbose => "verbose",
+        }
+    }
+
+    fn from_str(name: &str) -> Option<Self> {
+        match name.trim() {
+            "quiet" => Some(Verbosity::Quiet),
+            "normal" => Some(Verbosity::Normal),
+            "verbose" => Some(Verbosity::Verbose),
+            _ => None,
+        }
+    }
+}
+
+static VERBOSITY: AtomicU8 = AtomicU8::new(Verbosity::Normal as u8);
+
+/// Returns the current verbosity.
+pub fn verbosity() -> Verbosity {
+    match VERBOSITY.load(Ordering::Relaxed) {
+        0 => Verbosity::Quiet,
+        1 => Verbosity::Normal,
+        _ => Verbosity::Verbose,
+    }
+}
+
+/// Sets the current verbosity.
+pub fn set_verbosity(level: Verbosity) {
+    VERBOSITY.store(level as u8, Ordering::Relaxed);
+}
+
+/// Returns `true` if messages of `level` should be printed.
+pub fn enabled(level: Verbosity) -> bool {
+    verbosity() >= level
+}
+
+/// Removes the verbosity flags from `args` and sets the verbosity accordingly,
+/// falling back to [`VERBOSITY_ENV`] when neither flag is given.
+///
+/// Exits the process when both flags are given.
+pub fn init_from_args(args: &mut Vec<String>) {
+    let quiet = take_flag(args, QUIET_FLAG);
+    let verbose = take_flag(args, VERBOSE_FLAG);
+
+    let level = match (quiet, verbose) {
+        (true, true) => {
+            eprintln!("❌ {} and {} cannot be combined", QUIET_FLAG, VERBOSE_FLAG);
+            std::process::exit(1);
+        }
+        (true, false) => Verbosity::Quiet,
+        (false, true) => Verbosity::Verbose,
+        (false, false) => env::var(VERBOSITY_ENV)
+            .ok()
+            .and_then(|name| Verbosity::from_str(&name))
+            .unwrap_or(Verbosity::Normal),
+    };
+    set_verbosity(level);
+}
+
+/// Echoes commands on stderr before they run when the verbosity is
+/// [`Verbosity::Verbose`].
+pub trait LogCommand {
+    /// Prints the command line and returns `self` for chaining, e.g.
+    /// `Command::new("git").arg("status").logged().status()`.
+    fn logged(&mut self) -> &mut Self;
+}
+
+impl LogCommand for Command {
+    fn logged(&mut self) -> &mut Self {
+        if enabled(Verbosity::Verbose) {
+            let mut line = quote(&self.get_program().to_string_lossy());
+            for arg in self.get_args() {
+                line.push(' ');
+                line.push_str(&quote(&arg.to_string_lossy()));
+            }
+            match self.get_current_dir() {
+                Some(dir) => eprintln!("$ {}  (in {})", line, dir.display()),
+                None => eprintln!("$ {}", line),
+            }
+        }
+        self
+    }
+}
+
+fn quote(word: &str) -> String {
+    if word.is_empty() || word.contains(char::is_whitespace) {
+        format!("'{}'", word)
+    } else {
+        word.to_string()
+    }
+}
+
+/// Prints a progress message on stdout unless the output is quiet.
+#[macro_export]
+macro_rules! info {
+    ($($arg:tt)*) => {
+        if $crate::log::enabled($crate::log::Verbosity::Normal) {
+            println!($($arg)*);
+        }
+    };
+}
+
+/// Prints a warning on stderr unless the output is quiet.
+#[macro_export]
+macro_rules! warn {
+    ($($arg:tt)*) => {
+        if $crate::log::enabled($crate::log::Verbosity::Normal) {
+            eprintln!($($arg)*);
+        }
+    };
+}
+
+/// Prints a diagnostic message on stderr only when the output is verbose.
+#[macro_export]
+macro_rules! verbose {
+    ($($arg:tt)*) => {
+        if $crate::log::enabled($crate::log::Verbosity::Verbose) {
+            eprintln!($($arg)*);
+        }
+    };
+}
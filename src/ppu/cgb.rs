@@ -0,0 +1,148 @@
+//! Game Boy Color palette memory, following the "LCD Color Palettes (CGB
+//! only)" chapter of Pan Docs.
+//!
+//! Each of the two palette memories holds eight palettes of four colors,
+//! stored as little-endian 15-bit RGB555 values and accessed one byte at a
+//! time through an index register (BCPS/OCPS) and a data register
+//! (BCPD/OCPD).
+
+use serde::{Deserialize, Serialize};
+
+use crate::state::byte_array;
+
+const PALETTE_MEMORY_SIZE: usize = 64;
+
+/// Set in the index register to advance it after every data write.
+const SPEC_AUTO_INCREMENT: u8 = 0x80;
+const SPEC_INDEX: u8 = 0x3F;
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct ColorPalettes {
+    #[serde(with = "byte_array")]
+    memory: [u8; PALETTE_MEMORY_SIZE],
+    /// The index register: the byte addressed by the data register and the
+    /// auto-increment bit.
+    spec: u8,
+}
+
+impl ColorPalettes {
+    /// Returns palette memory filled with `value`. The CGB boot ROM leaves
+    /// every background color white.
+    pub(super) fn filled(value: u8) -> ColorPalettes {
+        ColorPalettes { memory: [value; PALETTE_MEMORY_SIZE], spec: 0 }
+    }
+
+    pub(super) fn read_spec(&self) -> u8 {
+        // Bit 6 is unused and reads as 1
+        0x40 | self.spec
+    }
+
+    pub(super) fn write_spec(&mut self, value: u8) {
+        self.spec = value & (SPEC_AUTO_INCREMENT | SPEC_INDEX);
+    }
+
+    pub(super) fn read_data(&self) -> u8 {
+        self.memory[(self.spec & SPEC_INDEX) as usize]
+    }
+
+    pub(super) fn write_data(&mut self, value: u8) {
+        self.memory[(self.spec & SPEC_INDEX) as usize] = value;
+        if self.spec & SPEC_AUTO_INCREMENT != 0 {
+            self.spec = SPEC_AUTO_INCREMENT | (self.spec.wrapping_add(1) & SPEC_INDEX);
+        }
+    }
+
+    /// Returns color `color` (0–3) of palette `palette` (0–7) as RGBA.
+    pub(super) fn rgba(&self, palette: u8, color: u8) -> [u8; 4] {
+        let offset = (palette as usize & 0x07) * 8 + color as usize * 2;
+        let rgb555 = u16::from_le_bytes([self.memory[offset], self.memory[offset + 1]]);
+        // Scale each 5-bit channel to 8 bits by repeating its upper bits
+        let channel = |shift: u16| {
+            let value = ((rgb555 >> shift) & 0x1F) as u8;
+            value << 3 | value >> 2
+        };
+        [channel(0), channel(5), channel(10), 0xFF]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ppu::{LCDC_BG_ENABLE, LCDC_TILE_DATA, Ppu};
+
+    #[test]
+    fn data_writes_auto_increment_the_index() {
+        let mut palettes = ColorPalettes::filled(0x00);
+        palettes.write_spec(SPEC_AUTO_INCREMENT | 0x3E);
+        for value in [0x11, 0x22, 0x33] {
+            palettes.write_data(value);
+        }
+
+        // The index wraps around from 0x3F to 0x00
+        assert_eq!(palettes.read_spec(), 0xC1);
+        for (index, value) in [(0x3E, 0x11), (0x3F, 0x22), (0x00, 0x33)] {
+            palettes.write_spec(index);
+            assert_eq!(palettes.read_data(), value, "index {:#04X}", index);
+        }
+    }
+
+    #[test]
+    fn index_stays_without_auto_increment() {
+        let mut palettes = ColorPalettes::filled(0x00);
+        palettes.write_spec(0x05);
+        palettes.write_data(0x11);
+        palettes.write_data(0x22);
+
+        assert_eq!(palettes.read_spec(), 0x45);
+        assert_eq!(palettes.read_data(), 0x22);
+    }
+
+    #[test]
+    fn reads_do_not_increment_the_index() {
+        let mut palettes = ColorPalettes::filled(0x00);
+        palettes.write_spec(SPEC_AUTO_INCREMENT | 0x10);
+        palettes.read_data();
+
+        assert_eq!(palettes.read_spec(), 0xD0);
+    }
+
+    #[test]
+    fn converts_rgb555_to_rgba() {
+        let mut palettes = ColorPalettes::filled(0x00);
+        palettes.write_spec(SPEC_AUTO_INCREMENT | 0x08);
+        for color in [0x7FFF_u16, 0x001F, 0x03E0, 0x7C00] {
+            for byte in color.to_le_bytes() {
+                palettes.write_data(byte);
+            }
+        }
+        palettes.write_spec(SPEC_AUTO_INCREMENT | 0x3E);
+        for byte in 0x4210_u16.to_le_bytes() {
+            palettes.write_data(byte);
+        }
+
+        let colors: Vec<[u8; 4]> = (0..4).map(|color| palettes.rgba(1, color)).collect();
+        assert_eq!(
+            colors,
+            [[0xFF, 0xFF, 0xFF, 0xFF], [0xFF, 0x00, 0x00, 0xFF], [0x00, 0xFF, 0x00, 0xFF], [0x00, 0x00, 0xFF, 0xFF]]
+        );
+        // A channel value of 16 scales to 0x84
+        assert_eq!(palettes.rgba(7, 3), [0x84, 0x84, 0x84, 0xFF]);
+        assert_eq!(palettes.rgba(0, 0), [0x00, 0x00, 0x00, 0xFF]);
+    }
+
+    #[test]
+    fn background_uses_the_color_palette() {
+        let mut ppu = Ppu::new(true);
+        // Colors 0 and 1 of every row
+        ppu.write_tile(1, &[0x0F, 0x00].repeat(8).try_into().unwrap());
+        ppu.write(0x9800, 1);
+        ppu.write(0xFF68, SPEC_AUTO_INCREMENT);
+        for byte in [0x1F, 0x00, 0x00, 0x7C] {
+            ppu.write(0xFF69, byte);
+        }
+        ppu.render_lines(LCDC_TILE_DATA | LCDC_BG_ENABLE, 1);
+
+        assert_eq!(ppu.pixel(0, 0), [0xFF, 0x00, 0x00, 0xFF]);
+        assert_eq!(ppu.pixel(4, 0), [0x00, 0x00, 0xFF, 0xFF]);
+    }
+}
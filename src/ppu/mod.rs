@@ -0,0 +1,703 @@
+//! The picture processing unit: video RAM, OAM, the LCD registers and the
+//! scanline renderer.
+//!
+//! Each of the 154 lines of a frame lasts 456 dots (one dot per clock tick,
+//! four per M-cycle). Visible lines 0–143 go through OAM scan (mode 2),
+//! drawing (mode 3) and HBlank (mode 0); lines 144–153 are VBlank (mode 1).
+//! A whole line is rendered when drawing ends, which is enough for games
+//! that only change registers between lines.
+//!
+//! Each line layers the scrolled background, the window and up to ten
+//! objects (see `sprites`).
+//!
+//! In CGB mode VRAM has a second bank, holding extra tile data and the
+//! attributes of each tile map entry, and colors come from the palette
+//! memories in `cgb` instead of BGP, OBP0 and OBP1.
+
+mod cgb;
+mod sprites;
+mod tiles;
+
+use serde::{Deserialize, Serialize};
+
+use crate::interrupt::Interrupt;
+use crate::state::{boxed_byte_array, byte_array};
+
+use cgb::ColorPalettes;
+
+pub use tiles::{TILES_PER_BANK, TILES_PER_ROW, TileSheet};
+
+pub const SCREEN_WIDTH: usize = 160;
+pub const SCREEN_HEIGHT: usize = 144;
+
+/// RGBA colors of the four DMG shades, from lightest to darkest.
+pub const DMG_SHADES: [[u8; 4]; 4] = [
+    [0xFF, 0xFF, 0xFF, 0xFF],
+    [0xAA, 0xAA, 0xAA, 0xFF],
+    [0x55, 0x55, 0x55, 0xFF],
+    [0x00, 0x00, 0x00, 0xFF],
+];
+
+const VRAM_BANK_SIZE: usize = 0x2000;
+/// Both VRAM banks; the DMG only uses the first.
+const VRAM_SIZE: usize = VRAM_BANK_SIZE * 2;
+const OAM_SIZE: usize = 0xA0;
+
+const DOTS_PER_LINE: u32 = 456;
+const LINES_PER_FRAME: u8 = 154;
+const OAM_SCAN_DOTS: u32 = 80;
+const DRAWING_DOTS: u32 = 172;
+/// Dots in a whole frame, used to keep time while the LCD is off.
+const DOTS_PER_FRAME: u32 = DOTS_PER_LINE * LINES_PER_FRAME as u32;
+
+const LCDC_ENABLE: u8 = 0x80;
+const LCDC_WINDOW_TILE_MAP: u8 = 0x40;
+const LCDC_WINDOW_ENABLE: u8 = 0x20;
+const LCDC_BG_TILE_MAP: u8 = 0x08;
+const LCDC_TILE_DATA: u8 = 0x10;
+const LCDC_OBJ_SIZE: u8 = 0x04;
+const LCDC_OBJ_ENABLE: u8 = 0x02;
+const LCDC_BG_ENABLE: u8 = 0x01;
+
+const STAT_LYC_SELECT: u8 = 0x40;
+const STAT_OAM_SELECT: u8 = 0x20;
+const STAT_VBLANK_SELECT: u8 = 0x10;
+const STAT_HBLANK_SELECT: u8 = 0x08;
+const STAT_COINCIDENCE: u8 = 0x04;
+
+/// WX values above this put the window past the right edge of the screen.
+const WINDOW_X_MAX: u8 = 166;
+
+/// CGB tile map attributes, stored in VRAM bank 1 at the tile's map entry.
+const MAP_ATTR_PALETTE: u8 = 0x07;
+const MAP_ATTR_BANK: u8 = 0x08;
+const MAP_ATTR_X_FLIP: u8 = 0x20;
+const MAP_ATTR_Y_FLIP: u8 = 0x40;
+const MAP_ATTR_PRIORITY: u8 = 0x80;
+
+/// The PPU mode reported in the lower bits of STAT.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Mode {
+    HBlank = 0,
+    VBlank = 1,
+    OamScan = 2,
+    Drawing = 3,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Ppu {
+    /// Enables the CGB registers, VRAM bank 1 and color rendering.
+    cgb: bool,
+    #[serde(with = "boxed_byte_array")]
+    vram: Box<[u8; VRAM_SIZE]>,
+    /// VRAM bank mapped at `0x8000..=0x9FFF` (VBK), always 0 on the DMG.
+    vram_bank: u8,
+    #[serde(with = "byte_array")]
+    oam: [u8; OAM_SIZE],
+    lcdc: u8,
+    /// Interrupt select bits of STAT; the mode and coincidence bits are
+    /// computed on read.
+    stat: u8,
+    /// The internal STAT interrupt line: the OR of every selected condition.
+    /// An interrupt is only requested when it goes from low to high, so a
+    /// condition that starts while another one holds the line high is lost.
+    stat_line: bool,
+    scy: u8,
+    scx: u8,
+    ly: u8,
+    lyc: u8,
+    bgp: u8,
+    obp0: u8,
+    obp1: u8,
+    wy: u8,
+    wx: u8,
+    /// Background palettes (BCPS/BCPD).
+    bg_palettes: ColorPalettes,
+    /// Object palettes (OCPS/OCPD).
+    obj_palettes: ColorPalettes,
+    /// The window's own line counter, which only advances on lines where the
+    /// window was drawn.
+    window_line: u8,
+    /// Set once LY has matched WY this frame; the window stays eligible
+    /// until the next frame even if WY changes afterwards.
+    window_y_triggered: bool,
+    mode: Mode,
+    /// Dots elapsed in the current line, or in the current frame while the
+    /// LCD is off.
+    dots: u32,
+    frame_count: u64,
+    framebuffer: Vec<u8>,
+}
+
+impl Default for Ppu {
+    fn default() -> Ppu {
+        Ppu::new(false)
+    }
+}
+
+impl Ppu {
+    /// Returns a PPU in CGB mode if `cgb` is set, or in DMG mode otherwise.
+    pub fn new(cgb: bool) -> Ppu {
+        Ppu {
+            cgb,
+            vram: Box::new([0; VRAM_SIZE]),
+            vram_bank: 0,
+            oam: [0; OAM_SIZE],
+            lcdc: 0,
+            stat: 0,
+            stat_line: false,
+            scy: 0,
+            scx: 0,
+            ly: 0,
+            lyc: 0,
+            bgp: 0,
+            obp0: 0,
+            obp1: 0,
+            wy: 0,
+            wx: 0,
+            bg_palettes: ColorPalettes::filled(0xFF),
+            obj_palettes: ColorPalettes::filled(0x00),
+            window_line: 0,
+            window_y_triggered: false,
+            mode: Mode::HBlank,
+            dots: 0,
+            frame_count: 0,
+            framebuffer: vec![0xFF; SCREEN_WIDTH * SCREEN_HEIGHT * 4],
+        }
+    }
+
+    /// The last rendered frame, as 160×144 RGBA pixels in row-major order.
+    pub fn framebuffer(&self) -> &[u8] {
+        &self.framebuffer
+    }
+
+    /// Returns the number of frames started since power-on. It keeps
+    /// counting while the LCD is off so frame-based loops still advance.
+    pub fn frame_count(&self) -> u64 {
+        self.frame_count
+    }
+
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    pub fn ly(&self) -> u8 {
+        self.ly
+    }
+
+    /// Advances the PPU by one M-cycle. Returns the interrupt bits to
+    /// request in IF.
+    pub fn tick(&mut self) -> u8 {
+        if self.lcdc & LCDC_ENABLE == 0 {
+            self.dots += 4;
+            if self.dots >= DOTS_PER_FRAME {
+                self.dots -= DOTS_PER_FRAME;
+                self.frame_count += 1;
+            }
+            return 0;
+        }
+
+        let mut interrupts = 0;
+        for _ in 0..4 {
+            interrupts |= self.tick_dot();
+        }
+        interrupts
+    }
+
+    fn tick_dot(&mut self) -> u8 {
+        let mut interrupts = 0;
+        self.dots += 1;
+        if self.dots == DOTS_PER_LINE {
+            self.dots = 0;
+            self.ly = (self.ly + 1) % LINES_PER_FRAME;
+        }
+
+        let mode = if self.ly as usize >= SCREEN_HEIGHT {
+            Mode::VBlank
+        } else if self.dots < OAM_SCAN_DOTS {
+            Mode::OamScan
+        } else if self.dots < OAM_SCAN_DOTS + DRAWING_DOTS {
+            Mode::Drawing
+        } else {
+            Mode::HBlank
+        };
+        if mode != self.mode {
+            self.mode = mode;
+            match mode {
+                Mode::HBlank => self.render_line(),
+                Mode::VBlank => {
+                    self.frame_count += 1;
+                    self.window_line = 0;
+                    self.window_y_triggered = false;
+                    interrupts |= Interrupt::VBlank.bit();
+                }
+                Mode::OamScan | Mode::Drawing => {}
+            }
+        }
+        interrupts | self.update_stat_line()
+    }
+
+    /// Recomputes the STAT interrupt line. Returns the STAT interrupt bit if
+    /// it went high.
+    fn update_stat_line(&mut self) -> u8 {
+        let mode_select = match self.mode {
+            Mode::HBlank => STAT_HBLANK_SELECT,
+            Mode::VBlank => STAT_VBLANK_SELECT,
+            Mode::OamScan => STAT_OAM_SELECT,
+            Mode::Drawing => 0,
+        };
+        let line = self.lcdc & LCDC_ENABLE != 0
+            && (self.stat & mode_select != 0 || (self.ly == self.lyc && self.stat & STAT_LYC_SELECT != 0));
+        let rising = line && !self.stat_line;
+        self.stat_line = line;
+        if rising { Interrupt::Stat.bit() } else { 0 }
+    }
+
+    /// Writes STAT. Returns the interrupt bits to request in IF.
+    ///
+    /// On the DMG, the write selects every source but OAM scan for one cycle
+    /// before taking effect, so writing STAT during HBlank, VBlank or while
+    /// LY equals LYC requests an interrupt if the line was low.
+    pub fn write_stat(&mut self, value: u8) -> u8 {
+        let mut interrupts = 0;
+        if !self.cgb {
+            self.stat = STAT_LYC_SELECT | STAT_VBLANK_SELECT | STAT_HBLANK_SELECT;
+            interrupts |= self.update_stat_line();
+        }
+        self.stat = value & 0x78;
+        interrupts | self.update_stat_line()
+    }
+
+    pub fn read(&self, addr: u16) -> u8 {
+        match addr {
+            0x8000..=0x9FFF => self.vram[self.vram_index(addr)],
+            0xFE00..=0xFE9F => self.oam[(addr - 0xFE00) as usize],
+            0xFF40 => self.lcdc,
+            0xFF41 => {
+                let coincidence = if self.ly == self.lyc { STAT_COINCIDENCE } else { 0 };
+                0x80 | self.stat | coincidence | self.mode as u8
+            }
+            0xFF42 => self.scy,
+            0xFF43 => self.scx,
+            0xFF44 => self.ly,
+            0xFF45 => self.lyc,
+            0xFF47 => self.bgp,
+            0xFF48 => self.obp0,
+            0xFF49 => self.obp1,
+            0xFF4A => self.wy,
+            0xFF4B => self.wx,
+            0xFF4F if self.cgb => 0xFE | self.vram_bank,
+            0xFF68 if self.cgb => self.bg_palettes.read_spec(),
+            0xFF69 if self.cgb => self.bg_palettes.read_data(),
+            0xFF6A if self.cgb => self.obj_palettes.read_spec(),
+            0xFF6B if self.cgb => self.obj_palettes.read_data(),
+            _ => 0xFF,
+        }
+    }
+
+    pub fn write(&mut self, addr: u16, value: u8) {
+        match addr {
+            0x8000..=0x9FFF => self.vram[self.vram_index(addr)] = value,
+            0xFE00..=0xFE9F => self.oam[(addr - 0xFE00) as usize] = value,
+            0xFF40 => {
+                let was_enabled = self.lcdc & LCDC_ENABLE != 0;
+                self.lcdc = value;
+                if was_enabled && value & LCDC_ENABLE == 0 {
+                    // Turning the LCD off resets it to the top of the screen
+                    self.ly = 0;
+                    self.dots = 0;
+                    self.mode = Mode::HBlank;
+                    self.stat_line = false;
+                    self.window_line = 0;
+                    self.window_y_triggered = false;
+                } else if !was_enabled && value & LCDC_ENABLE != 0 {
+                    self.dots = 0;
+                    self.mode = Mode::OamScan;
+                }
+            }
+            0xFF42 => self.scy = value,
+            0xFF43 => self.scx = value,
+            // LY is read-only
+            0xFF44 => {}
+            0xFF45 => self.lyc = value,
+            0xFF47 => self.bgp = value,
+            0xFF48 => self.obp0 = value,
+            0xFF49 => self.obp1 = value,
+            0xFF4A => self.wy = value,
+            0xFF4B => self.wx = value,
+            0xFF4F if self.cgb => self.vram_bank = value & 0x01,
+            0xFF68 if self.cgb => self.bg_palettes.write_spec(value),
+            0xFF69 if self.cgb => self.bg_palettes.write_data(value),
+            0xFF6A if self.cgb => self.obj_palettes.write_spec(value),
+            0xFF6B if self.cgb => self.obj_palettes.write_data(value),
+            _ => {}
+        }
+    }
+
+    /// Returns the offset in `vram` of `addr` in the mapped bank.
+    fn vram_index(&self, addr: u16) -> usize {
+        self.vram_bank as usize * VRAM_BANK_SIZE + (addr - 0x8000) as usize
+    }
+
+    /// Renders line LY into the framebuffer.
+    fn render_line(&mut self) {
+        if self.ly == self.wy {
+            self.window_y_triggered = true;
+        }
+
+        // On the DMG, clearing LCDC bit 0 blanks the background and window;
+        // in CGB mode it only takes their priority over objects away
+        let bg_master_priority = self.lcdc & LCDC_BG_ENABLE != 0;
+        let mut bg_pixels = [(0u8, 0u8); SCREEN_WIDTH];
+        if self.cgb || bg_master_priority {
+            let window_visible = self.lcdc & LCDC_WINDOW_ENABLE != 0
+                && self.window_y_triggered
+                && self.wx <= WINDOW_X_MAX;
+            // The window starts at WX - 7, which is off the left edge for WX < 7
+            let window_start = self.wx as i16 - 7;
+            for (x, pixel) in bg_pixels.iter_mut().enumerate() {
+                *pixel = if window_visible && x as i16 >= window_start {
+                    self.window_pixel((x as i16 - window_start) as u8)
+                } else {
+                    self.background_pixel(x as u8)
+                };
+            }
+            if window_visible {
+                self.window_line = self.window_line.wrapping_add(1);
+            }
+        }
+        let sprites = if self.lcdc & LCDC_OBJ_ENABLE != 0 { self.line_sprites() } else { Vec::new() };
+
+        let y = self.ly as usize;
+        for (x, &(bg_color, attributes)) in bg_pixels.iter().enumerate() {
+            let sprite = self.sprite_pixel(&sprites, x as u8).filter(|&(_, _, behind_bg)| {
+                let bg_priority = if self.cgb {
+                    bg_master_priority && (behind_bg || attributes & MAP_ATTR_PRIORITY != 0)
+                } else {
+                    behind_bg
+                };
+                !(bg_priority && bg_color != 0)
+            });
+
+            let rgba = match (sprite, self.cgb) {
+                (Some((color, palette, _)), true) => self.obj_palettes.rgba(palette, color),
+                (Some((color, palette, _)), false) => DMG_SHADES[palette_shade(palette, color) as usize],
+                (None, true) => self.bg_palettes.rgba(attributes & MAP_ATTR_PALETTE, bg_color),
+                (None, false) => DMG_SHADES[palette_shade(self.bgp, bg_color) as usize],
+            };
+            let offset = (y * SCREEN_WIDTH + x) * 4;
+            self.framebuffer[offset..offset + 4].copy_from_slice(&rgba);
+        }
+    }
+
+    /// Returns the background color index (0–3, before the palette) and
+    /// CGB attributes at screen column `x` of the current line.
+    fn background_pixel(&self, x: u8) -> (u8, u8) {
+        let map_base = if self.lcdc & LCDC_BG_TILE_MAP != 0 { 0x1C00 } else { 0x1800 };
+        self.map_pixel(map_base, x.wrapping_add(self.scx), self.ly.wrapping_add(self.scy))
+    }
+
+    /// Returns the window color index (0–3, before the palette) and CGB
+    /// attributes at column `x` of the window, on the window's current line.
+    fn window_pixel(&self, x: u8) -> (u8, u8) {
+        let map_base = if self.lcdc & LCDC_WINDOW_TILE_MAP != 0 { 0x1C00 } else { 0x1800 };
+        self.map_pixel(map_base, x, self.window_line)
+    }
+
+    /// Returns the color index and CGB attributes of pixel (`x`, `y`) of the
+    /// 256×256 tile map at VRAM offset `map_base`. On the DMG the attributes
+    /// are always 0.
+    fn map_pixel(&self, map_base: usize, x: u8, y: u8) -> (u8, u8) {
+        let entry = map_base + (y as usize / 8) * 32 + x as usize / 8;
+        let tile = self.vram[entry];
+        if !self.cgb {
+            return (self.tile_pixel(tile, 0, x % 8, y % 8), 0);
+        }
+
+        let attributes = self.vram[VRAM_BANK_SIZE + entry];
+        let bank = if attributes & MAP_ATTR_BANK != 0 { 1 } else { 0 };
+        let column = if attributes & MAP_ATTR_X_FLIP != 0 { 7 - x % 8 } else { x % 8 };
+        let row = if attributes & MAP_ATTR_Y_FLIP != 0 { 7 - y % 8 } else { y % 8 };
+        (self.tile_pixel(tile, bank, column, row), attributes)
+    }
+
+    /// Returns the color index of pixel (`x`, `y`) of background tile `tile`
+    /// in VRAM bank `bank`, addressed through the tile data area selected by
+    /// LCDC bit 4.
+    fn tile_pixel(&self, tile: u8, bank: usize, x: u8, y: u8) -> u8 {
+        let tile_addr = if self.lcdc & LCDC_TILE_DATA != 0 {
+            tile as usize * 16
+        } else {
+            // Signed addressing relative to 0x9000
+            (0x1000 + (tile as i8 as isize) * 16) as usize
+        };
+        self.tile_data_pixel(bank * VRAM_BANK_SIZE + tile_addr, x, y)
+    }
+
+    /// Returns the color index of pixel (`x`, `y`) of the 2bpp tile stored at
+    /// VRAM offset `tile_addr`.
+    fn tile_data_pixel(&self, tile_addr: usize, x: u8, y: u8) -> u8 {
+        let row = tile_addr + y as usize * 2;
+        let bit = 7 - x;
+        let low = (self.vram[row] >> bit) & 0x01;
+        let high = (self.vram[row + 1] >> bit) & 0x01;
+        high << 1 | low
+    }
+}
+
+/// Maps color index `color` through the DMG palette register `palette`.
+fn palette_shade(palette: u8, color: u8) -> u8 {
+    (palette >> (color * 2)) & 0x03
+}
+
+#[cfg(test)]
+impl Ppu {
+    /// Turns the LCD on with `lcdc` and runs until lines `0..lines` have been
+    /// rendered.
+    pub(super) fn render_lines(&mut self, lcdc: u8, lines: u32) {
+        self.write(0xFF40, LCDC_ENABLE | lcdc);
+        for _ in 0..lines * DOTS_PER_LINE / 4 {
+            self.tick();
+        }
+    }
+
+    /// Writes the 16 bytes of tile `tile` in the `0x8000` addressing area.
+    pub(super) fn write_tile(&mut self, tile: u8, data: &[u8; 16]) {
+        for (offset, &byte) in data.iter().enumerate() {
+            self.write(0x8000 + tile as u16 * 16 + offset as u16, byte);
+        }
+    }
+
+    /// Returns the RGBA color of framebuffer pixel (`x`, `y`).
+    pub(super) fn pixel(&self, x: usize, y: usize) -> [u8; 4] {
+        let offset = (y * SCREEN_WIDTH + x) * 4;
+        self.framebuffer[offset..offset + 4].try_into().expect("4 bytes")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A tile whose columns are, from left to right, colors 3, 3, 1, 1, 2,
+    /// 2, 0 and 0 on every row.
+    const STRIPES: [u8; 16] = [0xF0, 0xCC, 0xF0, 0xCC, 0xF0, 0xCC, 0xF0, 0xCC, 0xF0, 0xCC, 0xF0, 0xCC, 0xF0, 0xCC, 0xF0, 0xCC];
+
+    fn shades(colors: &[usize]) -> Vec<[u8; 4]> {
+        colors.iter().map(|&color| DMG_SHADES[color]).collect()
+    }
+
+    #[test]
+    fn renders_a_background_tile() {
+        let mut ppu = Ppu::new(false);
+        ppu.write_tile(1, &STRIPES);
+        ppu.write(0x9800, 1);
+        ppu.write(0xFF47, 0xE4);
+        ppu.render_lines(LCDC_TILE_DATA | LCDC_BG_ENABLE, 1);
+
+        let line: Vec<[u8; 4]> = (0..9).map(|x| ppu.pixel(x, 0)).collect();
+        assert_eq!(line, shades(&[3, 3, 1, 1, 2, 2, 0, 0, 0]));
+        assert_eq!(ppu.pixel(0, 0), [0x00, 0x00, 0x00, 0xFF]);
+    }
+
+    #[test]
+    fn background_follows_bgp_and_scroll() {
+        let mut ppu = Ppu::new(false);
+        ppu.write_tile(1, &STRIPES);
+        ppu.write(0x9800, 1);
+        // Colors 1 and 2 swapped, 0 and 3 swapped
+        ppu.write(0xFF47, 0x1B);
+        ppu.write(0xFF43, 2);
+        ppu.render_lines(LCDC_TILE_DATA | LCDC_BG_ENABLE, 1);
+
+        let line: Vec<[u8; 4]> = (0..6).map(|x| ppu.pixel(x, 0)).collect();
+        assert_eq!(line, shades(&[2, 2, 1, 1, 3, 3]));
+    }
+
+    #[test]
+    fn background_uses_signed_tile_addressing() {
+        let mut ppu = Ppu::new(false);
+        // Tile -1 in the 0x8800 area is stored at 0x8FF0
+        for offset in 0..16 {
+            ppu.write(0x8FF0 + offset, STRIPES[offset as usize]);
+        }
+        ppu.write(0x9800, 0xFF);
+        ppu.write(0xFF47, 0xE4);
+        ppu.render_lines(LCDC_BG_ENABLE, 1);
+
+        assert_eq!(ppu.pixel(0, 0), DMG_SHADES[3]);
+        assert_eq!(ppu.pixel(4, 0), DMG_SHADES[2]);
+    }
+
+    #[test]
+    fn background_is_blank_when_disabled_on_dmg() {
+        let mut ppu = Ppu::new(false);
+        ppu.write_tile(1, &STRIPES);
+        ppu.write(0x9800, 1);
+        ppu.write(0xFF47, 0xE4);
+        ppu.render_lines(LCDC_TILE_DATA, 1);
+
+        assert_eq!(ppu.pixel(0, 0), DMG_SHADES[0]);
+    }
+
+    /// Fills the window map's first row with a tile whose row 0 is color 3
+    /// and whose other rows are color 1, then renders 12 lines.
+    fn render_window(wy: u8, wx: u8) -> Ppu {
+        let mut ppu = Ppu::new(false);
+        let mut tile = [0xFF, 0x00].repeat(8);
+        tile[1] = 0xFF;
+        ppu.write_tile(1, &tile.try_into().unwrap());
+        for column in 0..32 {
+            ppu.write(0x9C00 + column, 1);
+        }
+        ppu.write(0xFF47, 0xE4);
+        ppu.write(0xFF4A, wy);
+        ppu.write(0xFF4B, wx);
+        ppu.render_lines(LCDC_WINDOW_TILE_MAP | LCDC_WINDOW_ENABLE | LCDC_TILE_DATA | LCDC_BG_ENABLE, 12);
+        ppu
+    }
+
+    #[test]
+    fn window_starts_at_wy_and_wx_minus_7() {
+        let ppu = render_window(10, 27);
+
+        assert_eq!(ppu.pixel(20, 9), DMG_SHADES[0]);
+        assert_eq!(ppu.pixel(19, 10), DMG_SHADES[0]);
+        // The window's own first row is drawn on line WY
+        assert_eq!(ppu.pixel(20, 10), DMG_SHADES[3]);
+        assert_eq!(ppu.pixel(159, 10), DMG_SHADES[3]);
+        assert_eq!(ppu.pixel(20, 11), DMG_SHADES[1]);
+    }
+
+    #[test]
+    fn window_past_wx_166_is_hidden() {
+        let ppu = render_window(0, WINDOW_X_MAX + 1);
+
+        assert_eq!(ppu.pixel(159, 0), DMG_SHADES[0]);
+    }
+
+    #[test]
+    fn window_below_wx_7_is_shifted_off_the_left_edge() {
+        let mut ppu = Ppu::new(false);
+        ppu.write_tile(1, &STRIPES);
+        ppu.write(0x9800, 1);
+        ppu.write(0xFF47, 0xE4);
+        ppu.write(0xFF4B, 3);
+        ppu.render_lines(LCDC_WINDOW_ENABLE | LCDC_TILE_DATA | LCDC_BG_ENABLE, 1);
+
+        // The window starts at column -4, so its fifth column is drawn first
+        let line: Vec<[u8; 4]> = (0..4).map(|x| ppu.pixel(x, 0)).collect();
+        assert_eq!(line, shades(&[2, 2, 0, 0]));
+    }
+
+    /// Returns every `(LY, mode)` the PPU passes through from one VBlank
+    /// entry up to the next, and the number of dots in between.
+    fn record_frame(ppu: &mut Ppu) -> (Vec<(u8, Mode)>, usize) {
+        while ppu.tick_dot() & Interrupt::VBlank.bit() == 0 {}
+        let mut states = vec![(ppu.ly, ppu.mode)];
+        let mut dots = 0;
+        loop {
+            dots += 1;
+            let vblank = ppu.tick_dot() & Interrupt::VBlank.bit() != 0;
+            if vblank {
+                return (states, dots);
+            }
+            if states.last() != Some(&(ppu.ly, ppu.mode)) {
+                states.push((ppu.ly, ppu.mode));
+            }
+        }
+    }
+
+    #[test]
+    fn modes_follow_the_frame_timing() {
+        let mut ppu = Ppu::new(false);
+        ppu.write(0xFF40, LCDC_ENABLE);
+        let (states, dots) = record_frame(&mut ppu);
+
+        let vblank = (144..154).map(|ly| (ly, Mode::VBlank));
+        let visible = (0..144).flat_map(|ly| [(ly, Mode::OamScan), (ly, Mode::Drawing), (ly, Mode::HBlank)]);
+        assert_eq!(states, vblank.chain(visible).collect::<Vec<_>>());
+        assert_eq!(dots, 70224);
+    }
+
+    #[test]
+    fn stat_and_ly_report_the_current_line_and_mode() {
+        let mut ppu = Ppu::new(false);
+        ppu.write(0xFF40, LCDC_ENABLE);
+        record_frame(&mut ppu);
+        assert_eq!((ppu.read(0xFF44), ppu.read(0xFF41) & 0x03), (144, 1));
+
+        // Through the 10 VBlank lines to the first OAM scan of line 0
+        for _ in 0..10 * 456 / 4 {
+            ppu.tick();
+        }
+        assert_eq!((ppu.read(0xFF44), ppu.read(0xFF41) & 0x03), (0, 2));
+        for _ in 0..80 / 4 {
+            ppu.tick();
+        }
+        assert_eq!(ppu.read(0xFF41) & 0x03, 3);
+        for _ in 0..172 / 4 {
+            ppu.tick();
+        }
+        assert_eq!(ppu.read(0xFF41) & 0x03, 0);
+    }
+
+    /// Returns the `(LY, mode)` of every STAT interrupt over the first
+    /// `lines` lines after turning the LCD on.
+    fn stat_interrupts(ppu: &mut Ppu, lines: u32) -> Vec<(u8, Mode)> {
+        ppu.write(0xFF40, LCDC_ENABLE);
+        (0..lines * DOTS_PER_LINE)
+            .filter_map(|_| (ppu.tick_dot() & Interrupt::Stat.bit() != 0).then_some((ppu.ly, ppu.mode)))
+            .collect()
+    }
+
+    #[test]
+    fn stat_line_blocks_back_to_back_sources() {
+        let mut ppu = Ppu::new(false);
+        ppu.write(0xFF45, 5);
+        assert_eq!(ppu.write_stat(STAT_HBLANK_SELECT | STAT_LYC_SELECT), 0);
+
+        // HBlank of line 4 holds the line high into LY = LYC on line 5, which
+        // lasts through its HBlank
+        let lines = [0, 1, 2, 3, 4, 6, 7];
+        let expected: Vec<(u8, Mode)> = lines.iter().map(|&ly| (ly, Mode::HBlank)).collect();
+        assert_eq!(stat_interrupts(&mut ppu, 8), expected);
+    }
+
+    #[test]
+    fn lyc_interrupt_fires_when_the_line_was_low() {
+        let mut ppu = Ppu::new(false);
+        ppu.write(0xFF45, 5);
+        ppu.write_stat(STAT_LYC_SELECT);
+
+        assert_eq!(stat_interrupts(&mut ppu, 8), [(5, Mode::OamScan)]);
+    }
+
+    #[test]
+    fn stat_write_requests_an_interrupt_on_dmg_only() {
+        for (cgb, expected) in [(false, Interrupt::Stat.bit()), (true, 0)] {
+            let mut ppu = Ppu::new(cgb);
+            ppu.write(0xFF45, 100);
+            ppu.write(0xFF40, LCDC_ENABLE);
+            // Into the HBlank of line 0
+            for _ in 0..(OAM_SCAN_DOTS + DRAWING_DOTS) / 4 {
+                ppu.tick();
+            }
+            assert_eq!(ppu.mode(), Mode::HBlank);
+            assert_eq!(ppu.write_stat(0x00), expected, "CGB: {}", cgb);
+        }
+    }
+
+    #[test]
+    fn stat_write_during_drawing_requests_nothing() {
+        let mut ppu = Ppu::new(false);
+        ppu.write(0xFF45, 100);
+        ppu.write(0xFF40, LCDC_ENABLE);
+        for _ in 0..OAM_SCAN_DOTS / 4 + 1 {
+            ppu.tick();
+        }
+
+        assert_eq!(ppu.mode(), Mode::Drawing);
+        assert_eq!(ppu.write_stat(0x00), 0);
+    }
+}
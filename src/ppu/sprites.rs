@@ -0,0 +1,200 @@
+//! Object (sprite) selection and pixel lookup, following the "Object
+//! Attribute Memory" and "Object priority" chapters of Pan Docs.
+
+use super::{LCDC_OBJ_SIZE, Ppu, VRAM_BANK_SIZE};
+
+/// Most objects the PPU can show on one line.
+const MAX_SPRITES_PER_LINE: usize = 10;
+
+const ATTR_BEHIND_BG: u8 = 0x80;
+const ATTR_Y_FLIP: u8 = 0x40;
+const ATTR_X_FLIP: u8 = 0x20;
+const ATTR_PALETTE: u8 = 0x10;
+const ATTR_CGB_BANK: u8 = 0x08;
+const ATTR_CGB_PALETTE: u8 = 0x07;
+
+/// An OAM entry. `y` and `x` are stored with their hardware offsets of 16
+/// and 8, so an object at `x == 0` is entirely off-screen.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct Sprite {
+    y: u8,
+    x: u8,
+    tile: u8,
+    attributes: u8,
+}
+
+impl Ppu {
+    fn sprite_height(&self) -> u8 {
+        if self.lcdc & LCDC_OBJ_SIZE != 0 { 16 } else { 8 }
+    }
+
+    /// Returns the objects on line LY in drawing priority order.
+    ///
+    /// The first 10 objects in OAM that overlap the line are selected, even
+    /// those hidden at `x == 0`; among them, the one with the smaller X wins,
+    /// then the one earlier in OAM. In CGB mode only OAM order counts.
+    pub(super) fn line_sprites(&self) -> Vec<Sprite> {
+        let height = self.sprite_height();
+        let line = self.ly as u16 + 16;
+
+        let mut sprites: Vec<Sprite> = self
+            .oam
+            .chunks_exact(4)
+            .map(|entry| Sprite { y: entry[0], x: entry[1], tile: entry[2], attributes: entry[3] })
+            .filter(|sprite| line >= sprite.y as u16 && line < sprite.y as u16 + height as u16)
+            .take(MAX_SPRITES_PER_LINE)
+            .collect();
+        if !self.cgb {
+            // A stable sort keeps OAM order between objects sharing an X
+            sprites.sort_by_key(|sprite| sprite.x);
+        }
+        sprites
+    }
+
+    /// Returns the color index, palette and BG-priority bit of the
+    /// highest-priority opaque object pixel at screen column `x`, if any. The
+    /// palette is the OBP0 or OBP1 register, or the palette number in CGB
+    /// mode.
+    pub(super) fn sprite_pixel(&self, sprites: &[Sprite], x: u8) -> Option<(u8, u8, bool)> {
+        let height = self.sprite_height();
+        let screen_x = x as u16 + 8;
+
+        sprites.iter().find_map(|sprite| {
+            if screen_x < sprite.x as u16 || screen_x >= sprite.x as u16 + 8 {
+                return None;
+            }
+
+            let mut column = (screen_x - sprite.x as u16) as u8;
+            let mut row = (self.ly as u16 + 16 - sprite.y as u16) as u8;
+            if sprite.attributes & ATTR_X_FLIP != 0 {
+                column = 7 - column;
+            }
+            if sprite.attributes & ATTR_Y_FLIP != 0 {
+                row = height - 1 - row;
+            }
+
+            // Tall objects ignore bit 0 of the tile index
+            let tile = if height == 16 { sprite.tile & 0xFE } else { sprite.tile };
+            let bank = if self.cgb && sprite.attributes & ATTR_CGB_BANK != 0 { VRAM_BANK_SIZE } else { 0 };
+            let color = self.tile_data_pixel(bank + tile as usize * 16, column, row);
+            if color == 0 {
+                return None;
+            }
+
+            let palette = if self.cgb {
+                sprite.attributes & ATTR_CGB_PALETTE
+            } else if sprite.attributes & ATTR_PALETTE != 0 {
+                self.obp1
+            } else {
+                self.obp0
+            };
+            Some((color, palette, sprite.attributes & ATTR_BEHIND_BG != 0))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ppu::{DMG_SHADES, LCDC_BG_ENABLE, LCDC_OBJ_ENABLE, LCDC_TILE_DATA};
+
+    const LCDC: u8 = LCDC_OBJ_ENABLE | LCDC_TILE_DATA | LCDC_BG_ENABLE;
+
+    /// Returns a DMG PPU with identity palettes and tiles 1–3 filled with
+    /// colors 1–3.
+    fn ppu() -> Ppu {
+        let mut ppu = Ppu::new(false);
+        for color in 1..=3u8 {
+            let low = if color & 0x01 != 0 { 0xFF } else { 0x00 };
+            let high = if color & 0x02 != 0 { 0xFF } else { 0x00 };
+            let mut tile = [0; 16];
+            for row in tile.chunks_exact_mut(2) {
+                row.copy_from_slice(&[low, high]);
+            }
+            ppu.write_tile(color, &tile);
+        }
+        for register in [0xFF47, 0xFF48, 0xFF49] {
+            ppu.write(register, 0xE4);
+        }
+        ppu
+    }
+
+    /// Places object `index` on line 0 at screen column `x`.
+    fn place(ppu: &mut Ppu, index: u16, x: u8, tile: u8, attributes: u8) {
+        let entry = 0xFE00 + index * 4;
+        for (offset, value) in [16, x + 8, tile, attributes].into_iter().enumerate() {
+            ppu.write(entry + offset as u16, value);
+        }
+    }
+
+    #[test]
+    fn smaller_x_wins_on_dmg() {
+        let mut ppu = ppu();
+        place(&mut ppu, 0, 4, 2, 0);
+        place(&mut ppu, 1, 0, 3, 0);
+        ppu.render_lines(LCDC, 1);
+
+        assert_eq!(ppu.pixel(0, 0), DMG_SHADES[3]);
+        // Columns 4–7 overlap, where the object further left wins
+        assert_eq!(ppu.pixel(5, 0), DMG_SHADES[3]);
+        assert_eq!(ppu.pixel(9, 0), DMG_SHADES[2]);
+    }
+
+    #[test]
+    fn earlier_oam_entry_wins_at_the_same_x() {
+        let mut ppu = ppu();
+        place(&mut ppu, 0, 0, 1, 0);
+        place(&mut ppu, 1, 0, 2, 0);
+        ppu.render_lines(LCDC, 1);
+
+        assert_eq!(ppu.pixel(0, 0), DMG_SHADES[1]);
+    }
+
+    #[test]
+    fn only_oam_order_counts_on_cgb() {
+        let mut ppu = ppu();
+        ppu.cgb = true;
+        place(&mut ppu, 0, 4, 2, 0);
+        place(&mut ppu, 1, 0, 3, 0);
+
+        let sprites = ppu.line_sprites();
+        assert_eq!(ppu.sprite_pixel(&sprites, 5).map(|(color, _, _)| color), Some(2));
+    }
+
+    #[test]
+    fn at_most_10_objects_per_line() {
+        let mut ppu = ppu();
+        for index in 0..11 {
+            place(&mut ppu, index, index as u8 * 8, 3, 0);
+        }
+        ppu.render_lines(LCDC, 1);
+
+        assert_eq!(ppu.pixel(72, 0), DMG_SHADES[3]);
+        assert_eq!(ppu.pixel(80, 0), DMG_SHADES[0]);
+    }
+
+    #[test]
+    fn hidden_objects_count_towards_the_limit() {
+        let mut ppu = ppu();
+        for index in 0..10 {
+            // X = 0 is entirely off-screen
+            ppu.write(0xFE00 + index * 4, 16);
+        }
+        place(&mut ppu, 10, 0, 3, 0);
+        ppu.render_lines(LCDC, 1);
+
+        assert_eq!(ppu.pixel(0, 0), DMG_SHADES[0]);
+    }
+
+    #[test]
+    fn bg_over_obj_only_hides_behind_nonzero_colors() {
+        let mut ppu = ppu();
+        // Background tile 1 in the first column, tile 0 (color 0) elsewhere
+        ppu.write(0x9800, 1);
+        place(&mut ppu, 0, 4, 3, ATTR_BEHIND_BG);
+        ppu.render_lines(LCDC, 1);
+
+        assert_eq!(ppu.pixel(4, 0), DMG_SHADES[1]);
+        assert_eq!(ppu.pixel(8, 0), DMG_SHADES[3]);
+    }
+}
@@ -0,0 +1,92 @@
+//! Decoding the tile data in VRAM into a tile sheet, for inspecting
+//! graphics.
+
+use super::{DMG_SHADES, Ppu, VRAM_BANK_SIZE};
+
+/// Tiles in the `0x8000..=0x97FF` tile data area of one VRAM bank, covering
+/// both the `0x8000` and `0x8800` addressing blocks.
+pub const TILES_PER_BANK: usize = 384;
+/// Tiles in each row of a tile sheet.
+pub const TILES_PER_ROW: usize = 16;
+
+const TILE_SIZE: usize = 8;
+
+/// An RGBA image of every tile in VRAM, in tile data order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TileSheet {
+    pub width: usize,
+    pub height: usize,
+    /// RGBA pixels in row-major order.
+    pub pixels: Vec<u8>,
+}
+
+impl Ppu {
+    /// Decodes the tile data into a sheet of 16 tiles per row, shading color
+    /// indices 0–3 from white to black regardless of the palettes. In CGB mode
+    /// the tiles of VRAM bank 1 follow those of bank 0.
+    pub fn tile_sheet(&self) -> TileSheet {
+        let banks = if self.cgb { 2 } else { 1 };
+        let tile_count = TILES_PER_BANK * banks;
+        let width = TILES_PER_ROW * TILE_SIZE;
+        let height = tile_count.div_ceil(TILES_PER_ROW) * TILE_SIZE;
+
+        let mut pixels = vec![0xFF; width * height * 4];
+        for tile in 0..tile_count {
+            let tile_addr = (tile / TILES_PER_BANK) * VRAM_BANK_SIZE + (tile % TILES_PER_BANK) * 16;
+            let left = (tile % TILES_PER_ROW) * TILE_SIZE;
+            let top = (tile / TILES_PER_ROW) * TILE_SIZE;
+            for y in 0..TILE_SIZE {
+                for x in 0..TILE_SIZE {
+                    let color = self.tile_data_pixel(tile_addr, x as u8, y as u8);
+                    let offset = ((top + y) * width + left + x) * 4;
+                    pixels[offset..offset + 4].copy_from_slice(&DMG_SHADES[color as usize]);
+                }
+            }
+        }
+        TileSheet { width, height, pixels }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns pixel (`x`, `y`) of `sheet`.
+    fn pixel(sheet: &TileSheet, x: usize, y: usize) -> [u8; 4] {
+        let offset = (y * sheet.width + x) * 4;
+        sheet.pixels[offset..offset + 4].try_into().unwrap()
+    }
+
+    #[test]
+    fn dmg_sheet_holds_one_bank() {
+        let sheet = Ppu::new(false).tile_sheet();
+
+        assert_eq!((sheet.width, sheet.height), (128, 192));
+        assert_eq!(sheet.pixels.len(), 128 * 192 * 4);
+    }
+
+    #[test]
+    fn cgb_sheet_holds_both_banks() {
+        let sheet = Ppu::new(true).tile_sheet();
+
+        assert_eq!((sheet.width, sheet.height), (128, 384));
+        assert_eq!(sheet.pixels.len(), 128 * 384 * 4);
+    }
+
+    #[test]
+    fn tiles_are_laid_out_16_per_row() {
+        let mut ppu = Ppu::new(true);
+        // Tile 17 of bank 0: a black first row
+        ppu.write(0x8110, 0xFF);
+        ppu.write(0x8111, 0xFF);
+        // Tile 0 of bank 1: a light gray first pixel
+        ppu.write(0xFF4F, 0x01);
+        ppu.write(0x8000, 0x80);
+
+        let sheet = ppu.tile_sheet();
+        assert_eq!(pixel(&sheet, 8, 8), DMG_SHADES[3]);
+        assert_eq!(pixel(&sheet, 15, 8), DMG_SHADES[3]);
+        assert_eq!(pixel(&sheet, 8, 9), DMG_SHADES[0]);
+        assert_eq!(pixel(&sheet, 0, 192), DMG_SHADES[1]);
+    }
+}
@@ -0,0 +1,242 @@
+//! Recording joypad input to `.gbi` files and playing it back, for
+//! reproducible runs.
+//!
+//! A `.gbi` file is text: a header line, then one event per line giving the
+//! frame it happens on, counted from the start of the recording, the button
+//! and whether it is pressed or released:
+//!
+//! ```text
+//! # rusty_boy input recording v1
+//! 120 Start press
+//! 124 Start release
+//! ```
+//!
+//! Emulation is deterministic, so playing a recording back from the same
+//! starting state (e.g. right after [`Emulator::new`] or [`Emulator::reset`],
+//! or after loading the same save state) reproduces the run exactly.
+//!
+//! [`Emulator::new`]: crate::emulator::Emulator::new
+//! [`Emulator::reset`]: crate::emulator::Emulator::reset
+
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::joypad::Button;
+
+/// First line of every `.gbi` file.
+const HEADER: &str = "# rusty_boy input recording v1";
+
+/// A button changing state at the start of a frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InputEvent {
+    pub frame: u64,
+    pub button: Button,
+    pub pressed: bool,
+}
+
+/// Appends input events to a `.gbi` file as they happen.
+pub struct Recorder {
+    writer: BufWriter<File>,
+    /// Frames completed since recording started.
+    frame: u64,
+}
+
+impl Recorder {
+    /// Creates or truncates the recording at `path`.
+    pub fn create(path: &Path) -> io::Result<Recorder> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writeln!(writer, "{}", HEADER)?;
+        Ok(Recorder { writer, frame: 0 })
+    }
+
+    pub(crate) fn record(&mut self, button: Button, pressed: bool) -> io::Result<()> {
+        let action = if pressed { "press" } else { "release" };
+        writeln!(self.writer, "{} {:?} {}", self.frame, button, action)
+    }
+
+    pub(crate) fn frame_completed(&mut self) {
+        self.frame += 1;
+    }
+
+    /// Flushes the recording to disk.
+    pub fn finish(mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// Feeds the events of a recording back, frame by frame.
+pub struct Player {
+    events: Vec<InputEvent>,
+    /// Index of the first event not played yet.
+    next: usize,
+    /// Frames completed since playback started.
+    frame: u64,
+}
+
+impl Player {
+    /// Loads the recording at `path`.
+    pub fn open(path: &Path) -> io::Result<Player> {
+        Player::parse(&fs::read_to_string(path)?)
+    }
+
+    /// Parses the contents of a `.gbi` file. Events must be in frame order.
+    pub fn parse(content: &str) -> io::Result<Player> {
+        let mut lines = content.lines().enumerate();
+        if lines.next().map(|(_, line)| line.trim()) != Some(HEADER) {
+            return Err(invalid_data("not an input recording".to_string()));
+        }
+
+        let mut events: Vec<InputEvent> = Vec::new();
+        for (index, line) in lines {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let event = parse_event(line).ok_or_else(|| invalid_data(format!("line {}: invalid event \"{}\"", index + 1, line)))?;
+            if events.last().is_some_and(|last| last.frame > event.frame) {
+                return Err(invalid_data(format!("line {}: events are out of frame order", index + 1)));
+            }
+            events.push(event);
+        }
+        Ok(Player { events, next: 0, frame: 0 })
+    }
+
+    pub fn events(&self) -> &[InputEvent] {
+        &self.events
+    }
+
+    /// Returns `true` once every event has been played.
+    pub fn is_finished(&self) -> bool {
+        self.next == self.events.len()
+    }
+
+    /// Returns the events due at the start of the current frame, marking
+    /// them played.
+    pub(crate) fn due_events(&mut self) -> &[InputEvent] {
+        let start = self.next;
+        while self.next < self.events.len() && self.events[self.next].frame <= self.frame {
+            self.next += 1;
+        }
+        &self.events[start..self.next]
+    }
+
+    pub(crate) fn frame_completed(&mut self) {
+        self.frame += 1;
+    }
+}
+
+/// Parses an event line such as `120 Start press`.
+fn parse_event(line: &str) -> Option<InputEvent> {
+    let mut words = line.split_whitespace();
+    let frame = words.next()?.parse().ok()?;
+    let name = words.next()?;
+    let button = Button::ALL.into_iter().find(|button| format!("{:?}", button) == name)?;
+    let pressed = match words.next()? {
+        "press" => true,
+        "release" => false,
+        _ => return None,
+    };
+    words.next().is_none().then_some(InputEvent { frame, button, pressed })
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+    use crate::emulator::Emulator;
+    use crate::testing::{SHOW_BUTTONS, program_rom};
+
+    /// Input applied before each frame of the recorded run.
+    const INPUT: [&[(Button, bool)]; 8] = [
+        &[],
+        &[(Button::A, true)],
+        &[],
+        &[(Button::Start, true)],
+        &[(Button::A, false)],
+        &[],
+        &[(Button::Start, false), (Button::B, true)],
+        &[(Button::B, false)],
+    ];
+
+    fn emulator() -> Emulator {
+        Emulator::new(program_rom(&SHOW_BUTTONS)).unwrap()
+    }
+
+    #[test]
+    fn playback_reproduces_the_recorded_frames() {
+        let path = env::temp_dir().join(format!("rusty_boy_recording_{}.gbi", std::process::id()));
+        let mut recorded = emulator();
+        recorded.start_recording(&path).unwrap();
+        let expected: Vec<Vec<u8>> = INPUT
+            .iter()
+            .map(|events| {
+                for &(button, pressed) in *events {
+                    if pressed {
+                        recorded.press(button);
+                    } else {
+                        recorded.release(button);
+                    }
+                }
+                recorded.run_frames(1).to_vec()
+            })
+            .collect();
+        recorded.stop_recording().unwrap();
+
+        let mut replayed = emulator();
+        replayed.play_recording(&path).unwrap();
+        // Live input is ignored during playback
+        replayed.press(Button::Select);
+        let frames: Vec<Vec<u8>> = INPUT.iter().map(|_| replayed.run_frames(1).to_vec()).collect();
+        fs::remove_file(&path).unwrap();
+
+        assert_ne!(expected[0], expected[2]);
+        assert_ne!(expected[2], expected[4]);
+        assert!(frames == expected);
+        assert!(!replayed.is_playing());
+    }
+
+    #[test]
+    fn parses_events_comments_and_blank_lines() {
+        let player = Player::parse(&format!("{}\n\n# note\n3 A press\n  3 Start press  \n7 A release\n", HEADER)).unwrap();
+
+        let events = [(3, Button::A, true), (3, Button::Start, true), (7, Button::A, false)]
+            .map(|(frame, button, pressed)| InputEvent { frame, button, pressed });
+        assert_eq!(player.events(), events);
+    }
+
+    /// Asserts that parsing `content` fails with a message starting with
+    /// `message`.
+    fn assert_rejected(content: &str, message: &str) {
+        let error = Player::parse(content).err().unwrap_or_else(|| panic!("{:?} parsed", content));
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(error.to_string().starts_with(message), "{:?}: {}", content, error);
+    }
+
+    #[test]
+    fn rejects_a_missing_or_unknown_header() {
+        for content in ["", "1 A press\n", "# rusty_boy input recording v2\n1 A press\n"] {
+            assert_rejected(content, "not an input recording");
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_events() {
+        for line in ["x A press", "-1 A press", "1 Turbo press", "1 a press", "1 A hold", "1 A", "1 A press now"] {
+            assert_rejected(&format!("{}\n{}\n", HEADER, line), &format!("line 2: invalid event \"{}\"", line));
+        }
+        assert_rejected(&format!("{}\n5 A press\n4 A release\n", HEADER), "line 3: events are out of frame order");
+    }
+
+    #[test]
+    fn missing_file_is_an_error() {
+        let path = env::temp_dir().join("rusty_boy_missing_recording.gbi");
+
+        assert_eq!(Player::open(&path).err().map(|e| e.kind()), Some(io::ErrorKind::NotFound));
+    }
+}
@@ -0,0 +1,137 @@
+//! A ring buffer of save states for rewinding gameplay.
+
+use std::collections::VecDeque;
+
+/// Default cap on the memory held by rewind snapshots.
+pub const DEFAULT_MAX_BYTES: usize = 64 * 1024 * 1024;
+
+/// Snapshots taken every `interval` frames, newest last. Once `depth`
+/// snapshots or `max_bytes` bytes are held, the oldest ones are dropped.
+pub struct Rewind {
+    interval: u32,
+    depth: usize,
+    max_bytes: usize,
+    snapshots: VecDeque<Vec<u8>>,
+    /// Total size of `snapshots`.
+    bytes: usize,
+    frames_since_snapshot: u32,
+}
+
+impl Rewind {
+    pub fn new(interval: u32, depth: usize, max_bytes: usize) -> Rewind {
+        Rewind {
+            interval: interval.max(1),
+            depth,
+            max_bytes,
+            snapshots: VecDeque::new(),
+            bytes: 0,
+            frames_since_snapshot: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    /// Returns the memory held by the snapshots, in bytes.
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    /// Counts a frame. Returns `true` when a snapshot is due.
+    pub(crate) fn frame_completed(&mut self) -> bool {
+        self.frames_since_snapshot += 1;
+        if self.frames_since_snapshot < self.interval {
+            return false;
+        }
+        self.frames_since_snapshot = 0;
+        true
+    }
+
+    pub(crate) fn push(&mut self, snapshot: Vec<u8>) {
+        self.bytes += snapshot.len();
+        self.snapshots.push_back(snapshot);
+        while self.snapshots.len() > self.depth || (self.bytes > self.max_bytes && self.snapshots.len() > 1) {
+            if let Some(oldest) = self.snapshots.pop_front() {
+                self.bytes -= oldest.len();
+            }
+        }
+    }
+
+    /// Removes and returns the newest snapshot.
+    pub(crate) fn pop(&mut self) -> Option<Vec<u8>> {
+        let snapshot = self.snapshots.pop_back()?;
+        self.bytes -= snapshot.len();
+        // Count the next interval from the restored point
+        self.frames_since_snapshot = 0;
+        Some(snapshot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the first byte of every snapshot, oldest first.
+    fn ids(rewind: &Rewind) -> Vec<u8> {
+        rewind.snapshots.iter().map(|snapshot| snapshot[0]).collect()
+    }
+
+    #[test]
+    fn drops_the_oldest_snapshot_past_depth() {
+        let mut rewind = Rewind::new(1, 3, DEFAULT_MAX_BYTES);
+        for id in 0..5 {
+            rewind.push(vec![id; 10]);
+        }
+
+        assert_eq!(ids(&rewind), [2, 3, 4]);
+        assert_eq!(rewind.bytes(), 30);
+    }
+
+    #[test]
+    fn drops_the_oldest_snapshots_past_max_bytes() {
+        let mut rewind = Rewind::new(1, 10, 25);
+        rewind.push(vec![0; 10]);
+        rewind.push(vec![1; 10]);
+        assert_eq!(ids(&rewind), [0, 1]);
+
+        rewind.push(vec![2; 15]);
+        assert_eq!(ids(&rewind), [1, 2]);
+        assert_eq!(rewind.bytes(), 25);
+    }
+
+    #[test]
+    fn keeps_the_newest_snapshot_even_past_max_bytes() {
+        let mut rewind = Rewind::new(1, 10, 25);
+        rewind.push(vec![0; 10]);
+        rewind.push(vec![1; 40]);
+
+        assert_eq!(ids(&rewind), [1]);
+        assert_eq!(rewind.bytes(), 40);
+    }
+
+    #[test]
+    fn pop_returns_the_newest_snapshot() {
+        let mut rewind = Rewind::new(1, 10, DEFAULT_MAX_BYTES);
+        rewind.push(vec![0; 10]);
+        rewind.push(vec![1; 20]);
+
+        assert_eq!(rewind.pop(), Some(vec![1; 20]));
+        assert_eq!(rewind.bytes(), 10);
+        assert_eq!(rewind.pop(), Some(vec![0; 10]));
+        assert_eq!(rewind.pop(), None);
+        assert!(rewind.is_empty());
+    }
+
+    #[test]
+    fn snapshot_is_due_every_interval() {
+        let mut rewind = Rewind::new(3, 10, DEFAULT_MAX_BYTES);
+        let due: Vec<bool> = (0..6).map(|_| rewind.frame_completed()).collect();
+
+        assert_eq!(due, [false, false, true, false, false, true]);
+    }
+}
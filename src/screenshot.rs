@@ -0,0 +1,174 @@
+//! Saving framebuffers as PNG images and comparing them with reference
+//! screenshots, to catch rendering regressions.
+
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter};
+use std::path::Path;
+
+use crate::ppu::{SCREEN_HEIGHT, SCREEN_WIDTH};
+
+/// Writes a 160×144 RGBA `framebuffer` to `path` as a PNG image.
+pub fn write_png(path: &Path, framebuffer: &[u8]) -> io::Result<()> {
+    write_rgba_png(path, SCREEN_WIDTH, SCREEN_HEIGHT, framebuffer)
+}
+
+/// Writes a `width`×`height` image of RGBA `pixels` to `path` as a PNG
+/// image.
+pub fn write_rgba_png(path: &Path, width: usize, height: usize, pixels: &[u8]) -> io::Result<()> {
+    let writer = BufWriter::new(File::create(path)?);
+    let mut encoder = png::Encoder::new(writer, width as u32, height as u32);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let mut writer = encoder.write_header().map_err(io::Error::other)?;
+    writer.write_image_data(pixels).map_err(io::Error::other)?;
+    writer.finish().map_err(io::Error::other)
+}
+
+/// The area of a framebuffer that differs from a reference image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bounds {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+/// The result of comparing a framebuffer with a reference image.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffResult {
+    pub differing_pixels: usize,
+    /// The smallest rectangle containing every differing pixel, or `None`
+    /// when the images are identical.
+    pub bounds: Option<Bounds>,
+}
+
+impl DiffResult {
+    /// Returns `true` if at most `tolerance` pixels differ.
+    pub fn within(&self, tolerance: usize) -> bool {
+        self.differing_pixels <= tolerance
+    }
+}
+
+/// Compares a 160×144 RGBA `actual` framebuffer with the PNG at
+/// `expected_png`, pixel by pixel. The reference must have the screen size;
+/// RGB, grayscale and palette images are accepted. A framebuffer of any
+/// other size is rejected with [`io::ErrorKind::InvalidInput`].
+pub fn compare_framebuffer(actual: &[u8], expected_png: &Path) -> io::Result<DiffResult> {
+    if actual.len() != SCREEN_WIDTH * SCREEN_HEIGHT * 4 {
+        let message = format!("framebuffer is {} bytes, not {}×{} RGBA", actual.len(), SCREEN_WIDTH, SCREEN_HEIGHT);
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, message));
+    }
+    let expected = read_png(expected_png)?;
+
+    let mut differing_pixels = 0;
+    let mut bounds: Option<(usize, usize, usize, usize)> = None;
+    let pixels = actual.chunks_exact(4).zip(expected.chunks_exact(4));
+    for (i, (actual, expected)) in pixels.enumerate() {
+        if actual == expected {
+            continue;
+        }
+        differing_pixels += 1;
+        let (x, y) = (i % SCREEN_WIDTH, i / SCREEN_WIDTH);
+        bounds = Some(match bounds {
+            Some((left, top, right, bottom)) => (left.min(x), top.min(y), right.max(x), bottom.max(y)),
+            None => (x, y, x, y),
+        });
+    }
+
+    let bounds = bounds.map(|(left, top, right, bottom)| Bounds {
+        x: left,
+        y: top,
+        width: right - left + 1,
+        height: bottom - top + 1,
+    });
+    Ok(DiffResult { differing_pixels, bounds })
+}
+
+/// Decodes the PNG at `path` into 160×144 RGBA pixels.
+fn read_png(path: &Path) -> io::Result<Vec<u8>> {
+    let mut decoder = png::Decoder::new(BufReader::new(File::open(path)?));
+    decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+    let mut reader = decoder.read_info().map_err(io::Error::other)?;
+    let mut buffer = vec![0; reader.output_buffer_size().unwrap_or(0)];
+    let info = reader.next_frame(&mut buffer).map_err(io::Error::other)?;
+
+    if info.width as usize != SCREEN_WIDTH || info.height as usize != SCREEN_HEIGHT {
+        let message = format!("{} is {}×{}, not the screen size", path.display(), info.width, info.height);
+        return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+    }
+
+    let pixels = &buffer[..info.buffer_size()];
+    let rgba = match info.color_type {
+        png::ColorType::Rgba => pixels.to_vec(),
+        png::ColorType::Rgb => pixels.chunks_exact(3).flat_map(|p| [p[0], p[1], p[2], 0xFF]).collect(),
+        png::ColorType::GrayscaleAlpha => pixels.chunks_exact(2).flat_map(|p| [p[0], p[0], p[0], p[1]]).collect(),
+        png::ColorType::Grayscale => pixels.iter().flat_map(|&v| [v, v, v, 0xFF]).collect(),
+        png::ColorType::Indexed => unreachable!("EXPAND converts palette images to RGB"),
+    };
+    Ok(rgba)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::emulator::Emulator;
+
+    /// Returns the path of `name` in the `testdata` directory.
+    fn testdata(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata").join(name)
+    }
+
+    fn checkerboard_frame() -> Vec<u8> {
+        let rom = fs::read(testdata("checkerboard.gb")).unwrap();
+        Emulator::new(rom).unwrap().run_frames(10).to_vec()
+    }
+
+    #[test]
+    fn checkerboard_matches_its_reference() {
+        let diff = compare_framebuffer(&checkerboard_frame(), &testdata("checkerboard.png")).unwrap();
+
+        assert_eq!(diff, DiffResult { differing_pixels: 0, bounds: None });
+    }
+
+    #[test]
+    fn reports_the_bounds_of_differing_pixels() {
+        let mut frame = checkerboard_frame();
+        for (x, y) in [(3, 10), (40, 2)] {
+            let offset = (y * SCREEN_WIDTH + x) * 4;
+            frame[offset] ^= 0xFF;
+        }
+
+        let diff = compare_framebuffer(&frame, &testdata("checkerboard.png")).unwrap();
+        assert_eq!(diff.differing_pixels, 2);
+        assert_eq!(diff.bounds, Some(Bounds { x: 3, y: 2, width: 38, height: 9 }));
+        assert!(diff.within(2) && !diff.within(1));
+    }
+
+    #[test]
+    fn rejects_framebuffers_of_the_wrong_size() {
+        let frame = checkerboard_frame();
+
+        for len in [0, frame.len() - 4, frame.len() + 4] {
+            let mut actual = frame.clone();
+            actual.resize(len, 0xFF);
+            let error = compare_framebuffer(&actual, &testdata("checkerboard.png")).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidInput, "{} bytes", len);
+        }
+    }
+
+    #[test]
+    fn round_trips_through_write_png() {
+        let frame = checkerboard_frame();
+        let path = env::temp_dir().join(format!("rusty_boy_screenshot_{}.png", std::process::id()));
+
+        write_png(&path, &frame).unwrap();
+        let diff = compare_framebuffer(&frame, &path);
+        fs::remove_file(&path).unwrap();
+        assert!(diff.unwrap().within(0));
+    }
+}
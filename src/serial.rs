@@ -0,0 +1,123 @@
+//! The serial port: SB (`0xFF01`) and SC (`0xFF02`).
+//!
+//! No link partner is emulated, so every bit shifted in is 1 and a transfer
+//! leaves SB at `0xFF`. Each byte shifted out is handed to an optional
+//! callback, which is how test ROMs such as blargg's report their results.
+
+use serde::{Deserialize, Serialize};
+
+/// Receives every byte sent over the serial port.
+pub type SerialCallback = Box<dyn FnMut(u8) + Send>;
+
+const SC_TRANSFER: u8 = 0x80;
+const SC_INTERNAL_CLOCK: u8 = 0x01;
+
+/// M-cycles per bit with the internal 8192 Hz clock.
+const CYCLES_PER_BIT: u16 = 128;
+
+#[derive(Default, Serialize, Deserialize)]
+pub struct Serial {
+    sb: u8,
+    sc: u8,
+    /// M-cycles until the running transfer completes.
+    remaining: u16,
+    /// Not part of save states; loading one keeps the current callback.
+    #[serde(skip)]
+    callback: Option<SerialCallback>,
+}
+
+impl Serial {
+    pub fn new() -> Serial {
+        Serial::default()
+    }
+
+    pub fn set_callback(&mut self, callback: SerialCallback) {
+        self.callback = Some(callback);
+    }
+
+    pub(crate) fn take_callback(&mut self) -> Option<SerialCallback> {
+        self.callback.take()
+    }
+
+    /// Advances the port by one M-cycle. Returns `true` when a Serial
+    /// interrupt should be requested.
+    pub fn tick(&mut self) -> bool {
+        if self.remaining == 0 {
+            return false;
+        }
+
+        self.remaining -= 1;
+        if self.remaining > 0 {
+            return false;
+        }
+
+        if let Some(callback) = &mut self.callback {
+            callback(self.sb);
+        }
+        self.sb = 0xFF;
+        self.sc &= !SC_TRANSFER;
+        true
+    }
+
+    pub fn read(&self, addr: u16) -> u8 {
+        match addr {
+            0xFF01 => self.sb,
+            // Only bits 7 and 0 are used on the DMG
+            _ => 0x7E | self.sc,
+        }
+    }
+
+    pub fn write(&mut self, addr: u16, value: u8) {
+        match addr {
+            0xFF01 => self.sb = value,
+            _ => {
+                self.sc = value & (SC_TRANSFER | SC_INTERNAL_CLOCK);
+                // With the external clock the transfer waits for a partner
+                // that never comes
+                self.remaining = if self.sc == SC_TRANSFER | SC_INTERNAL_CLOCK { CYCLES_PER_BIT * 8 } else { 0 };
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::interrupt::Interrupt;
+    use crate::testing::program_bus;
+
+    #[test]
+    fn transfer_sends_sb_and_requests_an_interrupt() {
+        let mut bus = program_bus(&[]);
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&sent);
+        bus.serial_mut().set_callback(Box::new(move |byte| sink.lock().unwrap().push(byte)));
+        bus.write(0xFF0F, 0x00);
+
+        bus.write(0xFF01, b'P');
+        bus.write(0xFF02, 0x81);
+        for _ in 0..CYCLES_PER_BIT * 8 - 1 {
+            bus.tick();
+        }
+        assert!(sent.lock().unwrap().is_empty());
+        assert_eq!(bus.read(0xFF02), 0xFF);
+        bus.tick();
+
+        assert_eq!(*sent.lock().unwrap(), [b'P']);
+        assert_eq!(bus.read(0xFF0F) & Interrupt::Serial.bit(), Interrupt::Serial.bit());
+        assert_eq!(bus.read(0xFF01), 0xFF);
+        assert_eq!(bus.read(0xFF02), 0x7F);
+    }
+
+    #[test]
+    fn external_clock_never_completes() {
+        let mut serial = Serial::new();
+        serial.write(0xFF01, 0x42);
+        serial.write(0xFF02, 0x80);
+
+        assert!((0..CYCLES_PER_BIT * 16).all(|_| !serial.tick()));
+        assert_eq!(serial.read(0xFF01), 0x42);
+    }
+}
@@ -0,0 +1,119 @@
+//! Save state format helpers.
+//!
+//! A save state is a [`StateHeader`] followed by the CPU, the bus and the
+//! cartridge state, all encoded with `postcard`. The ROM itself is not
+//! stored: a state can only be loaded into an emulator running the same
+//! cartridge, which the header records.
+
+use std::fmt;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::cartridge::header::CartridgeHeader;
+
+/// Identifies Rusty Boy save states.
+const STATE_MAGIC: [u8; 4] = *b"RBST";
+
+/// Bumped whenever the layout of any serialized component changes, so
+/// states from other versions are rejected instead of misread.
+pub const STATE_VERSION: u16 = 7;
+
+/// Errors that can occur while loading a save state.
+#[derive(Debug)]
+pub enum StateError {
+    /// The data does not start with a save state header.
+    NotAState,
+    /// The state was written by an incompatible version; holds its version.
+    UnsupportedVersion(u16),
+    /// The state was saved with a different cartridge; holds its title.
+    WrongCartridge(String),
+    /// The state is truncated or otherwise malformed.
+    Corrupt(postcard::Error),
+}
+
+impl fmt::Display for StateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StateError::NotAState => write!(f, "not a save state"),
+            StateError::UnsupportedVersion(version) => {
+                write!(f, "save state version {} is not supported (expected {})", version, STATE_VERSION)
+            }
+            StateError::WrongCartridge(title) => write!(f, "save state belongs to another cartridge ({})", title),
+            StateError::Corrupt(e) => write!(f, "corrupt save state: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for StateError {}
+
+impl From<postcard::Error> for StateError {
+    fn from(e: postcard::Error) -> StateError {
+        StateError::Corrupt(e)
+    }
+}
+
+/// The first part of every save state, checked before decoding the rest.
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct StateHeader {
+    magic: [u8; 4],
+    version: u16,
+    title: String,
+    header_checksum: u8,
+}
+
+impl StateHeader {
+    pub(crate) fn new(cartridge: &CartridgeHeader) -> StateHeader {
+        StateHeader {
+            magic: STATE_MAGIC,
+            version: STATE_VERSION,
+            title: cartridge.title.clone(),
+            header_checksum: cartridge.header_checksum,
+        }
+    }
+
+    /// Checks that the state can be loaded into a machine running
+    /// `cartridge`.
+    pub(crate) fn validate(&self, cartridge: &CartridgeHeader) -> Result<(), StateError> {
+        if self.magic != STATE_MAGIC {
+            return Err(StateError::NotAState);
+        }
+        if self.version != STATE_VERSION {
+            return Err(StateError::UnsupportedVersion(self.version));
+        }
+        if self.title != cartridge.title || self.header_checksum != cartridge.header_checksum {
+            return Err(StateError::WrongCartridge(self.title.clone()));
+        }
+        Ok(())
+    }
+}
+
+/// Serializes fixed-size byte arrays, which serde only supports up to 32
+/// elements, as byte sequences. Use with `#[serde(with = "byte_array")]`.
+pub(crate) mod byte_array {
+    use super::*;
+
+    pub(crate) fn serialize<S: Serializer, const N: usize>(bytes: &[u8; N], serializer: S) -> Result<S::Ok, S::Error> {
+        bytes.as_slice().serialize(serializer)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>, const N: usize>(deserializer: D) -> Result<[u8; N], D::Error> {
+        let bytes = Vec::<u8>::deserialize(deserializer)?;
+        let len = bytes.len();
+        bytes.try_into().map_err(|_| serde::de::Error::invalid_length(len, &"a byte array of the expected size"))
+    }
+}
+
+/// Like [`byte_array`], for boxed arrays.
+pub(crate) mod boxed_byte_array {
+    use super::*;
+
+    pub(crate) fn serialize<S: Serializer, const N: usize>(bytes: &[u8; N], serializer: S) -> Result<S::Ok, S::Error> {
+        byte_array::serialize(bytes, serializer)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>, const N: usize>(
+        deserializer: D,
+    ) -> Result<Box<[u8; N]>, D::Error> {
+        byte_array::deserialize(deserializer).map(Box::new)
+    }
+}
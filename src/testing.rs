@@ -0,0 +1,54 @@
+//! Helpers shared by the unit tests.
+
+use crate::bus::Bus;
+use crate::cartridge::Cartridge;
+use crate::cartridge::mbc::ROM_BANK_SIZE;
+
+/// Where [`program_rom`] places its program.
+pub(crate) const PROGRAM_START: u16 = 0x0150;
+
+const CARTRIDGE_TYPE: usize = 0x0147;
+const ROM_SIZE: usize = 0x0148;
+const RAM_SIZE: usize = 0x0149;
+const HEADER_CHECKSUM: usize = 0x014D;
+
+/// Returns an image of `banks` ROM banks with a valid header for
+/// `cartridge_type` and RAM size code `ram_size`. The entry point jumps to
+/// [`PROGRAM_START`].
+pub(crate) fn rom(cartridge_type: u8, banks: usize, ram_size: u8) -> Vec<u8> {
+    let mut rom = vec![0; banks * ROM_BANK_SIZE];
+    // NOP; JP PROGRAM_START
+    let [low, high] = PROGRAM_START.to_le_bytes();
+    rom[0x0100..0x0104].copy_from_slice(&[0x00, 0xC3, low, high]);
+    rom[0x0134..0x0138].copy_from_slice(b"TEST");
+    rom[CARTRIDGE_TYPE] = cartridge_type;
+    rom[ROM_SIZE] = (banks / 2).trailing_zeros() as u8;
+    rom[RAM_SIZE] = ram_size;
+    rom[HEADER_CHECKSUM] = rom[0x0134..HEADER_CHECKSUM]
+        .iter()
+        .fold(0u8, |sum, &byte| sum.wrapping_sub(byte).wrapping_sub(1));
+    rom
+}
+
+/// Returns a 32 KiB ROM-only image running `program` from [`PROGRAM_START`].
+pub(crate) fn program_rom(program: &[u8]) -> Vec<u8> {
+    let mut rom = rom(0x00, 2, 0x00);
+    let start = PROGRAM_START as usize;
+    rom[start..start + program.len()].copy_from_slice(program);
+    rom
+}
+
+/// A [`program_rom`] program that copies the action buttons of P1 into BGP
+/// in a loop, so the frames show which buttons are held.
+pub(crate) const SHOW_BUTTONS: [u8; 10] = [
+    0x3E, 0x10, // 0x0150: LD A,0x10
+    0xE0, 0x00, // LDH (P1),A
+    0xF0, 0x00, // LDH A,(P1)
+    0xE0, 0x47, // LDH (BGP),A
+    0x18, 0xF6, // JR 0x0150
+];
+
+/// Returns a bus with a [`program_rom`] inserted.
+pub(crate) fn program_bus(program: &[u8]) -> Bus {
+    Bus::new(Cartridge::new(program_rom(program)).expect("valid test ROM"))
+}
@@ -0,0 +1,178 @@
+//! The timer: DIV (`0xFF04`), TIMA (`0xFF05`), TMA (`0xFF06`) and TAC
+//! (`0xFF07`).
+//!
+//! Both DIV and TIMA are driven by a 16-bit counter incremented every clock
+//! tick, of which DIV is the upper byte. TIMA increments on the falling edge
+//! of the counter bit selected by TAC (ANDed with the TAC enable bit), which
+//! is why writing DIV or TAC can increment TIMA as a side effect. See the
+//! "Timer obscure behaviour" chapter of Pan Docs.
+
+use serde::{Deserialize, Serialize};
+
+/// Counter bit whose falling edge increments TIMA, per TAC clock select:
+/// 4096 Hz, 262144 Hz, 65536 Hz and 16384 Hz.
+const TAC_COUNTER_BITS: [u16; 4] = [9, 3, 5, 7];
+
+const TAC_ENABLE: u8 = 0x04;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Timer {
+    /// Internal counter, incremented every clock tick (4 per M-cycle).
+    counter: u16,
+    tima: u8,
+    tma: u8,
+    tac: u8,
+    /// TIMA overflowed during the last M-cycle; it reads as 0 until the
+    /// reload from TMA happens at the end of the next one.
+    overflowed: bool,
+    /// TIMA was reloaded from TMA during the current M-cycle, so writes to
+    /// TIMA are ignored and writes to TMA also land in TIMA.
+    reloaded: bool,
+}
+
+impl Timer {
+    pub fn new() -> Timer {
+        Timer::default()
+    }
+
+    /// Sets the internal counter without the side effects of a DIV write,
+    /// to start from the value the boot ROM leaves behind.
+    pub(crate) fn set_counter(&mut self, counter: u16) {
+        self.counter = counter;
+    }
+
+    /// Advances the timer by one M-cycle. Returns `true` when a Timer
+    /// interrupt should be requested.
+    pub fn tick(&mut self) -> bool {
+        self.reloaded = false;
+        let mut interrupt = false;
+        if self.overflowed {
+            self.overflowed = false;
+            self.reloaded = true;
+            self.tima = self.tma;
+            interrupt = true;
+        }
+
+        let before = self.input();
+        self.counter = self.counter.wrapping_add(4);
+        self.detect_falling_edge(before);
+        interrupt
+    }
+
+    pub fn read(&self, addr: u16) -> u8 {
+        match addr {
+            0xFF04 => (self.counter >> 8) as u8,
+            0xFF05 => self.tima,
+            0xFF06 => self.tma,
+            _ => 0xF8 | self.tac,
+        }
+    }
+
+    pub fn write(&mut self, addr: u16, value: u8) {
+        match addr {
+            0xFF04 => {
+                let before = self.input();
+                self.counter = 0;
+                self.detect_falling_edge(before);
+            }
+            0xFF05 => {
+                if !self.reloaded {
+                    self.tima = value;
+                    // Writing during the overflow cycle cancels the reload
+                    self.overflowed = false;
+                }
+            }
+            0xFF06 => {
+                self.tma = value;
+                if self.reloaded {
+                    self.tima = value;
+                }
+            }
+            _ => {
+                let before = self.input();
+                self.tac = value & 0x07;
+                self.detect_falling_edge(before);
+            }
+        }
+    }
+
+    /// Returns the signal feeding TIMA: the selected counter bit ANDed with
+    /// the enable bit.
+    fn input(&self) -> bool {
+        let bit = TAC_COUNTER_BITS[(self.tac & 0x03) as usize];
+        self.tac & TAC_ENABLE != 0 && self.counter & (1 << bit) != 0
+    }
+
+    fn detect_falling_edge(&mut self, before: bool) {
+        if before && !self.input() {
+            let (tima, overflow) = self.tima.overflowing_add(1);
+            self.tima = tima;
+            self.overflowed |= overflow;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interrupt::Interrupt;
+    use crate::testing::program_bus;
+
+    /// Returns a timer with TIMA one increment away from overflowing, counting
+    /// every 4 M-cycles.
+    fn about_to_overflow() -> Timer {
+        let mut timer = Timer::new();
+        timer.write(0xFF06, 0x42);
+        timer.write(0xFF05, 0xFF);
+        timer.write(0xFF07, TAC_ENABLE | 0x01);
+        timer
+    }
+
+    /// Ticks until TIMA overflows, returning whether any tick requested an
+    /// interrupt on the way.
+    fn tick_until_overflow(timer: &mut Timer) -> bool {
+        let mut interrupt = false;
+        for _ in 0..4 {
+            interrupt |= timer.tick();
+        }
+        interrupt
+    }
+
+    #[test]
+    fn tma_is_reloaded_one_cycle_after_overflow() {
+        let mut timer = about_to_overflow();
+        assert!(!tick_until_overflow(&mut timer));
+        // TIMA reads 0 for the M-cycle after the overflow
+        assert_eq!(timer.read(0xFF05), 0x00);
+
+        assert!(timer.tick());
+        assert_eq!(timer.read(0xFF05), 0x42);
+        assert!(!timer.tick());
+    }
+
+    #[test]
+    fn writing_tima_during_the_overflow_cycle_cancels_the_reload() {
+        let mut timer = about_to_overflow();
+        tick_until_overflow(&mut timer);
+        timer.write(0xFF05, 0x10);
+
+        assert!(!timer.tick());
+        assert_eq!(timer.read(0xFF05), 0x10);
+    }
+
+    #[test]
+    fn overflow_requests_the_timer_interrupt() {
+        let mut bus = program_bus(&[]);
+        bus.write(0xFF06, 0x42);
+        bus.write(0xFF05, 0xFF);
+        bus.write(0xFF07, TAC_ENABLE | 0x01);
+
+        for _ in 0..4 {
+            bus.tick();
+        }
+        assert_eq!(bus.read(0xFF0F) & Interrupt::Timer.bit(), 0);
+        bus.tick();
+        assert_ne!(bus.read(0xFF0F) & Interrupt::Timer.bit(), 0);
+        assert_eq!(bus.read(0xFF05), 0x42);
+    }
+}
@@ -0,0 +1,81 @@
+//! JavaScript bindings for running the emulator in a browser, enabled by the
+//! `wasm` feature.
+//!
+//! Build the package with [wasm-pack](https://rustwasm.github.io/wasm-pack/):
+//!
+//! ```text
+//! wasm-pack build --target web -- --features wasm
+//! ```
+//!
+//! The bindings only expose the emulator core. Frame pacing, save files and
+//! input recordings rely on the host clock and file system, so the page
+//! drives frames itself, e.g. from `requestAnimationFrame`.
+//!
+//! The bindings are plain Rust on other targets, so `cargo test --features
+//! wasm` exercises them on the host.
+
+use wasm_bindgen::prelude::*;
+
+use crate::emulator::Emulator;
+use crate::joypad::Button;
+
+#[wasm_bindgen]
+pub struct WasmEmulator {
+    emulator: Emulator,
+}
+
+#[wasm_bindgen]
+impl WasmEmulator {
+    /// Powers on the emulator with `rom` inserted.
+    #[wasm_bindgen(constructor)]
+    pub fn new(rom: &[u8]) -> Result<WasmEmulator, JsError> {
+        let emulator = Emulator::new(rom.to_vec())?;
+        Ok(WasmEmulator { emulator })
+    }
+
+    /// Runs until the PPU finishes the next frame.
+    #[wasm_bindgen(js_name = stepFrame)]
+    pub fn step_frame(&mut self) {
+        self.emulator.run_frame();
+    }
+
+    /// The last rendered frame, as 160×144 RGBA pixels ready for an
+    /// `ImageData`.
+    pub fn framebuffer(&self) -> Vec<u8> {
+        self.emulator.framebuffer().to_vec()
+    }
+
+    pub fn press(&mut self, button: Button) {
+        self.emulator.press(button);
+    }
+
+    pub fn release(&mut self, button: Button) {
+        self.emulator.release(button);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ppu::{SCREEN_HEIGHT, SCREEN_WIDTH};
+    use crate::testing::{SHOW_BUTTONS, program_rom};
+
+    #[test]
+    fn runs_frames_and_takes_input() {
+        let mut emulator = WasmEmulator::new(&program_rom(&SHOW_BUTTONS)).unwrap();
+        emulator.step_frame();
+        let released = emulator.framebuffer();
+        assert_eq!(released.len(), SCREEN_WIDTH * SCREEN_HEIGHT * 4);
+
+        emulator.press(Button::A);
+        emulator.step_frame();
+        emulator.step_frame();
+        let pressed = emulator.framebuffer();
+        assert_ne!(pressed, released);
+
+        emulator.release(Button::A);
+        emulator.step_frame();
+        emulator.step_frame();
+        assert_eq!(emulator.framebuffer(), released);
+    }
+}